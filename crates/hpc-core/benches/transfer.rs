@@ -0,0 +1,63 @@
+// benches/transfer.rs
+//
+// Round-trip-Latenz H2D + D2H über die typisierten Helfer
+// (GpuBuffer::from_slice + read_to_vec) für 4 KiB .. 64 MiB.
+// Ohne OpenCL-GPU wird der Benchmark übersprungen (GPU-lose CI).
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use hpc_core::{GpuBuffer, Ready};
+use opencl3::{
+    command_queue::CommandQueue,
+    context::Context,
+    device::{Device, CL_DEVICE_TYPE_GPU},
+    platform::get_platforms,
+};
+
+// 4 KiB, 16 KiB, ..., 64 MiB
+const SIZES: &[usize] = &[
+    4 << 10,
+    16 << 10,
+    64 << 10,
+    256 << 10,
+    1 << 20,
+    4 << 20,
+    16 << 20,
+    64 << 20,
+];
+
+fn gpu_setup() -> Option<(Context, CommandQueue)> {
+    let platform = get_platforms().ok()?.into_iter().next()?;
+    let dev_id   = *platform.get_devices(CL_DEVICE_TYPE_GPU).ok()?.first()?;
+    let device   = Device::new(dev_id);
+    let context  = Context::from_device(&device).ok()?;
+    let queue    = CommandQueue::create(&context, device.id(), 0).ok()?;
+    Some((context, queue))
+}
+
+fn bench_transfer(c: &mut Criterion) {
+    let Some((context, queue)) = gpu_setup() else {
+        eprintln!("transfer bench: keine OpenCL-GPU gefunden – übersprungen");
+        return;
+    };
+
+    let mut group = c.benchmark_group("transfer_roundtrip");
+
+    for &bytes in SIZES {
+        let host = vec![1.0_f32; bytes / std::mem::size_of::<f32>()];
+        // Hin- und Rückweg zählen beide
+        group.throughput(Throughput::Bytes((2 * bytes) as u64));
+
+        group.bench_with_input(BenchmarkId::from_parameter(bytes), &host, |b, host| {
+            b.iter(|| {
+                let buf: GpuBuffer<Ready> = GpuBuffer::from_slice(&context, &queue, host).unwrap();
+                let back: Vec<f32> = buf.read_to_vec(&queue).unwrap();
+                criterion::black_box(back);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_transfer);
+criterion_main!(benches);
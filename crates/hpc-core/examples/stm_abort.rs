@@ -1,59 +1,71 @@
 // examples/stm_abort.rs
 //
+// CLI-Hülle um hpc_core::stm::run_stm (Workload dort, für Benches/Tests aufrufbar).
 // Deterministisch: Barrier-Sync + per-Thread RNG-Seed.
-// CLI: --threads, --conflict, (--ops ODER --duration), --seed
+// CLI: --threads, --conflict, (--ops ODER --duration), --seed, --model, --retry-budget, --backoff,
+//      --trace-format csv|json|chrome|perfetto, --trace-out <dir>
 // Default: --ops 1_000_000. Bei Angabe beider gewinnt --ops.
 // Aborts werden optional via feature "memtrace" geloggt.
+// --model uniform (Default): Konflikt mit fester Wahrscheinlichkeit je Op.
+// --model hotspot: Zipf-verteilte Adressen, Konflikt wenn ein anderer Thread
+//   dieselbe Adresse im Zeitfenster berührt hat (nicht mehr bit-deterministisch,
+//   da vom Thread-Interleaving abhängig).
+// --retry-budget N: abgebrochene Op bis zu N-mal wiederholen, danach aufgeben
+//   (cause "budget_exhausted"). Ohne Angabe: ein Abort, kein Retry (wie bisher).
+// --backoff fixed|exp|jitter: Wartezeit nach Abort (hpc_core::BackoffPolicy), Basis 10 µs,
+//   exp/jitter verdoppeln bis max. 1 ms. Default fixed (wie bisher).
+// --trace-format: csv (Default) schreibt die CSVs, json/chrome brauchen Feature "json",
+//   perfetto Feature "perfetto".
+//   Ohne Feature "memtrace" wird nichts geschrieben.
 
 use std::env;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::{
-    atomic::{AtomicBool, AtomicU64, Ordering},
-    Arc, Barrier,
-};
-use std::thread;
-use std::time::{Duration, Instant};
+use std::time::Duration;
+
+use hpc_core::stm::{run_stm, Conflict, Model, StmConfig, StmMode};
+use hpc_core::BackoffPolicy;
 
 // ---- CLI ----
 
 #[derive(Clone, Copy, Debug)]
-enum Conflict {
-    Low,
-    Med,
-    High,
+enum TraceFormat {
+    Csv,
+    Json,
+    Chrome,
+    Perfetto,
 }
-impl FromStr for Conflict {
+impl FromStr for TraceFormat {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_ascii_lowercase().as_str() {
-            "low" => Ok(Conflict::Low),
-            "med" | "medium" => Ok(Conflict::Med),
-            "high" => Ok(Conflict::High),
+            "csv" => Ok(TraceFormat::Csv),
+            "json" => Ok(TraceFormat::Json),
+            "chrome" => Ok(TraceFormat::Chrome),
+            "perfetto" => Ok(TraceFormat::Perfetto),
             _ => Err(()),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-enum Mode {
-    Ops(u64),
-    Duration(u64),
-}
-
 #[derive(Debug)]
 struct Config {
-    threads: usize,
-    conflict: Conflict,
-    mode: Mode,
-    seed: u64,
+    stm: StmConfig,
+    trace_format: TraceFormat,
+    trace_out: PathBuf,
 }
 
 fn parse_args() -> Config {
     let mut threads = 4usize;
     let mut conflict = Conflict::Low;
+    let mut model = Model::Uniform;
     let mut duration_s: Option<u64> = None;
     let mut ops: Option<u64> = None;
     let mut seed = 1u64;
+    let mut retry_budget: Option<u32> = None;
+    let mut backoff = BackoffPolicy::default();
+    let mut trace_format = TraceFormat::Csv;
+    let mut trace_out = PathBuf::from(".");
 
     let mut args = env::args().skip(1);
     while let Some(arg) = args.next() {
@@ -68,6 +80,11 @@ fn parse_args() -> Config {
                     conflict = v.parse().unwrap_or(Conflict::Low);
                 }
             }
+            "--model" => {
+                if let Some(v) = args.next() {
+                    model = v.parse().unwrap_or(Model::Uniform);
+                }
+            }
             "--duration" => {
                 if let Some(v) = args.next() {
                     duration_s = v.parse().ok();
@@ -83,44 +100,52 @@ fn parse_args() -> Config {
                     seed = v.parse().unwrap_or(1);
                 }
             }
+            "--retry-budget" => {
+                if let Some(v) = args.next() {
+                    retry_budget = v.parse().ok();
+                }
+            }
+            "--backoff" => {
+                if let Some(v) = args.next() {
+                    let (base, cap) = (Duration::from_micros(10), Duration::from_millis(1));
+                    backoff = match v.to_ascii_lowercase().as_str() {
+                        "exp" => BackoffPolicy::Exponential { base, cap },
+                        "jitter" => BackoffPolicy::Jittered { base, cap, seed: 0 },
+                        _ => BackoffPolicy::Fixed(base),
+                    };
+                }
+            }
+            "--trace-format" => {
+                if let Some(v) = args.next() {
+                    trace_format = v.parse().unwrap_or(TraceFormat::Csv);
+                }
+            }
+            "--trace-out" => {
+                if let Some(v) = args.next() {
+                    trace_out = PathBuf::from(v);
+                }
+            }
             _ => {}
         }
     }
 
     // Priorität: ops > duration > default ops
     let mode = if let Some(n) = ops {
-        Mode::Ops(n.max(1))
+        StmMode::Ops(n.max(1))
     } else if let Some(s) = duration_s {
-        Mode::Duration(s.max(1))
+        StmMode::Duration(Duration::from_secs(s.max(1)))
     } else {
-        Mode::Ops(1_000_000)
+        StmMode::Ops(1_000_000)
     };
 
-    Config { threads, conflict, mode, seed }
-}
-
-// ---- sehr einfacher, deterministischer PRNG ----
-#[derive(Clone)]
-struct XorShift64 {
-    state: u64,
-}
-impl XorShift64 {
-    fn new(seed: u64) -> Self { Self { state: seed.max(1) } }
-    fn next_u32(&mut self) -> u32 {
-        let mut x = self.state;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        self.state = x;
-        (x >> 32) as u32
-    }
-    fn next_f32(&mut self) -> f32 {
-        let v = self.next_u32();
-        (v as f32) / (u32::MAX as f32)
+    Config {
+        stm: StmConfig { threads, conflict, model, mode, seed, retry_budget, backoff, progress: true },
+        trace_format,
+        trace_out,
     }
 }
 
-// ---- Dummy-STM-Workload ----
+// ---- Dummy-STM-Workload (hpc_core::stm::run_stm) ----
 
 fn main() {
     #[cfg(feature="memtrace")]
@@ -128,163 +153,84 @@ fn main() {
     #[cfg(not(feature="memtrace"))]
     eprintln!("memtrace: DISABLED");
 
-    let cfg = parse_args();
-
-    // Konfliktwahrscheinlichkeit grob (feintuning später)
-    let p_conflict = match cfg.conflict {
-        Conflict::Low => 0.02_f32,
-        Conflict::Med => 0.15_f32,
-        Conflict::High => 0.40_f32,
-    };
-
-    // Zähler
-    let aborts = Arc::new(AtomicU64::new(0));
-    let commits = Arc::new(AtomicU64::new(0));
-
-    // Barrier für synchronen Start
-    let barrier = Arc::new(Barrier::new(cfg.threads));
-
-    // Für Duration-Modus
-    let stop = Arc::new(AtomicBool::new(false));
-    let stop_at = match cfg.mode {
-        Mode::Duration(s) => Some(Instant::now() + Duration::from_secs(s)),
-        _ => None,
-    };
-
-    // Für Ops-Modus: faire Verteilung
-    let (ops_base, ops_extra) = match cfg.mode {
-        Mode::Ops(n) => (n / cfg.threads as u64, n % cfg.threads as u64),
-        _ => (0, 0),
-    };
-
+    let Config { stm, trace_format, trace_out } = parse_args();
     eprintln!(
-        "stm_abort: threads={}, conflict={:?}, mode={:?}, seed={}",
-        cfg.threads, cfg.conflict, cfg.mode, cfg.seed
+        "stm_abort: threads={}, conflict={:?}, model={:?}, mode={:?}, seed={}, retry_budget={:?}, backoff={:?}",
+        stm.threads, stm.conflict, stm.model, stm.mode, stm.seed, stm.retry_budget, stm.backoff
     );
 
-    let mut handles = Vec::with_capacity(cfg.threads);
-    for tid in 0..cfg.threads {
-        let barrier = barrier.clone();
-        let aborts = Arc::clone(&aborts);
-        let commits = Arc::clone(&commits);
-        let stop_flag = stop.clone();
-        let local_mode = match cfg.mode {
-            Mode::Ops(_) => {
-                let per_thread = ops_base + if (tid as u64) < ops_extra { 1 } else { 0 };
-                Mode::Ops(per_thread)
-            }
-            Mode::Duration(s) => Mode::Duration(s),
-        };
-        let local_stop_at = stop_at; // Copy (Option<Instant> ist Copy)
-
-        // deterministischer Seed je Thread
-        let thread_seed = cfg.seed
-            ^ (((tid as u64) + 1) << 32)
-            ^ 0x9E37_79B9_7F4A_7C15u64;
-        let mut rng = XorShift64::new(thread_seed);
-
-        let h = thread::spawn(move || {
-            // synchroner Start
-            barrier.wait();
+    let res = run_stm(stm);
+    let snap = res.counters;
 
-            // Fortschritt
-            let mut done: u64 = 0;
-            let mut last = Instant::now();
+    println!("STM run finished.");
+    println!("aborts_total: {}", snap.aborts);
+    println!("commits_total: {}", snap.commits);
+    println!("gave_up_total: {}", snap.gave_up);
+    println!("wall_ms: {}", res.wall.as_millis());
 
-            match local_mode {
-                Mode::Ops(ops) => {
-                    for _ in 0..ops {
-                        // Arbeit simulieren
-                        spin_for_ns(1500 + (rng.next_u32() % 1500) as u64);
+    // Retries pro Commit (stm_retry)
+    #[cfg(feature = "metrics")]
+    hpc_core::metrics::summary();
 
-                        // Konfliktsampling
-                        if rng.next_f32() < p_conflict {
-                            aborts.fetch_add(1, Ordering::Relaxed);
-                            // deterministischer Backoff
-                            spin_for_ns(10_000 + ((tid as u64) * 1_000));
+    #[cfg(feature = "memtrace")]
+    if let Err(diffs) = snap.cross_check_trace() {
+        for d in diffs {
+            eprintln!("trace mismatch: {}", d);
+        }
+    }
 
-                            #[cfg(feature = "memtrace")]
-                            hpc_core::memtracer::trace_abort(
-                                /*tx_id*/ 0,
-                                /*cause*/ "conflict",
-                                /*retries*/ 1,
-                                /*conflict_sz*/ 1,
-                                /*abort_token*/ "stm",
-                            );
-                        } else {
-                            commits.fetch_add(1, Ordering::Relaxed);
-                        }
+    write_trace(trace_format, &trace_out);
+}
 
-                        // Fortschritt ausgeben (ca. 1×/s)
-                        done += 1;
-                        if last.elapsed().as_secs() >= 1 {
-                            eprintln!("progress tid={} done={}", tid, done);
-                            last = Instant::now();
-                        }
-                    }
+// Trace im gewählten Format nach `out` schreiben (no-op ohne Feature "memtrace")
+#[cfg(feature = "memtrace")]
+fn write_trace(format: TraceFormat, out: &Path) {
+    if let Err(e) = std::fs::create_dir_all(out) {
+        eprintln!("trace-out {}: {}", out.display(), e);
+        return;
+    }
+    match format {
+        TraceFormat::Csv => {
+            hpc_core::memtracer::flush_csv_to(out);
+            println!("memtrace.csv / memtrace_summary.txt in {} geschrieben (falls Events vorhanden).", out.display());
+        }
+        #[cfg(feature = "json")]
+        TraceFormat::Json | TraceFormat::Chrome => {
+            let (path, res) = match format {
+                TraceFormat::Json => {
+                    let p = out.join("memtrace.json");
+                    let r = hpc_core::memtracer::flush_json(&p);
+                    (p, r)
                 }
-                Mode::Duration(_) => {
-                    let deadline = local_stop_at.expect("deadline missing");
-                    while Instant::now() < deadline && !stop_flag.load(Ordering::Relaxed) {
-                        spin_for_ns(1500 + (rng.next_u32() % 1500) as u64);
-
-                        if rng.next_f32() < p_conflict {
-                            aborts.fetch_add(1, Ordering::Relaxed);
-                            spin_for_ns(10_000 + ((tid as u64) * 1_000));
-
-                            #[cfg(feature = "memtrace")]
-                            hpc_core::memtracer::trace_abort(0, "conflict", 1, 1, "stm");
-                        } else {
-                            commits.fetch_add(1, Ordering::Relaxed);
-                        }
-
-                        // Fortschritt ausgeben (ca. 1×/s)
-                        done += 1;
-                        if last.elapsed().as_secs() >= 1 {
-                            eprintln!("progress tid={} done={}", tid, done);
-                            last = Instant::now();
-                        }
-                    }
+                _ => {
+                    let p = out.join("memtrace_chrome.json");
+                    let r = hpc_core::memtracer::flush_chrome_trace(&p);
+                    (p, r)
                 }
+            };
+            match res {
+                Ok(()) => println!("{} geschrieben.", path.display()),
+                Err(e) => eprintln!("{}: {}", path.display(), e),
             }
-        });
-        handles.push(h);
-    }
-
-    // Duration-Modus: sauber stoppen
-    if let Mode::Duration(s) = cfg.mode {
-        let dur = Duration::from_secs(s);
-        let t0 = Instant::now();
-        while t0.elapsed() < dur {
-            thread::sleep(Duration::from_millis(5));
         }
-        stop.store(true, Ordering::Relaxed);
-    }
-
-    for h in handles {
-        let _ = h.join();
-    }
-
-    let a = aborts.load(Ordering::Relaxed);
-    let c = commits.load(Ordering::Relaxed);
-
-    println!("STM run finished.");
-    println!("aborts_total: {}", a);
-    println!("commits_total: {}", c);
-
-    #[cfg(feature = "memtrace")]
-    {
-        hpc_core::memtracer::flush_csv();
-        println!("memtrace.csv / memtrace_summary.txt geschrieben (falls Events vorhanden).");
+        #[cfg(not(feature = "json"))]
+        TraceFormat::Json | TraceFormat::Chrome => {
+            eprintln!("--trace-format {:?} braucht Feature \"json\"; nichts geschrieben.", format);
+        }
+        #[cfg(feature = "perfetto")]
+        TraceFormat::Perfetto => {
+            let path = out.join("memtrace.perfetto-trace");
+            match hpc_core::memtracer::flush_perfetto(&path) {
+                Ok(()) => println!("{} geschrieben.", path.display()),
+                Err(e) => eprintln!("{}: {}", path.display(), e),
+            }
+        }
+        #[cfg(not(feature = "perfetto"))]
+        TraceFormat::Perfetto => {
+            eprintln!("--trace-format perfetto braucht Feature \"perfetto\"; nichts geschrieben.");
+        }
     }
 }
 
-
-// sehr kleiner, portabler Busy-Wait (für deterministische Mikro-Sleeps)
-#[inline(always)]
-fn spin_for_ns(nanos: u64) {
-    let start = Instant::now();
-    while start.elapsed().as_nanos() < nanos as u128 {
-        core::hint::spin_loop();
-    }
-}
+#[cfg(not(feature = "memtrace"))]
+fn write_trace(_format: TraceFormat, _out: &Path) {}
@@ -4,13 +4,12 @@
 // Vektoraddition mit Safe-RustCL-Wrapper (Typ-State + Metrics + MemTrace)
 
 use bytemuck::{cast_slice, cast_slice_mut};
-use hpc_core::{ClError, GpuBuffer, Queued, Ready};
+use hpc_core::{ClError, GpuBuffer, Queued, Ready, SafeKernel};
 
 use opencl3::{
     command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
     context::Context,
     device::{Device, CL_DEVICE_TYPE_GPU},
-    kernel::Kernel,
     platform::get_platforms,
     program::Program,
 };
@@ -18,7 +17,7 @@ use opencl3::{
 #[cfg(feature = "metrics")]
 use hpc_core::summary;
 #[cfg(feature = "memtrace")]
-use hpc_core::{start as trace_start, Dir, flush_csv};
+use hpc_core::{start as trace_start, trace_barrier, Dir, flush_csv};
 
 fn main() -> Result<(), ClError> {
     // 1) OpenCL-Setup
@@ -70,20 +69,23 @@ fn main() -> Result<(), ClError> {
     #[cfg(feature = "memtrace")]
     let tok_k = trace_start(Dir::Kernel, 0);
     let src     = include_str!("../examples/vec_add.cl");
-    let program = Program::create_and_build_from_source(&context, src, "")
+    // -cl-kernel-arg-info: Arg-Typen prüfbar (float* ↔ f32)
+    let program = Program::create_and_build_from_source(&context, src, "-cl-kernel-arg-info")
         .map_err(|_| ClError::Api(-3))?;
-    let kernel  = Kernel::create(&program, "vec_add")?;
-    kernel.set_arg(0, a_ready.raw())?;
-    kernel.set_arg(1, b_ready.raw())?;
-    kernel.set_arg(2, out_ready.raw())?;
+    let mut kernel = SafeKernel::new(&program, "vec_add")?;
+    kernel.arg_buffer(0, a_ready.as_arg::<f32>())?;
+    kernel.arg_buffer(1, b_ready.as_arg::<f32>())?;
+    kernel.arg_buffer(2, out_ready.as_arg::<f32>())?;
     let global = [n, 1, 1];
     queue.enqueue_nd_range_kernel(
-        kernel.get(), 1,
+        kernel.kernel().get(), 1,
         std::ptr::null(), global.as_ptr(),
         std::ptr::null(), &[],
     )?;
     queue.finish()?;  // warte auf Kernel
     #[cfg(feature = "memtrace")]
+    trace_barrier("finish");
+    #[cfg(feature = "memtrace")]
     tok_k.finish();
 
     // 8) Device→Host (Out lesen)
@@ -0,0 +1,8 @@
+#pragma OPENCL EXTENSION cl_khr_fp16 : enable
+
+__kernel void vec_add_f16(__global const half* a,
+                          __global const half* b,
+                          __global half* c) {
+    int gid = get_global_id(0);
+    c[gid] = a[gid] + b[gid];
+}
@@ -0,0 +1,60 @@
+// crates/hpc-core/examples/vec_add_f16.rs
+// 2025 Thomas Bicanic – MIT License
+//
+// Vektoraddition in half precision (half::f16 ↔ OpenCL "half").
+// Braucht Feature "half" und ein Device mit cl_khr_fp16:
+//   cargo run --example vec_add_f16 --features half
+
+use half::f16;
+use hpc_core::{verify, ClError, GpuBuffer, Ready, SafeKernel};
+
+use opencl3::{
+    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
+    context::Context,
+    device::{Device, CL_DEVICE_TYPE_GPU},
+    platform::get_platforms,
+    program::Program,
+};
+
+fn main() -> Result<(), ClError> {
+    // 1) OpenCL-Setup
+    let platform   = get_platforms()?.remove(0);
+    let device_id  = platform.get_devices(CL_DEVICE_TYPE_GPU)?[0];
+    let device     = Device::new(device_id);
+    if !device.extensions()?.contains("cl_khr_fp16") {
+        eprintln!("{}: kein cl_khr_fp16, übersprungen", device.name()?);
+        return Ok(());
+    }
+    let context    = Context::from_device(&device)?;
+    let queue      = CommandQueue::create(&context, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+
+    // 2) Host-Daten: Werte jenseits 1.0, damit die f16-Rundung sichtbar wird
+    let n   = 1 << 20;
+    let h_a: Vec<f16> = (0..n).map(|i| f16::from_f32(1.0 + (i % 100) as f32 / 100.0)).collect();
+    let h_b: Vec<f16> = (0..n).map(|i| f16::from_f32(2.0 + (i % 7) as f32 / 7.0)).collect();
+
+    // 3) Device-Puffer: Größe = n * size_of::<f16>() = 2 Bytes je Element
+    let a_dev: GpuBuffer<Ready> = GpuBuffer::from_slice(&context, &queue, &h_a)?;
+    let b_dev: GpuBuffer<Ready> = GpuBuffer::from_slice(&context, &queue, &h_b)?;
+    let out_dev: GpuBuffer<Ready> = GpuBuffer::from_slice(&context, &queue, &vec![f16::ZERO; n])?;
+
+    // 4) Kernel; -cl-kernel-arg-info: Arg-Typen prüfbar (half* ↔ f16)
+    let src     = include_str!("../examples/vec_add_f16.cl");
+    let program = Program::create_and_build_from_source(&context, src, "-cl-kernel-arg-info")
+        .map_err(|_| ClError::Api(-3))?;
+    let mut kernel = SafeKernel::new(&program, "vec_add_f16")?;
+    kernel.arg_buffer(0, a_dev.as_arg::<f16>())?;
+    kernel.arg_buffer(1, b_dev.as_arg::<f16>())?;
+    kernel.arg_buffer(2, out_dev.as_arg::<f16>())?;
+    drop(kernel.launch(&queue, &[n], None)?);
+
+    // 5) Device→Host und Verifikation mit f16-Toleranz (Referenz in f32 gerechnet)
+    let h_out: Vec<f16> = out_dev.read_to_vec(&queue)?;
+    let expected: Vec<f16> = h_a.iter().zip(&h_b).map(|(a, b)| f16::from_f32(a.to_f32() + b.to_f32())).collect();
+    if let Some(i) = verify::first_mismatch(&h_out, &expected, <f16 as verify::Tolerance>::DEFAULT_TOL) {
+        panic!("vec_add_f16: Element {} = {}, erwartet {}", i, h_out[i], expected[i]);
+    }
+    println!("vec_add_f16 OK, first element = {}", h_out[0]);
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+// crates/hpc-core/examples/vec_add_fast.rs
+// 2025 Thomas Bicanic – MIT License
+//
+// Vektoraddition zweimal: naiv (wie vec_add, jede Stufe blockiert) und als
+// Pipeline mit gepinnten Hostpuffern (CL_MEM_ALLOC_HOST_PTR, gemappt), CHUNKS
+// Teilstücken und je einer Queue für H2D, Kernel und D2H. Abhängigkeiten laufen über Events (enqueue_marker),
+// der Host wartet erst, wenn ein Ergebnis gebraucht wird; so überlappen
+// Upload von Chunk i+1, Kernel i und Download i-1.
+// Mit Feature "metrics" stehen vec_add_naive/vec_add_fast samt GB/s in summary().
+// Argument: Elementanzahl (Default 4 Mi, muss durch CHUNKS teilbar sein)
+
+use bytemuck::{cast_slice, cast_slice_mut};
+use hpc_core::{enqueue_marker, ClError, GpuBuffer, Mapped, Queued, Ready, SafeKernel};
+use std::time::{Duration, Instant};
+
+use opencl3::{
+    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
+    context::Context,
+    device::{Device, CL_DEVICE_TYPE_GPU},
+    memory::{CL_MEM_ALLOC_HOST_PTR, CL_MEM_READ_WRITE},
+    platform::get_platforms,
+    program::Program,
+};
+
+const CHUNKS: usize = 4;
+
+fn main() -> Result<(), ClError> {
+    let platform = get_platforms()?.remove(0);
+    let device   = Device::new(platform.get_devices(CL_DEVICE_TYPE_GPU)?[0]);
+    let context  = Context::from_device(&device)?;
+
+    let n = std::env::args()
+        .nth(1)
+        .map(|s| s.parse::<usize>().expect("need element count"))
+        .unwrap_or(1 << 22);
+    assert!(n.is_multiple_of(CHUNKS), "element count must be a multiple of {CHUNKS}");
+
+    let src = include_str!("../examples/vec_add.cl");
+    let program = Program::create_and_build_from_source(&context, src, "-cl-kernel-arg-info")
+        .map_err(|_| ClError::Api(-3))?;
+
+    let naive = run_naive(&context, &device, &program, n)?;
+    let fast  = run_fast(&context, &device, &program, n)?;
+
+    println!("vec_add_fast OK for {} elements", n);
+    println!("naive: {:>8.3} ms", naive.as_secs_f64() * 1e3);
+    println!("fast:  {:>8.3} ms   ({} chunks, pinned, 3 queues)", fast.as_secs_f64() * 1e3, CHUNKS);
+    println!("speedup: {:.2}x", naive.as_secs_f64() / fast.as_secs_f64());
+
+    #[cfg(feature = "metrics")]
+    hpc_core::summary();
+
+    Ok(())
+}
+
+// Baseline: Vec-Hostpuffer, eine Queue, jede Stufe wartet auf die vorige
+fn run_naive(ctx: &Context, device: &Device, program: &Program, n: usize) -> Result<Duration, ClError> {
+    let queue = CommandQueue::create(ctx, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+    let bytes = n * std::mem::size_of::<f32>();
+    let h_a = vec![1.0_f32; n];
+    let h_b = vec![2.0_f32; n];
+    let mut h_out = vec![0.0_f32; n];
+    let a_dev = GpuBuffer::<Queued>::new(ctx, bytes)?;
+    let b_dev = GpuBuffer::<Queued>::new(ctx, bytes)?;
+    let out_dev = GpuBuffer::<Queued>::new(ctx, bytes)?.launch();
+    let mut kernel = SafeKernel::new(program, "vec_add")?;
+
+    let t = Instant::now();
+    let (a_if, g) = a_dev.enqueue_write(&queue, cast_slice(&h_a))?;
+    let a = a_if.into_ready(g);
+    let (b_if, g) = b_dev.enqueue_write(&queue, cast_slice(&h_b))?;
+    let b = b_if.into_ready(g);
+
+    kernel.arg_buffer(0, a.as_arg::<f32>())?;
+    kernel.arg_buffer(1, b.as_arg::<f32>())?;
+    // SAFETY: launch() hat nichts eingereiht, es gibt keine offene Operation
+    kernel.arg_buffer(2, unsafe { out_dev.as_arg::<f32>() })?;
+    let gk = kernel.launch(&queue, &[n], None)?;
+    let out = out_dev.into_ready(gk);
+
+    let (read_if, g) = out.enqueue_read(&queue, cast_slice_mut(&mut h_out))?;
+    let _out: GpuBuffer<Ready> = read_if.into_ready(g);
+    let elapsed = t.elapsed();
+
+    #[cfg(feature = "metrics")]
+    hpc_core::record_bytes("vec_add_naive", t, 3 * bytes);
+
+    assert!(h_out.iter().all(|&x| (x - 3.0).abs() < 1e-6));
+    Ok(elapsed)
+}
+
+// Pipeline: pro Chunk eigene Device-Puffer, Kernel hängt per Marker an beiden
+// Uploads, Download per enqueue_read_after am Kernel-Event des Chunks
+fn run_fast(ctx: &Context, device: &Device, program: &Program, n: usize) -> Result<Duration, ClError> {
+    let h2d  = CommandQueue::create(ctx, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+    let comp = CommandQueue::create(ctx, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+    let d2h  = CommandQueue::create(ctx, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+
+    let bytes = n * std::mem::size_of::<f32>();
+    let chunk = bytes / CHUNKS;
+    let mut h_a = pinned(ctx, &h2d, bytes)?;
+    let mut h_b = pinned(ctx, &h2d, bytes)?;
+    let mut h_out = pinned(ctx, &d2h, bytes)?;
+    h_a.as_mut_slice().fill(1.0);
+    h_b.as_mut_slice().fill(2.0);
+
+    // Allokation außerhalb der Messung, wie bei run_naive
+    let mut dev = Vec::with_capacity(CHUNKS);
+    for _ in 0..CHUNKS {
+        dev.push((
+            GpuBuffer::<Queued>::new(ctx, chunk)?,
+            GpuBuffer::<Queued>::new(ctx, chunk)?,
+            GpuBuffer::<Queued>::new(ctx, chunk)?.launch(),
+        ));
+    }
+
+    let t = Instant::now();
+
+    // 1) alle Uploads und Kernel einreihen, ohne den Host zu blockieren
+    let mut launched = Vec::with_capacity(CHUNKS);
+    for (i, (a, b, out)) in dev.into_iter().enumerate() {
+        let range = i * chunk..(i + 1) * chunk;
+        let (a_if, ga) = a.enqueue_write(&h2d, &cast_slice(h_a.as_slice())[range.clone()])?;
+        let (b_if, gb) = b.enqueue_write(&h2d, &cast_slice(h_b.as_slice())[range])?;
+        let uploaded = enqueue_marker(&comp, &[ga.event(), gb.event()])?;
+        // je Chunk ein Kernel: er leiht seine Puffer, solange er benutzt wird
+        let mut kernel = SafeKernel::new(program, "vec_add")?;
+
+        // SAFETY: der Kernel startet erst nach `uploaded`, also nach beiden
+        // Uploads; `out` hat keine offene Operation (launch())
+        unsafe {
+            kernel.arg_buffer(0, a_if.as_arg::<f32>())?;
+            kernel.arg_buffer(1, b_if.as_arg::<f32>())?;
+            kernel.arg_buffer(2, out.as_arg::<f32>())?;
+        }
+        let gk = kernel.launch_with_deps(&comp, &[chunk / std::mem::size_of::<f32>()], None, &[&uploaded])?;
+        launched.push(((a_if, ga), (b_if, gb), (out, gk)));
+    }
+
+    // 2) Downloads einreihen: Chunk i wartet auf der Queue nur auf seinen
+    //    eigenen Kernel, der Host blockiert hier nicht
+    let mut reads = Vec::with_capacity(CHUNKS);
+    let mut inputs = Vec::with_capacity(CHUNKS);
+    for ((a, b, (out, gk)), out_chunk) in launched.into_iter().zip(cast_slice_mut(h_out.as_mut_slice()).chunks_mut(chunk)) {
+        reads.push(out.enqueue_read_after(&d2h, out_chunk, gk)?);
+        inputs.push((a, b));
+    }
+    for (read_if, g) in reads {
+        let _out: GpuBuffer<Ready> = read_if.into_ready(g);
+    }
+    // Eingaben erst freigeben, wenn die Kernel sie gelesen haben
+    for ((a_if, ga), (b_if, gb)) in inputs {
+        drop((a_if.into_ready(ga), b_if.into_ready(gb)));
+    }
+    let elapsed = t.elapsed();
+
+    #[cfg(feature = "metrics")]
+    hpc_core::record_bytes("vec_add_fast", t, 3 * bytes);
+
+    assert!(h_out.as_slice().iter().all(|&x| (x - 3.0).abs() < 1e-6));
+    h_a.unmap(&h2d)?;
+    h_b.unmap(&h2d)?;
+    h_out.unmap(&d2h)?;
+    Ok(elapsed)
+}
+
+// Gepinnter Hostpuffer: CL_MEM_ALLOC_HOST_PTR lässt den Treiber page-locked
+// Hostspeicher anlegen, das Mapping liefert ihn als [f32]. Transfers von und
+// in diese Slices laufen per DMA ohne Zwischenkopie.
+fn pinned(ctx: &Context, queue: &CommandQueue, bytes: usize) -> Result<GpuBuffer<Mapped<f32>>, ClError> {
+    let buf = GpuBuffer::<Queued>::with_flags(ctx, bytes, CL_MEM_ALLOC_HOST_PTR | CL_MEM_READ_WRITE)?;
+    // frisch angelegt, also nichts offen: der Marker schließt nur den Übergang nach Ready ab
+    let ready = buf.launch().complete(enqueue_marker(queue, &[])?);
+    ready.enqueue_map_typed::<f32>(queue)
+}
@@ -0,0 +1,81 @@
+//! Retry delays shared by the STM workload and transfer waits
+
+use std::time::Duration;
+
+/// Delay before retry number `attempt` (1 = first retry)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackoffPolicy {
+    /// The same delay before every retry
+    Fixed(Duration),
+    /// `base * 2^(attempt - 1)`, at most `cap`
+    Exponential { base: Duration, cap: Duration },
+    /// `Exponential` scaled by a pseudo-random factor in `[0.5, 1.0)`, so
+    /// contending threads spread out; deterministic for a given `seed`
+    /// (e.g. the thread id) and attempt
+    Jittered { base: Duration, cap: Duration, seed: u64 },
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        BackoffPolicy::Fixed(Duration::from_micros(10))
+    }
+}
+
+impl BackoffPolicy {
+    /// Delay before retry `attempt`; attempt 0 is treated like 1
+    pub fn next_delay(&self, attempt: u32) -> Duration {
+        match *self {
+            BackoffPolicy::Fixed(d) => d,
+            BackoffPolicy::Exponential { base, cap } => exponential(base, cap, attempt),
+            BackoffPolicy::Jittered { base, cap, seed } => {
+                let d = exponential(base, cap, attempt);
+                // obere 53 Bit → [0, 1)
+                let u = (splitmix64(seed ^ attempt as u64) >> 11) as f64 / (1u64 << 53) as f64;
+                d.mul_f64(0.5 + 0.5 * u)
+            }
+        }
+    }
+
+    /// Same policy with `seed` replaced (no-op unless `Jittered`), e.g. one
+    /// seed per thread
+    pub fn with_seed(self, seed: u64) -> Self {
+        match self {
+            BackoffPolicy::Jittered { base, cap, .. } => BackoffPolicy::Jittered { base, cap, seed },
+            other => other,
+        }
+    }
+}
+
+fn exponential(base: Duration, cap: Duration, attempt: u32) -> Duration {
+    let shift = attempt.saturating_sub(1).min(31);
+    base.saturating_mul(1u32 << shift).min(cap)
+}
+
+// SplitMix64-Finalizer: gut verteilte Bits aus einem Zähler
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Run `op` until it succeeds, at most `1 + max_retries` times, sleeping
+/// `policy.next_delay(n)` before retry `n`. `op` gets the attempt number
+/// (0 = first try); the last error is returned once the retries are used up.
+pub fn retry<T, E>(
+    policy: &BackoffPolicy,
+    max_retries: u32,
+    mut op: impl FnMut(u32) -> std::result::Result<T, E>,
+) -> std::result::Result<T, E> {
+    let mut attempt = 0;
+    loop {
+        match op(attempt) {
+            Ok(v) => return Ok(v),
+            Err(e) if attempt >= max_retries => return Err(e),
+            Err(_) => {
+                attempt += 1;
+                std::thread::sleep(policy.next_delay(attempt));
+            }
+        }
+    }
+}
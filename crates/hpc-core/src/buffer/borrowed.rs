@@ -0,0 +1,113 @@
+//! Borrow-based transfers on `Ready` buffers.
+//!
+//! Concurrency contract: OpenCL allows any number of reads of one buffer to
+//! run at the same time, but a write must not overlap any other access.
+//! The methods here encode that in the borrow checker instead of the state:
+//!
+//! - `enqueue_read_shared` takes `&self`, so several reads (into different
+//!   host slices) may be in flight together;
+//! - `enqueue_write_exclusive` takes `&mut self`, so it cannot start while a
+//!   read guard is alive and nothing can read while it is pending.
+//!
+//! The buffer stays `Ready` throughout; the returned guard holds both
+//! borrows (buffer and host slice) and waits for the transfer on drop. As
+//! with `GpuEventGuard`, a guard must be dropped or waited, never leaked.
+
+use opencl3::{command_queue::CommandQueue, types::CL_NON_BLOCKING};
+use std::marker::PhantomData;
+
+use super::{GpuBuffer, GpuEventGuard, Ready};
+use crate::error::{counted, ClError, Result};
+
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Pending read from a shared borrow; waits on drop
+pub struct ReadGuard<'a> {
+    guard: GpuEventGuard,
+    _borrow: PhantomData<(&'a GpuBuffer<Ready>, &'a mut [u8])>,
+}
+
+/// Pending write through an exclusive borrow; waits on drop
+pub struct WriteGuard<'a> {
+    guard: GpuEventGuard,
+    _borrow: PhantomData<(&'a mut GpuBuffer<Ready>, &'a [u8])>,
+}
+
+impl ReadGuard<'_> {
+    /// Wait for the read; the host slice is filled afterwards
+    pub fn wait(self) -> Result<()> {
+        Ok(self.guard.wait()?)
+    }
+}
+
+impl WriteGuard<'_> {
+    /// Wait for the write to reach the device
+    pub fn wait(self) -> Result<()> {
+        Ok(self.guard.wait()?)
+    }
+}
+
+impl GpuBuffer<Ready> {
+    /// Non-blocking read into `host_out` that only borrows the buffer;
+    /// other shared reads may run concurrently
+    pub fn enqueue_read_shared<'a>(
+        &'a self,
+        queue: &CommandQueue,
+        host_out: &'a mut [u8],
+    ) -> Result<ReadGuard<'a>> {
+        self.check_host_access("enqueue_read_shared", true, false)?;
+        if host_out.len() != self.len {
+            return Err(ClError::BufferSizeMismatch {
+                expected: self.len,
+                actual: host_out.len(),
+            });
+        }
+
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let evt = queue
+            .enqueue_read_buffer(&self.buf, CL_NON_BLOCKING, 0, host_out, &[])
+            .map_err(counted("enqueue_read_shared"))?;
+
+        #[cfg(feature = "memtrace")]
+        crate::trace_on_complete(&evt, crate::memtracer::Dir::D2H, host_out.len());
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes_on_complete(&evt, "enqueue_read_shared", t, self.len);
+
+        Ok(ReadGuard { guard: GpuEventGuard::new(evt), _borrow: PhantomData })
+    }
+
+    /// Non-blocking overwrite of the whole buffer from `host`; the exclusive
+    /// borrow rules out any concurrent read or write
+    pub fn enqueue_write_exclusive<'a>(
+        &'a mut self,
+        queue: &CommandQueue,
+        host: &'a [u8],
+    ) -> Result<WriteGuard<'a>> {
+        self.check_host_access("enqueue_write_exclusive", false, true)?;
+        if host.len() != self.len {
+            return Err(ClError::BufferSizeMismatch {
+                expected: self.len,
+                actual: host.len(),
+            });
+        }
+
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let evt = queue
+            .enqueue_write_buffer(&mut self.buf, CL_NON_BLOCKING, 0, host, &[])
+            .map_err(counted("enqueue_write_exclusive"))?;
+
+        #[cfg(feature = "memtrace")]
+        crate::trace_on_complete(&evt, crate::memtracer::Dir::H2D, host.len());
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes_on_complete(&evt, "enqueue_write_exclusive", t, self.len);
+
+        Ok(WriteGuard { guard: GpuEventGuard::new(evt), _borrow: PhantomData })
+    }
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "gl-interop")]
+
+//! OpenGL interop (`cl_khr_gl_sharing`): buffers backed by a GL buffer object
+
+use opencl3::{
+    command_queue::CommandQueue,
+    context::Context,
+    memory::{Buffer, ClMem, CL_MEM_READ_WRITE},
+};
+use std::{ffi::c_void, marker::PhantomData, ops::Deref};
+
+use super::{GlReleased, GpuBuffer, MapPtr, Ready};
+use crate::error::{counted, Result};
+
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+impl GpuBuffer<GlReleased> {
+    /// Wrap the OpenGL buffer object `gl_id` (`clCreateFromGLBuffer`), e.g.
+    /// a vertex buffer the results are rendered from without a host copy.
+    ///
+    /// `ctx` must be a shared context: created with the GL context and
+    /// display of the current thread (`CL_GL_CONTEXT_KHR` plus
+    /// `CL_GLX_DISPLAY_KHR`/`CL_WGL_HDC_KHR`/... in the context properties)
+    /// on a device that supports `cl_khr_gl_sharing`; otherwise OpenCL
+    /// returns `CL_INVALID_CONTEXT`. The size is taken from the GL buffer,
+    /// kernels may read and write it.
+    ///
+    /// The buffer starts out owned by GL; `acquire_gl` hands it to OpenCL.
+    pub fn from_gl_buffer(ctx: &Context, gl_id: u32) -> Result<Self> {
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let buf = Buffer::<u8>::create_from_gl_buffer(ctx, CL_MEM_READ_WRITE, gl_id)
+            .map_err(counted("GpuBuffer::from_gl_buffer"))?;
+        let len = buf.size().map_err(counted("GpuBuffer::from_gl_buffer"))?;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record("GpuBuffer::from_gl_buffer", t);
+
+        Ok(GpuBuffer {
+            buf,
+            len,
+            flags: CL_MEM_READ_WRITE,
+            map_ptr: MapPtr::NULL,
+            sub: None,
+            #[cfg(feature = "buffer_registry")]
+            reg: super::registry::Entry::new::<GlReleased>(len),
+            _state: PhantomData,
+        })
+    }
+
+    /// Take the buffer from GL (`clEnqueueAcquireGLObjects`) and wait until
+    /// OpenCL owns it; the result derefs to a `Ready` buffer for kernels
+    /// and transfers.
+    ///
+    /// GL must be done with the buffer first: call `glFinish` (or wait on
+    /// a GL sync object) before acquiring, OpenCL does not synchronize
+    /// with pending GL commands by itself.
+    pub fn acquire_gl(self, queue: &CommandQueue) -> Result<GlBuffer> {
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let mem = [self.buf.get() as *const c_void];
+        queue
+            .enqueue_acquire_gl_objects(&mem, &[])
+            .and_then(|evt| evt.wait())
+            .map_err(counted("acquire_gl"))?;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record("acquire_gl", t);
+
+        Ok(GlBuffer { buf: self.transition() })
+    }
+}
+
+/// GL-backed buffer currently owned by OpenCL (`acquire_gl`).
+///
+/// Derefs to a shared `GpuBuffer<Ready>` for kernel arguments and reads;
+/// only this type can go back to GL, so `release_gl` is never called on a
+/// buffer OpenCL allocated itself.
+pub struct GlBuffer {
+    buf: GpuBuffer<Ready>,
+}
+
+impl Deref for GlBuffer {
+    type Target = GpuBuffer<Ready>;
+
+    fn deref(&self) -> &GpuBuffer<Ready> {
+        &self.buf
+    }
+}
+
+impl GlBuffer {
+    /// Hand the buffer back to GL (`clEnqueueReleaseGLObjects`) and wait
+    /// until the release completes, so GL can render from it right away.
+    pub fn release_gl(self, queue: &CommandQueue) -> Result<GpuBuffer<GlReleased>> {
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let mem = [self.buf.raw().get() as *const c_void];
+        queue
+            .enqueue_release_gl_objects(&mem, &[])
+            .and_then(|evt| evt.wait())
+            .map_err(counted("release_gl"))?;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record("release_gl", t);
+
+        Ok(self.buf.transition())
+    }
+}
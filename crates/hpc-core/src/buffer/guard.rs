@@ -1,6 +1,8 @@
 //! Event guard for automatic synchronization
 
-use opencl3::event::Event;
+use opencl3::event::{Event, CL_COMPLETE};
+use std::time::{Duration, Instant};
+use crate::error::{ClError, Result};
 
 /// Guard that waits for event completion on drop
 pub struct GpuEventGuard {
@@ -24,10 +26,43 @@ impl GpuEventGuard {
         &self.evt
     }
     
+    /// Take the event without waiting for it (used to chain it as a
+    /// dependency of a later command)
+    pub(crate) fn into_event(self) -> Event {
+        let this = std::mem::ManuallyDrop::new(self);
+        // SAFETY: `this` is never dropped, so `evt` is moved out exactly once.
+        unsafe { std::ptr::read(&this.evt) }
+    }
+
     /// Wait for event completion explicitly
-    pub fn wait(self) -> Result<(), opencl3::error_codes::ClError> {
+    pub fn wait(self) -> std::result::Result<(), opencl3::error_codes::ClError> {
         self.evt.wait()
     }
+
+    /// Check `CL_EVENT_COMMAND_EXECUTION_STATUS` without blocking.
+    ///
+    /// `Ok(true)` once complete; a negative (error) status becomes `ClError::Api`.
+    pub fn is_complete(&self) -> Result<bool> {
+        let status = self.evt.command_execution_status()?.0;
+        if status < 0 {
+            return Err(ClError::Api(status));
+        }
+        Ok(status == CL_COMPLETE)
+    }
+
+    /// Poll until the event completes or `timeout` expires (`ClError::Timeout`)
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.is_complete()? {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(ClError::Timeout(timeout));
+            }
+            std::thread::sleep(Duration::from_micros(100));
+        }
+    }
 }
 
 impl Drop for GpuEventGuard {
@@ -0,0 +1,146 @@
+//! Buffers backed by caller-owned host memory (`CL_MEM_USE_HOST_PTR`)
+
+use opencl3::{
+    command_queue::CommandQueue,
+    context::Context,
+    memory::{Buffer, ClMem, CL_MAP_READ, CL_MAP_WRITE, CL_MEM_READ_WRITE, CL_MEM_USE_HOST_PTR},
+    types::CL_BLOCKING,
+};
+use bytemuck::Pod;
+use std::{ffi::c_void, marker::PhantomData, ptr};
+
+use super::{GpuBuffer, MapPtr, Ready};
+use crate::error::{counted, Result};
+use crate::kernel::{ClType, KernelArg};
+
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// A `Ready` buffer that uses a borrowed host slice as its storage.
+///
+/// Coherency rules: the device may cache the contents, so the slice is
+/// only consistent with the device inside `with_host`, which maps the
+/// buffer (on shared-memory devices the mapping is the slice itself, so no
+/// copy happens). The slice stays mutably borrowed for `'a`, so the host
+/// cannot touch it behind the device's back.
+///
+/// The device side is only handed out as a `KernelArg` borrowing this
+/// wrapper, and `SafeKernel::arg_buffer` keeps that borrow for as long as
+/// the kernel is used, so the wrapper (and the slice) cannot go away
+/// before a launch:
+///
+/// ```compile_fail,E0505
+/// # use hpc_core::{GpuBuffer, SafeKernel};
+/// # use opencl3::{command_queue::CommandQueue, context::Context, program::Program};
+/// fn run(ctx: &Context, queue: &CommandQueue, program: &Program, host: &mut [f32]) -> hpc_core::Result<()> {
+///     let mut kernel = SafeKernel::new(program, "scale")?;
+///     let buf = GpuBuffer::from_host_ptr(ctx, queue, host)?;
+///     kernel.arg_buffer(0, buf.as_arg())?;
+///     drop(buf);
+///     kernel.launch(queue, &[16], None)?.wait()?;
+///     Ok(())
+/// }
+/// ```
+///
+/// Drop finishes `queue` before the borrow ends; commands using the buffer
+/// on other queues must be awaited by the caller.
+pub struct HostPtrBuffer<'a, T: Pod> {
+    buf: GpuBuffer<Ready>,
+    queue: &'a CommandQueue,
+    _host: PhantomData<&'a mut [T]>,
+}
+
+impl GpuBuffer<Ready> {
+    /// Wrap `host` as device memory via `CL_MEM_USE_HOST_PTR` instead of
+    /// copying it. Drivers can only avoid copies for suitably aligned
+    /// memory (often 4096 bytes, e.g. from `PinnedBuffer`). Too large
+    /// buffers fail with `AllocTooLarge` like in `GpuBuffer::new`.
+    ///
+    /// `queue` is the queue the buffer is used on; it is finished when
+    /// the wrapper is dropped.
+    pub fn from_host_ptr<'a, T: Pod>(
+        ctx: &Context,
+        queue: &'a CommandQueue,
+        host: &'a mut [T],
+    ) -> Result<HostPtrBuffer<'a, T>> {
+        let len = std::mem::size_of_val(host);
+
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let buf = Buffer::<u8>::create(
+            ctx,
+            CL_MEM_READ_WRITE | CL_MEM_USE_HOST_PTR,
+            len,
+            host.as_mut_ptr().cast::<c_void>(),
+        )
+        .map_err(counted("GpuBuffer::from_host_ptr"))
+        .map_err(|e| super::alloc_error(ctx, len, e))?;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record("GpuBuffer::from_host_ptr", t);
+
+        Ok(HostPtrBuffer {
+            buf: GpuBuffer {
+                buf,
+                len,
+                flags: CL_MEM_READ_WRITE | CL_MEM_USE_HOST_PTR,
+                map_ptr: MapPtr::NULL,
+                sub: None,
+                #[cfg(feature = "buffer_registry")]
+                reg: super::registry::Entry::new::<Ready>(len),
+                _state: PhantomData,
+            },
+            queue,
+            _host: PhantomData,
+        })
+    }
+}
+
+impl<T: ClType> HostPtrBuffer<'_, T> {
+    /// Kernel argument handle for the device side; borrows the wrapper for
+    /// as long as the kernel it is bound to is used
+    pub fn as_arg(&self) -> KernelArg<'_, T> {
+        self.buf.as_arg()
+    }
+}
+
+impl<T: Pod> HostPtrBuffer<'_, T> {
+    /// Map the buffer (blocking) on the queue given to `from_host_ptr`,
+    /// run `f` on the host view and unmap again. Previously enqueued device
+    /// work must be complete, as for any map.
+    pub fn with_host<R>(&mut self, f: impl FnOnce(&mut [T]) -> R) -> Result<R> {
+        let queue = self.queue;
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let mut host_ptr: *mut c_void = ptr::null_mut();
+        queue.enqueue_map_buffer(
+            &self.buf.buf, CL_BLOCKING, CL_MAP_READ | CL_MAP_WRITE, 0, self.buf.len, &mut host_ptr, &[],
+        )?;
+
+        // SAFETY: the map covers all `len` bytes, which were a `[T]` when the
+        // buffer was created; with USE_HOST_PTR the mapping keeps the
+        // alignment of that slice. The slice does not escape `f`.
+        let out = f(unsafe {
+            std::slice::from_raw_parts_mut(host_ptr.cast::<T>(), self.buf.len / std::mem::size_of::<T>().max(1))
+        });
+
+        queue.enqueue_unmap_mem_object(self.buf.buf.get(), host_ptr, &[])?.wait()?;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record("with_host", t);
+
+        Ok(out)
+    }
+}
+
+impl<T: Pod> Drop for HostPtrBuffer<'_, T> {
+    fn drop(&mut self) {
+        // Kernel oder Transfers auf der Queue dürfen den Host-Speicher nach
+        // Ende der Borrow nicht mehr anfassen
+        if let Err(e) = self.queue.finish() {
+            diag_error!("HostPtrBuffer: finishing queue on drop failed: {e}");
+        }
+    }
+}
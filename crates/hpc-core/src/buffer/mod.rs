@@ -1,48 +1,141 @@
 //! GPU Buffer management with type-state pattern
 
 pub mod state;
+mod borrowed;
+mod gl;
 mod guard;
+mod host_ptr;
+mod raw;
+mod rect;
+mod registry;
+mod sub;
 
+pub use borrowed::{ReadGuard, WriteGuard};
+#[cfg(feature = "gl-interop")]
+pub use gl::GlBuffer;
 pub use guard::GpuEventGuard;
-pub use state::{State, Queued, InFlight, Ready};
+pub use host_ptr::HostPtrBuffer;
+pub use raw::RawMemGuard;
+pub use rect::Rect;
+pub use sub::{overlap_bytes, SubBuffer};
+#[cfg(feature = "buffer_registry")]
+pub use registry::assert_all_ready;
+#[cfg(all(feature = "buffer_registry", feature = "memtrace"))]
+pub(crate) use registry::count_in_state;
+pub use state::{State, Transition, Queued, InFlight, Ready, Mapped, MappedRead};
+#[cfg(feature = "gl-interop")]
+pub use state::GlReleased;
 
 use opencl3::{
     context::Context,
-    memory::{Buffer, CL_MEM_READ_WRITE},
+    device::Device,
+    memory::{
+        Buffer, ClMem, CL_MAP_READ, CL_MAP_WRITE, CL_MEM_ALLOC_HOST_PTR, CL_MEM_COPY_HOST_PTR,
+        CL_MEM_HOST_NO_ACCESS, CL_MEM_HOST_READ_ONLY, CL_MEM_HOST_WRITE_ONLY, CL_MEM_READ_WRITE,
+        CL_MEM_USE_HOST_PTR,
+    },
     command_queue::CommandQueue,
     event::Event,
-    types::CL_NON_BLOCKING,
+    types::{cl_event, cl_map_flags, cl_mem_flags, CL_BLOCKING, CL_NON_BLOCKING},
 };
-use std::{marker::PhantomData, ptr};
-use crate::error::{ClError, Result};
+use bytemuck::Pod;
+use std::{ffi::c_void, marker::PhantomData, ptr};
+use crate::error::{counted, ClError, Result};
+use crate::memory::StagingBuffer;
 
 #[cfg(feature = "metrics")]
 use std::time::Instant;
 
-#[cfg(feature = "metrics")]
-use std::sync::atomic::Ordering;
-
 /// GPU Buffer wrapper with compile-time state checking
 pub struct GpuBuffer<S: State> {
     buf: Buffer<u8>,
     len: usize,
+    /// `cl_mem_flags` at creation, for the host access checks
+    flags: cl_mem_flags,
+    map_ptr: MapPtr,
+    /// Set for buffers created by `sub_buffer`
+    sub: Option<sub::SubRegion>,
+    #[cfg(feature = "buffer_registry")]
+    reg: registry::Entry,
     _state: PhantomData<S>,
 }
 
+/// Host pointer of an active mapping (null unless `Mapped`)
+struct MapPtr(*mut c_void);
+
+// SAFETY: the pointer is only dereferenced through `&self`/`&mut self` of a
+// `GpuBuffer<Mapped<T>>`, so moving the buffer moves exclusive access with it.
+unsafe impl Send for MapPtr {}
+unsafe impl Sync for MapPtr {}
+
+impl MapPtr {
+    const NULL: MapPtr = MapPtr(ptr::null_mut());
+}
+
+// GpuBuffer selbst kann kein Drop haben (transition zerlegt ihn), daher
+// meldet der Zeiger ein Mapping, das nie per unmap() zurückgegeben wurde
+impl Drop for MapPtr {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            diag_warn!("GpuBuffer dropped while mapped; the mapping leaks until the context is released, call unmap()");
+        }
+    }
+}
+
+/// Number of `T` in `bytes`, or `SizeNotAligned` if `bytes` is not a
+/// whole number of them (zero-sized `T` hold no bytes). The check behind
+/// `new_typed`, typed kernel args and the typed reads.
+pub fn elem_count<T>(bytes: usize) -> Result<usize> {
+    let elem = std::mem::size_of::<T>();
+    match bytes.checked_div(elem) {
+        Some(n) if bytes.is_multiple_of(elem) => Ok(n),
+        _ if bytes == 0 => Ok(0),
+        _ => Err(ClError::SizeNotAligned { bytes, elem }),
+    }
+}
+
+/// Error for a failed allocation of `len` bytes: `AllocTooLarge` if `len`
+/// exceeds `CL_DEVICE_MAX_MEM_ALLOC_SIZE` of any device in `ctx`, `err`
+/// otherwise. Devices that do not answer the query are not checked.
+// Erst nach dem Fehlschlag abfragen, damit der Normalfall keine Device-Queries kostet
+pub(crate) fn alloc_error(ctx: &Context, len: usize, err: ClError) -> ClError {
+    let max = ctx.devices().iter().filter_map(|&id| Device::new(id).max_mem_alloc_size().ok()).min();
+    match max {
+        Some(max) if len as u64 > max => ClError::AllocTooLarge { requested: len, max },
+        _ => err,
+    }
+}
+
 // Queued state implementation
 impl GpuBuffer<Queued> {
-    /// Create a new GPU buffer
+    /// Create a new GPU buffer.
+    ///
+    /// Fails with `AllocTooLarge` instead of the driver's error code if
+    /// `len` exceeds `CL_DEVICE_MAX_MEM_ALLOC_SIZE` of a device in `ctx`;
+    /// the limit is only queried once the allocation has failed.
     pub fn new(ctx: &Context, len: usize) -> Result<Self> {
+        Self::with_flags(ctx, len, CL_MEM_READ_WRITE)
+    }
+
+    /// `new` with explicit `cl_mem_flags`, e.g. `CL_MEM_READ_ONLY |
+    /// CL_MEM_HOST_WRITE_ONLY` for a kernel input the host never reads back.
+    ///
+    /// The host access flags are enforced at the API boundary: a write
+    /// (`enqueue_write`, `CL_MAP_WRITE`, ...) on a `CL_MEM_HOST_READ_ONLY`
+    /// buffer, or a read on a `CL_MEM_HOST_WRITE_ONLY` one, fails with
+    /// `FlagViolation` before anything is enqueued; `CL_MEM_HOST_NO_ACCESS`
+    /// rejects both. `CL_MEM_READ_ONLY`/`CL_MEM_WRITE_ONLY` restrict only
+    /// kernels, so host transfers on them stay allowed.
+    pub fn with_flags(ctx: &Context, len: usize, flags: cl_mem_flags) -> Result<Self> {
         #[cfg(feature = "metrics")]
-        {
-            crate::metrics::ALLOCS.fetch_add(1, Ordering::Relaxed);
-            crate::metrics::ALLOC_BYTES.fetch_add(len, Ordering::Relaxed);
-        }
+        crate::metrics::record_alloc(len);
 
         #[cfg(feature = "metrics")]
         let t = Instant::now();
 
-        let buf = Buffer::<u8>::create(ctx, CL_MEM_READ_WRITE, len, ptr::null_mut())?;
+        let buf = Buffer::<u8>::create(ctx, flags, len, ptr::null_mut())
+            .map_err(counted("GpuBuffer::new"))
+            .map_err(|e| alloc_error(ctx, len, e))?;
 
         #[cfg(feature = "metrics")]
         crate::metrics::record("GpuBuffer::new", t);
@@ -50,16 +143,32 @@ impl GpuBuffer<Queued> {
         Ok(Self { 
             buf, 
             len,
+            flags,
+            map_ptr: MapPtr::NULL,
+            sub: None,
+            #[cfg(feature = "buffer_registry")]
+            reg: registry::Entry::new::<Queued>(len),
             _state: PhantomData 
         })
     }
 
-    /// Enqueue write operation from host to device
+    /// `new` for a buffer meant to hold `T`: fails with `SizeNotAligned`
+    /// before allocating if `len` is not a multiple of `size_of::<T>()`
+    pub fn new_typed<T: Pod>(ctx: &Context, len: usize) -> Result<Self> {
+        elem_count::<T>(len)?;
+        Self::new(ctx, len)
+    }
+
+    /// Enqueue write operation from host to device.
+    ///
+    /// The `enqueue_write` metric is taken on completion and carries the
+    /// buffer size, so `summary()` reports its throughput.
     pub fn enqueue_write(
         mut self,
         queue: &CommandQueue,
         host: &[u8],
     ) -> Result<(GpuBuffer<InFlight>, GpuEventGuard)> {
+        self.check_host_access("enqueue_write", false, true)?;
         // Validate buffer size
         if host.len() != self.len {
             return Err(ClError::BufferSizeMismatch {
@@ -71,42 +180,21 @@ impl GpuBuffer<Queued> {
         #[cfg(feature = "metrics")]
         let t = Instant::now();
 
-        #[cfg(feature = "memtrace")]
-        let token_box = if crate::memtracer::is_auto_trace_enabled() {
-            Some(Box::new(crate::memtracer::start(crate::memtracer::Dir::H2D, host.len())))
-        } else {
-            None
-        };
-
         let evt = queue.enqueue_write_buffer(
             &mut self.buf,
             CL_NON_BLOCKING,
             0,
             host,
             &[],
-        )?;
+        ).map_err(counted("enqueue_write"))?;
 
         #[cfg(feature = "memtrace")]
-        if let Some(token_box) = token_box {
-            use opencl3::event::CL_COMPLETE;
-            let ptr = Box::into_raw(token_box) as *mut std::ffi::c_void;
-            if let Err(e) = evt.set_callback(CL_COMPLETE, crate::memtrace_callback, ptr) {
-                eprintln!("callback failed: {e}");
-                unsafe { Box::from_raw(ptr.cast::<crate::memtracer::CopyToken>()) }.finish();
-            }
-        }
+        crate::trace_on_complete(&evt, crate::memtracer::Dir::H2D, host.len());
 
         #[cfg(feature = "metrics")]
-        crate::metrics::record("enqueue_write", t);
+        crate::metrics::record_bytes_on_complete(&evt, "enqueue_write", t, self.len);
 
-        Ok((
-            GpuBuffer {
-                buf: self.buf,
-                len: self.len,
-                _state: PhantomData::<InFlight>,
-            },
-            GpuEventGuard::new(evt),
-        ))
+        Ok((self.transition(), GpuEventGuard::new(evt)))
     }
 
     /// Launch buffer operation
@@ -114,22 +202,53 @@ impl GpuBuffer<Queued> {
         #[cfg(feature = "metrics")]
         crate::metrics::record("launch", Instant::now());
         
-        GpuBuffer { 
-            buf: self.buf, 
-            len: self.len, 
-            _state: PhantomData 
-        }
+        self.transition()
     }
 }
 
 // Ready state implementation
 impl GpuBuffer<Ready> {
-    /// Enqueue read operation from device to host
+    /// Enqueue read operation from device to host; like `enqueue_write`,
+    /// the `enqueue_read` metric spans enqueue to completion with bytes
     pub fn enqueue_read(
         mut self,
         queue: &CommandQueue,
         host_out: &mut [u8],
     ) -> Result<(GpuBuffer<InFlight>, GpuEventGuard)> {
+        let evt = self.read_nonblocking(queue, host_out, &[])?;
+        Ok((self.transition(), GpuEventGuard::new(evt)))
+    }
+}
+
+impl GpuBuffer<InFlight> {
+    /// Read into `host_out` once the operation behind `guard` (e.g. the
+    /// kernel writing this buffer) has completed, without blocking the
+    /// host; `launch_after` for reads. The returned guard covers both.
+    ///
+    /// The `enqueue_read` metric and memtrace record start at enqueue, so
+    /// they include the wait for `guard`.
+    pub fn enqueue_read_after(
+        mut self,
+        queue: &CommandQueue,
+        host_out: &mut [u8],
+        guard: GpuEventGuard,
+    ) -> Result<(GpuBuffer<InFlight>, GpuEventGuard)> {
+        let dep = guard.into_event();
+        match self.read_nonblocking(queue, host_out, &[dep.get()]) {
+            Ok(evt) => Ok((self, GpuEventGuard::new(evt))),
+            Err(e) => {
+                // nichts eingereiht: wie beim Drop des Guards auf die Abhängigkeit warten
+                let _ = dep.wait();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<S: State> GpuBuffer<S> {
+    // gemeinsamer Teil von enqueue_read und enqueue_read_after
+    fn read_nonblocking(&mut self, queue: &CommandQueue, host_out: &mut [u8], wait: &[cl_event]) -> Result<Event> {
+        self.check_host_access("enqueue_read", true, false)?;
         if host_out.len() != self.len {
             return Err(ClError::BufferSizeMismatch {
                 expected: self.len,
@@ -140,42 +259,307 @@ impl GpuBuffer<Ready> {
         #[cfg(feature = "metrics")]
         let t = Instant::now();
 
-        #[cfg(feature = "memtrace")]
-        let token_box = if crate::memtracer::is_auto_trace_enabled() {
-            Some(Box::new(crate::memtracer::start(crate::memtracer::Dir::D2H, host_out.len())))
-        } else {
-            None
-        };
-
         let evt = queue.enqueue_read_buffer(
             &mut self.buf,
             CL_NON_BLOCKING,
             0,
             host_out,
-            &[],
-        )?;
+            wait,
+        ).map_err(counted("enqueue_read"))?;
 
         #[cfg(feature = "memtrace")]
-        if let Some(token_box) = token_box {
-            use opencl3::event::CL_COMPLETE;
-            let ptr = Box::into_raw(token_box) as *mut std::ffi::c_void;
-            if let Err(e) = evt.set_callback(CL_COMPLETE, crate::memtrace_callback, ptr) {
-                eprintln!("callback failed: {e}");
-                unsafe { Box::from_raw(ptr.cast::<crate::memtracer::CopyToken>()) }.finish();
-            }
+        crate::trace_on_complete(&evt, crate::memtracer::Dir::D2H, host_out.len());
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes_on_complete(&evt, "enqueue_read", t, self.len);
+
+        Ok(evt)
+    }
+}
+
+// Typed convenience (blocking)
+impl GpuBuffer<Ready> {
+    /// Allocate a buffer sized for `data` and upload it, waiting for completion
+    pub fn from_slice<T: Pod>(ctx: &Context, queue: &CommandQueue, data: &[T]) -> Result<Self> {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        let (inflight, guard) = GpuBuffer::<Queued>::new(ctx, bytes.len())?.enqueue_write(queue, bytes)?;
+        Ok(inflight.into_ready(guard))
+    }
+
+    /// Read the whole buffer into a new `Vec<T>` (blocking read)
+    pub fn read_to_vec<T: Pod>(&self, queue: &CommandQueue) -> Result<Vec<T>> {
+        self.check_host_access("read_to_vec", true, false)?;
+        let n = elem_count::<T>(self.len)?;
+
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        #[cfg(feature = "memtrace")]
+        let token = crate::memtracer::is_auto_trace_enabled()
+            .then(|| crate::memtracer::start(crate::memtracer::Dir::D2H, self.len));
+
+        let mut out = vec![T::zeroed(); n];
+        queue
+            .enqueue_read_buffer(&self.buf, CL_BLOCKING, 0, bytemuck::cast_slice_mut(&mut out), &[])
+            .map_err(counted("read_to_vec"))?;
+
+        #[cfg(feature = "memtrace")]
+        if let Some(token) = token {
+            token.finish();
+        }
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes("read_to_vec", t, self.len);
+
+        Ok(out)
+    }
+
+    /// Like `read_to_vec`, but reads into a reused `staging` area and returns
+    /// a `[T]` view of it (blocking read). No host allocation once the
+    /// staging area is large enough; it is page aligned, so any `T` fits.
+    pub fn enqueue_read_into<'s, T: Pod>(
+        &self,
+        queue: &CommandQueue,
+        staging: &'s mut StagingBuffer,
+    ) -> Result<&'s [T]> {
+        self.check_host_access("enqueue_read_into", true, false)?;
+        elem_count::<T>(self.len)?;
+
+        let host = staging.prepare(self.len)?;
+
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        #[cfg(feature = "memtrace")]
+        let token = crate::memtracer::is_auto_trace_enabled()
+            .then(|| crate::memtracer::start(crate::memtracer::Dir::D2H, self.len));
+
+        queue
+            .enqueue_read_buffer(&self.buf, CL_BLOCKING, 0, host, &[])
+            .map_err(counted("enqueue_read_into"))?;
+
+        #[cfg(feature = "memtrace")]
+        if let Some(token) = token {
+            token.finish();
         }
 
         #[cfg(feature = "metrics")]
-        crate::metrics::record("enqueue_read", t);
+        crate::metrics::record_bytes("enqueue_read_into", t, self.len);
 
-        Ok((
-            GpuBuffer {
-                buf: self.buf,
-                len: self.len,
-                _state: PhantomData::<InFlight>,
-            },
-            GpuEventGuard::new(evt),
-        ))
+        Ok(bytemuck::cast_slice(host))
+    }
+
+    /// Copy into a new buffer of another context (e.g. a device of a second
+    /// vendor) via a host bounce: a blocking read on `src_queue` into a
+    /// staging area, then a blocking write on `dst_queue` (a queue of
+    /// `dst_ctx`) into the new buffer. The copy keeps the kernel and host
+    /// access flags; a `CL_MEM_HOST_READ_ONLY` or `CL_MEM_HOST_NO_ACCESS`
+    /// copy forbids the write, so it is created from the staging area
+    /// (`CL_MEM_COPY_HOST_PTR`) instead. Both legs are traced; the metric is
+    /// one `copy_to_context` sample with the buffer size.
+    ///
+    /// Crosses the bus twice and goes through host memory, so it is much
+    /// slower than a copy within one context; use it only across contexts.
+    pub fn copy_to_context(
+        &self,
+        src_queue: &CommandQueue,
+        dst_ctx: &Context,
+        dst_queue: &CommandQueue,
+    ) -> Result<GpuBuffer<Ready>> {
+        self.check_host_access("copy_to_context", true, false)?;
+
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let mut staging = StagingBuffer::with_capacity(self.len)?;
+        let host = staging.prepare(self.len)?;
+
+        #[cfg(feature = "memtrace")]
+        let token = crate::memtracer::is_auto_trace_enabled()
+            .then(|| crate::memtracer::start(crate::memtracer::Dir::D2H, self.len));
+        src_queue
+            .enqueue_read_buffer(&self.buf, CL_BLOCKING, 0, host, &[])
+            .map_err(counted("copy_to_context"))?;
+        #[cfg(feature = "memtrace")]
+        if let Some(token) = token {
+            token.finish();
+        }
+
+        // Host-Pointer-Flags gehören zum Quellpuffer und werden nicht übernommen
+        let host_ptr_flags = CL_MEM_USE_HOST_PTR | CL_MEM_ALLOC_HOST_PTR | CL_MEM_COPY_HOST_PTR;
+        let flags = self.flags & !host_ptr_flags;
+        // enqueue_write ist bei HOST_READ_ONLY/NO_ACCESS verboten, COPY_HOST_PTR
+        // lädt dann schon beim Erzeugen hoch
+        let write_forbidden = flags & (CL_MEM_HOST_READ_ONLY | CL_MEM_HOST_NO_ACCESS) != 0;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_alloc(self.len);
+
+        #[cfg(feature = "memtrace")]
+        let token = crate::memtracer::is_auto_trace_enabled()
+            .then(|| crate::memtracer::start(crate::memtracer::Dir::H2D, self.len));
+        let buf = if write_forbidden {
+            Buffer::<u8>::create(dst_ctx, flags | CL_MEM_COPY_HOST_PTR, self.len, host.as_mut_ptr().cast::<c_void>())
+                .map_err(counted("copy_to_context"))
+                .map_err(|e| alloc_error(dst_ctx, self.len, e))?
+        } else {
+            let mut buf = Buffer::<u8>::create(dst_ctx, flags, self.len, ptr::null_mut())
+                .map_err(counted("copy_to_context"))
+                .map_err(|e| alloc_error(dst_ctx, self.len, e))?;
+            // blockierend: die Staging-Fläche lebt nur bis zum Ende der Funktion
+            dst_queue
+                .enqueue_write_buffer(&mut buf, CL_BLOCKING, 0, host, &[])
+                .map_err(counted("copy_to_context"))?;
+            buf
+        };
+        #[cfg(feature = "memtrace")]
+        if let Some(token) = token {
+            token.finish();
+        }
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes("copy_to_context", t, self.len);
+
+        Ok(GpuBuffer {
+            buf,
+            len: self.len,
+            flags,
+            map_ptr: MapPtr::NULL,
+            sub: None,
+            #[cfg(feature = "buffer_registry")]
+            reg: registry::Entry::new::<Ready>(self.len),
+            _state: PhantomData,
+        })
+    }
+}
+
+// Ready -> Mapped
+impl GpuBuffer<Ready> {
+    /// Map the whole buffer for host writes as `[T]` (blocking map).
+    ///
+    /// The slice is reached through `as_mut_slice()` on the returned buffer,
+    /// so it cannot outlive `unmap()`. Its length is `len() / size_of::<T>()`;
+    /// trailing bytes that do not fill a whole `T` are not exposed.
+    #[must_use = "a mapped buffer must be returned with unmap()"]
+    pub fn enqueue_map_typed<T: Pod>(self, queue: &CommandQueue) -> Result<GpuBuffer<Mapped<T>>> {
+        self.map_as::<T, Mapped<T>>(queue, CL_MAP_WRITE)
+    }
+
+    /// Map the whole buffer read-only as `[T]` (blocking map).
+    ///
+    /// The returned buffer only hands out `&[T]` via `as_slice()`, which
+    /// lets results be inspected without a copy on devices that share host
+    /// memory. `unmap()` returns it to `Ready`.
+    #[must_use = "a mapped buffer must be returned with unmap()"]
+    pub fn enqueue_map_read<T: Pod>(self, queue: &CommandQueue) -> Result<GpuBuffer<MappedRead<T>>> {
+        self.map_as::<T, MappedRead<T>>(queue, CL_MAP_READ)
+    }
+
+    // Gemeinsamer Pfad für beide Map-Varianten: mappen, Alignment prüfen
+    fn map_as<T: Pod, To: State>(self, queue: &CommandQueue, flags: cl_map_flags) -> Result<GpuBuffer<To>>
+    where
+        Ready: Transition<To>,
+    {
+        self.check_host_access("enqueue_map", flags & CL_MAP_READ != 0, flags & CL_MAP_WRITE != 0)?;
+
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let mut host_ptr: *mut c_void = ptr::null_mut();
+        queue
+            .enqueue_map_buffer(&self.buf, CL_BLOCKING, flags, 0, self.len, &mut host_ptr, &[])
+            .map_err(counted("enqueue_map"))?;
+
+        let align = std::mem::align_of::<T>();
+        if !(host_ptr as usize).is_multiple_of(align) {
+            queue.enqueue_unmap_mem_object(self.buf.get(), host_ptr, &[])?.wait()?;
+            return Err(ClError::Misaligned { align });
+        }
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record("enqueue_map", t);
+
+        let mut mapped: GpuBuffer<To> = self.transition();
+        mapped.map_ptr = MapPtr(host_ptr);
+        Ok(mapped)
+    }
+}
+
+// Mapped state implementation
+impl<T: Pod> GpuBuffer<Mapped<T>> {
+    /// Number of `T` elements visible through the mapping
+    pub fn mapped_len(&self) -> usize {
+        self.len / std::mem::size_of::<T>()
+    }
+
+    /// Mapped region as a typed slice
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: map_ptr points to a live mapping of at least `len` bytes,
+        // alignment was checked in enqueue_map_typed, and T is Pod.
+        unsafe { std::slice::from_raw_parts(self.map_ptr.0 as *const T, self.mapped_len()) }
+    }
+
+    /// Mapped region as a mutable typed slice
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: as in `as_slice`; `&mut self` guarantees exclusive access.
+        unsafe { std::slice::from_raw_parts_mut(self.map_ptr.0 as *mut T, self.mapped_len()) }
+    }
+
+    /// Unmap and wait until the device sees the host writes.
+    ///
+    /// A mapped buffer must be unmapped before it is dropped; dropping it
+    /// mapped leaks the mapping and prints a warning.
+    pub fn unmap(self, queue: &CommandQueue) -> Result<GpuBuffer<Ready>> {
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        queue
+            .enqueue_unmap_mem_object(self.buf.get(), self.map_ptr.0, &[])
+            .and_then(|evt| evt.wait())
+            .map_err(counted("unmap"))?;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record("unmap", t);
+
+        let mut ready: GpuBuffer<Ready> = self.transition();
+        ready.map_ptr.0 = ptr::null_mut();
+        Ok(ready)
+    }
+}
+
+// MappedRead state implementation
+impl<T: Pod> GpuBuffer<MappedRead<T>> {
+    /// Number of `T` elements visible through the mapping
+    pub fn mapped_len(&self) -> usize {
+        self.len / std::mem::size_of::<T>()
+    }
+
+    /// Mapped region as a typed slice; there is no mutable counterpart
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: as for `Mapped<T>::as_slice`; the mapping stays live until
+        // `unmap(self)`, which cannot run while the slice is borrowed.
+        unsafe { std::slice::from_raw_parts(self.map_ptr.0 as *const T, self.mapped_len()) }
+    }
+
+    /// Unmap the read-only mapping.
+    ///
+    /// As with `Mapped<T>`, dropping the buffer still mapped leaks the
+    /// mapping and prints a warning.
+    pub fn unmap(self, queue: &CommandQueue) -> Result<GpuBuffer<Ready>> {
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        queue
+            .enqueue_unmap_mem_object(self.buf.get(), self.map_ptr.0, &[])
+            .and_then(|evt| evt.wait())
+            .map_err(counted("unmap"))?;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record("unmap", t);
+
+        let mut ready: GpuBuffer<Ready> = self.transition();
+        ready.map_ptr.0 = ptr::null_mut();
+        Ok(ready)
     }
 }
 
@@ -188,11 +572,7 @@ impl GpuBuffer<InFlight> {
         #[cfg(feature = "metrics")]
         crate::metrics::record("complete", Instant::now());
         
-        GpuBuffer { 
-            buf: self.buf, 
-            len: self.len, 
-            _state: PhantomData 
-        }
+        self.transition()
     }
 
     /// Transition to Ready with guard
@@ -200,16 +580,119 @@ impl GpuBuffer<InFlight> {
         #[cfg(feature = "metrics")]
         crate::metrics::record("into_ready", Instant::now());
         
-        GpuBuffer { 
-            buf: self.buf, 
-            len: self.len, 
-            _state: PhantomData 
+        self.transition()
+    }
+
+    /// Transition to Ready once the guard's event completes, giving up after `timeout`.
+    ///
+    /// On timeout (`ClError::Timeout`) or an event error the buffer stays
+    /// `InFlight` and is handed back together with its guard, so the caller can
+    /// retry or abandon it. Dropping the guard still blocks until completion.
+    pub fn try_into_ready_timeout(
+        self,
+        guard: GpuEventGuard,
+        timeout: std::time::Duration,
+    ) -> std::result::Result<GpuBuffer<Ready>, (Self, GpuEventGuard, ClError)> {
+        match guard.wait_timeout(timeout) {
+            Ok(()) => Ok(self.into_ready(guard)),
+            Err(e) => Err((self, guard, e)),
+        }
+    }
+
+    /// `try_into_ready_timeout` repeated up to `max_retries` more times while
+    /// it times out, pausing `policy.next_delay(n)` before retry `n`. An event
+    /// error ends the loop at once; the result of the last attempt is returned.
+    #[allow(clippy::result_large_err)] // gleiche Rückgabe wie try_into_ready_timeout
+    pub fn try_into_ready_retry(
+        self,
+        guard: GpuEventGuard,
+        timeout: std::time::Duration,
+        policy: &crate::BackoffPolicy,
+        max_retries: u32,
+    ) -> std::result::Result<GpuBuffer<Ready>, (Self, GpuEventGuard, ClError)> {
+        let (mut buf, mut guard) = (self, guard);
+        let mut attempt = 0;
+        loop {
+            match buf.try_into_ready_timeout(guard, timeout) {
+                Err((b, g, ClError::Timeout(_))) if attempt < max_retries => {
+                    attempt += 1;
+                    std::thread::sleep(policy.next_delay(attempt));
+                    (buf, guard) = (b, g);
+                }
+                done => return done,
+            }
         }
     }
 }
 
+/// Wait for several in-flight buffers with a single `clWaitForEvents` and
+/// return them as `Ready`, in the order given.
+///
+/// For the "enqueue several transfers, then wait once" pattern. On an event
+/// error nothing is returned; the guards are dropped, which still blocks
+/// until every command has finished.
+pub fn into_ready_all(
+    pending: Vec<(GpuBuffer<InFlight>, GpuEventGuard)>,
+) -> Result<Vec<GpuBuffer<Ready>>> {
+    #[cfg(feature = "metrics")]
+    let t = Instant::now();
+
+    let events: Vec<_> = pending.iter().map(|(_, g)| g.event().get()).collect();
+    if !events.is_empty() {
+        opencl3::event::wait_for_events(&events).map_err(counted("wait_all"))?;
+    }
+
+    #[cfg(feature = "metrics")]
+    crate::metrics::record("wait_all", t);
+
+    // Events sind fertig, das Warten im Drop der Guards kehrt sofort zurück
+    Ok(pending.into_iter().map(|(buf, g)| buf.into_ready(g)).collect())
+}
+
 // Common methods for all states
 impl<S: State> GpuBuffer<S> {
+    /// Move the buffer into state `To`; only edges declared via `Transition` compile
+    fn transition<To: State>(self) -> GpuBuffer<To>
+    where
+        S: Transition<To>,
+    {
+        #[cfg(feature = "buffer_registry")]
+        self.reg.set_state::<To>();
+
+        GpuBuffer {
+            buf: self.buf,
+            len: self.len,
+            flags: self.flags,
+            map_ptr: self.map_ptr,
+            sub: self.sub,
+            #[cfg(feature = "buffer_registry")]
+            reg: self.reg,
+            _state: PhantomData,
+        }
+    }
+
+    /// Name of the current state (`S::NAME`), for code that only holds a
+    /// type-erased handle
+    pub fn state_name(&self) -> &'static str {
+        S::NAME
+    }
+
+    /// `cl_mem_flags` the buffer was created with (`CL_MEM_READ_WRITE` for `new`)
+    pub fn flags(&self) -> cl_mem_flags {
+        self.flags
+    }
+
+    /// `FlagViolation` if the host access flags forbid a host `read` or
+    /// `write` of this buffer
+    pub(crate) fn check_host_access(&self, op: &'static str, read: bool, write: bool) -> Result<()> {
+        let no_read = CL_MEM_HOST_WRITE_ONLY | CL_MEM_HOST_NO_ACCESS;
+        let no_write = CL_MEM_HOST_READ_ONLY | CL_MEM_HOST_NO_ACCESS;
+        if (read && self.flags & no_read != 0) || (write && self.flags & no_write != 0) {
+            return Err(ClError::FlagViolation { op, flags: self.flags });
+        }
+        Ok(())
+    }
+
     /// Get raw OpenCL buffer reference
     pub fn raw(&self) -> &Buffer<u8> { 
         &self.buf 
@@ -229,4 +712,18 @@ impl<S: State> GpuBuffer<S> {
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
-}
\ No newline at end of file
+
+    /// Check whether both handles refer to the same device allocation (`cl_mem`)
+    pub fn same_as<S2: State>(&self, other: &GpuBuffer<S2>) -> bool {
+        self.buf.get() == other.buf.get()
+    }
+}
+
+/// Identity, not content: equal iff both wrap the same `cl_mem`
+impl<S: State> PartialEq for GpuBuffer<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_as(other)
+    }
+}
+
+impl<S: State> Eq for GpuBuffer<S> {}
\ No newline at end of file
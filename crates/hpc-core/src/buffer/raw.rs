@@ -0,0 +1,43 @@
+//! Borrowed `cl_mem` handle for FFI
+
+use opencl3::{memory::ClMem, types::cl_mem};
+use std::{marker::PhantomData, ops::Deref};
+
+use super::{GpuBuffer, State};
+
+/// The `cl_mem` of a `GpuBuffer`, valid while the guard lives.
+///
+/// The guard borrows the buffer, so it cannot be dropped (and the handle
+/// released) while the guard exists. `*guard` copies the plain pointer
+/// out, and the compiler does not track that copy:
+///
+/// - keep the guard alive until the FFI call has returned, i.e. bind it
+///   with `let` instead of dereferencing a temporary;
+/// - never store the pointer (in a struct, a static, a C-side context);
+///   it dangles as soon as the guard and then the buffer are dropped.
+///
+/// A C library that keeps the handle beyond the call must take its own
+/// reference with `clRetainMemObject`.
+#[derive(Debug)]
+pub struct RawMemGuard<'a> {
+    mem: cl_mem,
+    _borrow: PhantomData<&'a ()>,
+}
+
+impl Deref for RawMemGuard<'_> {
+    type Target = cl_mem;
+
+    fn deref(&self) -> &cl_mem {
+        &self.mem
+    }
+}
+
+impl<S: State> GpuBuffer<S> {
+    /// Borrow the raw `cl_mem` for an FFI call, see `RawMemGuard`.
+    ///
+    /// Only the lifetime is guarded, not the state: on an `InFlight`
+    /// buffer a pending command may still access the memory.
+    pub fn as_raw_guard(&self) -> RawMemGuard<'_> {
+        RawMemGuard { mem: self.buf.get(), _borrow: PhantomData }
+    }
+}
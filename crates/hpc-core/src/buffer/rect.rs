@@ -0,0 +1,151 @@
+//! Rectangular (2D/3D) transfers via `clEnqueue{Write,Read}BufferRect`
+
+use opencl3::command_queue::CommandQueue;
+use opencl3::types::CL_NON_BLOCKING;
+use std::ffi::c_void;
+
+use super::{GpuBuffer, GpuEventGuard, InFlight, Queued, Ready};
+use crate::error::{counted, ClError, Result};
+
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Geometry of a rectangular transfer, all values in bytes.
+///
+/// `region[0]` is the row width in bytes, `region[1]` the number of rows,
+/// `region[2]` the number of slices. A pitch of 0 means tightly packed
+/// (`row_pitch = region[0]`, `slice_pitch = region[1] * row_pitch`).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rect {
+    pub buffer_origin: [usize; 3],
+    pub host_origin: [usize; 3],
+    pub region: [usize; 3],
+    pub buffer_row_pitch: usize,
+    pub buffer_slice_pitch: usize,
+    pub host_row_pitch: usize,
+    pub host_slice_pitch: usize,
+}
+
+impl Rect {
+    /// Bytes covered by `region`
+    pub fn bytes(&self) -> usize {
+        self.region.iter().product()
+    }
+
+    // Prüft eine Seite (Buffer oder Host) gegen ihre Länge
+    fn check_side(&self, side: &str, origin: [usize; 3], row: usize, slice: usize, len: usize) -> Result<()> {
+        let [w, h, _] = self.region;
+        let overflow = || ClError::InvalidRect(format!("{} geometry of {:?} overflows usize", side, self.region));
+        let row = if row == 0 { w } else { row };
+        let packed_slice = h.checked_mul(row).ok_or_else(overflow)?;
+        let slice = if slice == 0 { packed_slice } else { slice };
+        if row < w || slice < packed_slice {
+            return Err(ClError::InvalidRect(format!(
+                "{} pitches ({}, {}) smaller than region {:?}", side, row, slice, self.region
+            )));
+        }
+        let end = self.end(origin, row, slice).ok_or_else(overflow)?;
+        if end > len {
+            return Err(ClError::InvalidRect(format!(
+                "{} region ends at byte {}, length is {}", side, end, len
+            )));
+        }
+        Ok(())
+    }
+
+    // Ende hinter dem letzten Byte der Region; None bei Überlauf
+    fn end(&self, origin: [usize; 3], row: usize, slice: usize) -> Option<usize> {
+        let [w, h, d] = self.region;
+        let z = origin[2].checked_add(d - 1)?.checked_mul(slice)?;
+        let y = origin[1].checked_add(h - 1)?.checked_mul(row)?;
+        z.checked_add(y)?.checked_add(origin[0])?.checked_add(w)
+    }
+
+    /// Check the geometry against the buffer and host lengths, as
+    /// `enqueue_write_rect`/`enqueue_read_rect` do before enqueueing: an
+    /// empty region, pitches below the region or a region reaching past
+    /// either end (including offsets that overflow `usize`) are `InvalidRect`.
+    pub fn validate(&self, buffer_len: usize, host_len: usize) -> Result<()> {
+        if self.region.contains(&0) {
+            return Err(ClError::InvalidRect(format!("empty region {:?}", self.region)));
+        }
+        self.check_side("buffer", self.buffer_origin, self.buffer_row_pitch, self.buffer_slice_pitch, buffer_len)?;
+        self.check_side("host", self.host_origin, self.host_row_pitch, self.host_slice_pitch, host_len)
+    }
+}
+
+impl GpuBuffer<Queued> {
+    /// Enqueue a strided host-to-device copy of `rect.region`
+    pub fn enqueue_write_rect(
+        mut self,
+        queue: &CommandQueue,
+        host: &[u8],
+        rect: &Rect,
+    ) -> Result<(GpuBuffer<InFlight>, GpuEventGuard)> {
+        self.check_host_access("enqueue_write_rect", false, true)?;
+        rect.validate(self.len, host.len())?;
+
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let evt = queue.enqueue_write_buffer_rect(
+            &mut self.buf,
+            CL_NON_BLOCKING,
+            rect.buffer_origin.as_ptr(),
+            rect.host_origin.as_ptr(),
+            rect.region.as_ptr(),
+            rect.buffer_row_pitch,
+            rect.buffer_slice_pitch,
+            rect.host_row_pitch,
+            rect.host_slice_pitch,
+            host.as_ptr() as *mut c_void,
+            &[],
+        ).map_err(counted("enqueue_write_rect"))?;
+
+        #[cfg(feature = "memtrace")]
+        crate::trace_on_complete(&evt, crate::memtracer::Dir::H2D, rect.bytes());
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes_on_complete(&evt, "enqueue_write_rect", t, rect.bytes());
+
+        Ok((self.transition(), GpuEventGuard::new(evt)))
+    }
+}
+
+impl GpuBuffer<Ready> {
+    /// Enqueue a strided device-to-host copy of `rect.region`
+    pub fn enqueue_read_rect(
+        self,
+        queue: &CommandQueue,
+        host_out: &mut [u8],
+        rect: &Rect,
+    ) -> Result<(GpuBuffer<InFlight>, GpuEventGuard)> {
+        self.check_host_access("enqueue_read_rect", true, false)?;
+        rect.validate(self.len, host_out.len())?;
+
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let evt = queue.enqueue_read_buffer_rect(
+            &self.buf,
+            CL_NON_BLOCKING,
+            rect.buffer_origin.as_ptr(),
+            rect.host_origin.as_ptr(),
+            rect.region.as_ptr(),
+            rect.buffer_row_pitch,
+            rect.buffer_slice_pitch,
+            rect.host_row_pitch,
+            rect.host_slice_pitch,
+            host_out.as_mut_ptr() as *mut c_void,
+            &[],
+        ).map_err(counted("enqueue_read_rect"))?;
+
+        #[cfg(feature = "memtrace")]
+        crate::trace_on_complete(&evt, crate::memtracer::Dir::D2H, rect.bytes());
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes_on_complete(&evt, "enqueue_read_rect", t, rect.bytes());
+
+        Ok((self.transition(), GpuEventGuard::new(evt)))
+    }
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "buffer_registry")]
+
+//! Debug registry of live buffers and their current state
+
+use once_cell::sync::Lazy;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+/// id -> (state name, length in bytes)
+static LIVE: Lazy<Mutex<BTreeMap<u64, (&'static str, usize)>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Registry slot of one `GpuBuffer`; moved along on every transition and
+/// unregistered on drop
+pub(crate) struct Entry(u64);
+
+impl Entry {
+    pub(crate) fn new<S>(len: usize) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        LIVE.lock().unwrap().insert(id, (state_name::<S>(), len));
+        Entry(id)
+    }
+
+    pub(crate) fn set_state<S>(&self) {
+        if let Some(slot) = LIVE.lock().unwrap().get_mut(&self.0) {
+            slot.0 = state_name::<S>();
+        }
+    }
+}
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        LIVE.lock().unwrap().remove(&self.0);
+    }
+}
+
+// "hpc_core::buffer::state::InFlight" -> "InFlight" (Generics bleiben erhalten)
+fn state_name<S>() -> &'static str {
+    let full = std::any::type_name::<S>();
+    let path_end = full.find('<').unwrap_or(full.len());
+    match full[..path_end].rfind("::") {
+        Some(i) => &full[i + 2..],
+        None => full,
+    }
+}
+
+/// Live buffers currently in state `state` (e.g. "InFlight")
+pub(crate) fn count_in_state(state: &str) -> usize {
+    LIVE.lock().unwrap().values().filter(|(s, _)| *s == state).count()
+}
+
+/// Panic listing every live buffer that is not `Ready` (still `Queued`,
+/// `InFlight`, `Mapped` or `MappedRead`). Call before exit or at the end of a test to
+/// catch operations that were enqueued but never awaited.
+pub fn assert_all_ready() {
+    let live = LIVE.lock().unwrap();
+    let pending: Vec<String> = live
+        .iter()
+        .filter(|(_, (state, _))| *state != "Ready")
+        .map(|(id, (state, len))| format!("#{} {} ({} bytes)", id, state, len))
+        .collect();
+    if !pending.is_empty() {
+        panic!("{} buffer(s) not Ready: {}", pending.len(), pending.join(", "));
+    }
+}
@@ -1,27 +1,98 @@
 //! Type-state pattern for compile-time state checking
 
+use bytemuck::Pod;
+use std::marker::PhantomData;
+
 /// Sealed trait pattern for state types
 mod sealed {
     pub trait Sealed {}
 }
 
 /// State trait for GPU buffer states
-pub trait State: sealed::Sealed + std::fmt::Debug + Send + Sync {}
+pub trait State: sealed::Sealed + std::fmt::Debug + Send + Sync {
+    /// Short state name for logs and registries, e.g. "InFlight"
+    const NAME: &'static str;
+}
 
 /// Buffer is queued and ready for operations
 #[derive(Debug, Clone, Copy)]
 pub struct Queued;
 impl sealed::Sealed for Queued {}
-impl State for Queued {}
+impl State for Queued {
+    const NAME: &'static str = "Queued";
+}
 
 /// Buffer operation is in flight
 #[derive(Debug, Clone, Copy)]
 pub struct InFlight;
 impl sealed::Sealed for InFlight {}
-impl State for InFlight {}
+impl State for InFlight {
+    const NAME: &'static str = "InFlight";
+}
 
 /// Buffer is ready for use
 #[derive(Debug, Clone, Copy)]
 pub struct Ready;
 impl sealed::Sealed for Ready {}
-impl State for Ready {}
\ No newline at end of file
+impl State for Ready {
+    const NAME: &'static str = "Ready";
+}
+
+/// Buffer is mapped into host memory as `[T]`
+pub struct Mapped<T>(PhantomData<fn() -> T>);
+impl<T: Pod> sealed::Sealed for Mapped<T> {}
+impl<T: Pod> State for Mapped<T> {
+    const NAME: &'static str = "Mapped";
+}
+
+impl<T> std::fmt::Debug for Mapped<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Mapped<{}>", std::any::type_name::<T>())
+    }
+}
+
+/// Buffer is mapped read-only into host memory as `[T]`
+pub struct MappedRead<T>(PhantomData<fn() -> T>);
+impl<T: Pod> sealed::Sealed for MappedRead<T> {}
+impl<T: Pod> State for MappedRead<T> {
+    const NAME: &'static str = "MappedRead";
+}
+
+impl<T> std::fmt::Debug for MappedRead<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MappedRead<{}>", std::any::type_name::<T>())
+    }
+}
+
+/// Buffer wraps an OpenGL buffer object that GL currently owns
+/// (`from_gl_buffer`, `GlBuffer::release_gl`); `acquire_gl` hands it to
+/// OpenCL as a `GlBuffer`
+#[cfg(feature = "gl-interop")]
+#[derive(Debug, Clone, Copy)]
+pub struct GlReleased;
+#[cfg(feature = "gl-interop")]
+impl sealed::Sealed for GlReleased {}
+#[cfg(feature = "gl-interop")]
+impl State for GlReleased {
+    const NAME: &'static str = "GlReleased";
+}
+
+/// Legal state transition `Self -> To`.
+///
+/// The complete transition graph is declared here and nowhere else; a
+/// `GpuBuffer` can only move between states that have an edge below, so a
+/// new state has to declare its edges before any method can produce it.
+/// Sealed through `State`: only the states in this module can implement it.
+pub trait Transition<To: State>: State {}
+
+impl Transition<InFlight> for Queued {}   // enqueue_write / launch
+impl Transition<Ready> for InFlight {}    // into_ready / complete
+impl Transition<InFlight> for Ready {}    // enqueue_read
+impl<T: Pod> Transition<Mapped<T>> for Ready {}  // enqueue_map_typed
+impl<T: Pod> Transition<Ready> for Mapped<T> {}  // unmap
+impl<T: Pod> Transition<MappedRead<T>> for Ready {}  // enqueue_map_read
+impl<T: Pod> Transition<Ready> for MappedRead<T> {}  // unmap
+#[cfg(feature = "gl-interop")]
+impl Transition<Ready> for GlReleased {}  // acquire_gl
+#[cfg(feature = "gl-interop")]
+impl Transition<GlReleased> for Ready {}  // GlBuffer::release_gl, nur für GL-Puffer
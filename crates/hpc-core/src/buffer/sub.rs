@@ -0,0 +1,116 @@
+//! Sub-buffers (`clCreateSubBuffer`) and overlap of their byte ranges
+
+use opencl3::memory::{
+    ClMem, CL_MEM_HOST_NO_ACCESS, CL_MEM_HOST_READ_ONLY, CL_MEM_HOST_WRITE_ONLY, CL_MEM_READ_ONLY,
+    CL_MEM_READ_WRITE, CL_MEM_WRITE_ONLY,
+};
+use std::{marker::PhantomData, ops::Deref};
+
+use super::{GpuBuffer, MapPtr, Ready, State};
+use crate::error::{ClError, Result};
+
+/// Position of a sub-buffer inside its parent allocation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SubRegion {
+    /// Parent `cl_mem` as address: identity only, never dereferenced.
+    /// Unique while compared, because the view borrows the live parent.
+    parent: usize,
+    offset: usize,
+}
+
+/// A sub-buffer view that borrows its parent for `'a`.
+///
+/// While a view exists the parent cannot be dropped, moved to another
+/// state or written via `enqueue_write_exclusive`. The view itself derefs
+/// to a shared `GpuBuffer<Ready>`, so it supports shared reads and kernel
+/// arguments but no state changes; host writes go through the parent
+/// once the views are gone.
+pub struct SubBuffer<'a> {
+    buf: GpuBuffer<Ready>,
+    _parent: PhantomData<&'a GpuBuffer<Ready>>,
+}
+
+impl Deref for SubBuffer<'_> {
+    type Target = GpuBuffer<Ready>;
+
+    fn deref(&self) -> &GpuBuffer<Ready> {
+        &self.buf
+    }
+}
+
+impl GpuBuffer<Ready> {
+    /// Sub-buffer viewing bytes `[offset, offset + len)` of this buffer.
+    ///
+    /// `offset` must be a multiple of `CL_DEVICE_MEM_BASE_ADDR_ALIGN` (bits)
+    /// and the range must lie inside the buffer; OpenCL rejects both
+    /// otherwise, as well as sub-buffers of sub-buffers.
+    pub fn sub_buffer(&self, offset: usize, len: usize) -> Result<SubBuffer<'_>> {
+        // Kernel-Zugriff wie beim Parent (READ_WRITE auf READ_ONLY wäre
+        // CL_INVALID_VALUE); Host-Zugriffsflags erbt der Sub-Buffer ohnehin
+        let kernel_access = CL_MEM_READ_WRITE | CL_MEM_READ_ONLY | CL_MEM_WRITE_ONLY;
+        let buf = self.buf.create_sub_buffer(self.flags & kernel_access, offset, len)?;
+        let host_access = CL_MEM_HOST_WRITE_ONLY | CL_MEM_HOST_READ_ONLY | CL_MEM_HOST_NO_ACCESS;
+        Ok(SubBuffer {
+            buf: GpuBuffer {
+                buf,
+                len,
+                flags: self.flags & (kernel_access | host_access),
+                map_ptr: MapPtr::NULL,
+                sub: Some(SubRegion { parent: self.buf.get() as usize, offset }),
+                #[cfg(feature = "buffer_registry")]
+                reg: super::registry::Entry::new::<Ready>(len),
+                _state: PhantomData,
+            },
+            _parent: PhantomData,
+        })
+    }
+
+    /// Split into the sub-buffers `[0, offset)` and `[offset, len())`, the
+    /// buffer analog of `slice::split_at`.
+    ///
+    /// Same rules as `sub_buffer`: `offset` must be a multiple of
+    /// `CL_DEVICE_MEM_BASE_ADDR_ALIGN` (`CL_MISALIGNED_SUB_BUFFER_OFFSET`
+    /// otherwise) and this buffer must not itself be a sub-buffer. An
+    /// `offset` of 0 or `len()` and beyond is `SplitOutOfRange`. Both
+    /// halves keep the parent allocation alive; dropping them releases only
+    /// the sub-buffer objects, never the parent's memory.
+    pub fn split_at(&self, offset: usize) -> Result<(SubBuffer<'_>, SubBuffer<'_>)> {
+        // leere Hälften lehnt clCreateSubBuffer ohnehin ab (size 0)
+        if offset == 0 || offset >= self.len {
+            return Err(ClError::SplitOutOfRange { offset, len: self.len });
+        }
+        Ok((self.sub_buffer(0, offset)?, self.sub_buffer(offset, self.len - offset)?))
+    }
+}
+
+impl<S: State> GpuBuffer<S> {
+    /// Byte offset inside the parent, `None` for a buffer that is not a sub-buffer
+    pub fn sub_offset(&self) -> Option<usize> {
+        self.sub.map(|s| s.offset)
+    }
+
+    // (Wurzel-Allokation, Start, Ende) in Bytes
+    fn byte_range(&self) -> (usize, usize, usize) {
+        match self.sub {
+            Some(s) => (s.parent, s.offset, s.offset + self.len),
+            None => (self.buf.get() as usize, 0, self.len),
+        }
+    }
+}
+
+/// Bytes shared by `a` and `b`: the intersection of their
+/// `[offset, offset + len)` ranges within the same parent allocation
+/// (a whole buffer counts as its own range starting at 0). 0 if they belong
+/// to different allocations or do not overlap.
+///
+/// Fed into `trace_abort`'s `conflict_sz`, this makes `conflict_avg` in
+/// memtrace_abort.csv a byte figure. Views are passed dereferenced,
+/// e.g. `overlap_bytes(&*a, &*b)`.
+pub fn overlap_bytes<A: State, B: State>(a: &GpuBuffer<A>, b: &GpuBuffer<B>) -> usize {
+    let (root_a, start_a, end_a) = a.byte_range();
+    let (root_b, start_b, end_b) = b.byte_range();
+    if root_a != root_b {
+        return 0;
+    }
+    end_a.min(end_b).saturating_sub(start_a.max(start_b))
+}
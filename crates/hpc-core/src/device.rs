@@ -0,0 +1,113 @@
+//! Platform and device enumeration for diagnostics ("clinfo lite")
+
+use opencl3::{
+    device::{Device, CL_DEVICE_TYPE_ACCELERATOR, CL_DEVICE_TYPE_ALL, CL_DEVICE_TYPE_CPU, CL_DEVICE_TYPE_GPU},
+    platform::get_platforms,
+    types::{cl_device_id, cl_device_type, cl_platform_id},
+};
+use std::fmt;
+
+use crate::error::Result;
+
+/// Coarse device class from `CL_DEVICE_TYPE`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceKind {
+    Gpu,
+    Cpu,
+    Accelerator,
+    Other,
+}
+
+impl DeviceKind {
+    fn from_type(t: cl_device_type) -> Self {
+        if t & CL_DEVICE_TYPE_GPU != 0 {
+            DeviceKind::Gpu
+        } else if t & CL_DEVICE_TYPE_CPU != 0 {
+            DeviceKind::Cpu
+        } else if t & CL_DEVICE_TYPE_ACCELERATOR != 0 {
+            DeviceKind::Accelerator
+        } else {
+            DeviceKind::Other
+        }
+    }
+}
+
+impl fmt::Display for DeviceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DeviceKind::Gpu => "GPU",
+            DeviceKind::Cpu => "CPU",
+            DeviceKind::Accelerator => "Accelerator",
+            DeviceKind::Other => "Other",
+        })
+    }
+}
+
+/// One device as reported by its platform
+#[derive(Clone, Debug)]
+pub struct DeviceInfo {
+    pub id: cl_device_id,
+    pub kind: DeviceKind,
+    pub name: String,
+    pub compute_units: u32,
+    pub global_mem_bytes: u64,
+    /// `CL_DEVICE_HOST_UNIFIED_MEMORY`: shares memory with the host
+    /// (integrated GPU, CPU device), so transfers do not cross PCIe
+    pub unified_memory: bool,
+}
+
+/// One platform (ICD) with all of its devices, in driver order
+#[derive(Clone, Debug)]
+pub struct PlatformInfo {
+    pub id: cl_platform_id,
+    pub name: String,
+    pub vendor: String,
+    pub version: String,
+    pub devices: Vec<DeviceInfo>,
+}
+
+/// All platforms and devices the system exposes.
+///
+/// Indices into the returned vectors match `get_platforms()` and
+/// `Platform::get_devices(CL_DEVICE_TYPE_ALL)`. A platform without devices
+/// is listed with an empty device list instead of failing the whole call.
+pub fn enumerate() -> Result<Vec<PlatformInfo>> {
+    let mut out = Vec::new();
+    for platform in get_platforms()? {
+        let ids = platform.get_devices(CL_DEVICE_TYPE_ALL).unwrap_or_default();
+        let mut devices = Vec::with_capacity(ids.len());
+        for id in ids {
+            let device = Device::new(id);
+            devices.push(DeviceInfo {
+                id,
+                kind: DeviceKind::from_type(device.dev_type()?),
+                name: device.name()?,
+                compute_units: device.max_compute_units()?,
+                global_mem_bytes: device.global_mem_size()?,
+                unified_memory: device.host_unified_memory().unwrap_or(false),
+            });
+        }
+        out.push(PlatformInfo {
+            id: platform.id(),
+            name: platform.name()?,
+            vendor: platform.vendor()?,
+            version: platform.version()?,
+            devices,
+        });
+    }
+    Ok(out)
+}
+
+/// Print `enumerate()` to stdout, one line per platform and device
+pub fn print_platforms() -> Result<()> {
+    for (p, platform) in enumerate()?.iter().enumerate() {
+        println!("[{}] {} ({}, {})", p, platform.name, platform.vendor, platform.version);
+        for (d, dev) in platform.devices.iter().enumerate() {
+            println!(
+                "  [{}.{}] {:<11} {}  {} CUs, {} MiB",
+                p, d, dev.kind, dev.name, dev.compute_units, dev.global_mem_bytes >> 20
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+//! Library diagnostics: through the `log` crate with feature "log",
+//! otherwise on stderr
+
+// Makros statt Funktionen, damit `log` Modulpfad und Zeile des Aufrufers sieht
+
+macro_rules! diag_warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::warn!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        eprintln!($($arg)*);
+    }};
+}
+
+// ohne metrics/memtrace gibt es keine Aufrufer
+#[allow(unused_macros)]
+macro_rules! diag_error {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::error!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        eprintln!($($arg)*);
+    }};
+}
@@ -8,17 +8,86 @@ pub enum ClError {
     
     #[error("Buffer size mismatch: expected {expected}, got {actual}")]
     BufferSizeMismatch { expected: usize, actual: usize },
+
+    #[error("Buffer of {bytes} bytes is not a whole number of {elem}-byte elements")]
+    SizeNotAligned { bytes: usize, elem: usize },
     
     #[error("Invalid state transition")]
     InvalidState,
     
     #[error("Memory allocation failed: {0}")]
     AllocationFailed(String),
+
+    #[error("Buffer of {requested} bytes exceeds CL_DEVICE_MAX_MEM_ALLOC_SIZE ({max} bytes)")]
+    AllocTooLarge { requested: usize, max: u64 },
+
+    #[error("{op} not allowed on a buffer created with host access flags {flags:#x}")]
+    FlagViolation { op: &'static str, flags: u64 },
+
+    #[error("Cannot split a {len}-byte buffer at {offset}: both halves must be non-empty")]
+    SplitOutOfRange { offset: usize, len: usize },
+
+    #[error("Mapped pointer is not aligned to {align} bytes")]
+    Misaligned { align: usize },
+
+    #[error("Timed out after {0:?}")]
+    Timeout(std::time::Duration),
+
+    #[error("Command queue was created without CL_QUEUE_PROFILING_ENABLE")]
+    ProfilingDisabled,
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Kernel arg {index}: kernel declares `{declared}`, got `{given}`")]
+    ArgTypeMismatch { index: u32, declared: String, given: String },
+
+    #[error("Kernel arg {index}: kernel expects a {expected}-byte scalar, got {given} bytes")]
+    ArgSizeMismatch { index: u32, expected: usize, given: usize },
+
+    #[error("Invalid rect transfer: {0}")]
+    InvalidRect(String),
+
+    #[error("Kernel arg {0} is not set")]
+    ArgUnset(u32),
+
+    #[error("Kernel arg {0} is an empty buffer")]
+    EmptyBufferArg(u32),
+
+    #[error("{count} elements exceed the 32-bit element index of the kernel")]
+    TooManyElements { count: usize },
+
+    #[error("Invalid work size: {0}")]
+    InvalidWorkSize(String),
+
+    #[error("None of the {candidates} local sizes fits the kernel and the global size")]
+    NoValidLocalSize { candidates: usize },
+
+    #[error("Program build failed: {0}")]
+    BuildFailed(String),
+
+    #[error("Kernel `{name}` not found in program (available: {available})")]
+    KernelNotFound { name: String, available: String },
+
+    #[error("Trace parse error at line {line}: {reason}")]
+    TraceParse { line: usize, reason: String },
 }
 
 /// Result type alias for HPC-Core operations
 pub type Result<T> = std::result::Result<T, ClError>;
 
+// Für `.map_err(counted("op"))?`: zählt den Fehler unter `op` in
+// `metrics::FAILURES` (Feature "metrics") und wandelt ihn in ClError
+pub(crate) fn counted<E: Into<ClError>>(op: &'static str) -> impl FnOnce(E) -> ClError {
+    move |e| {
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_failure(op);
+        #[cfg(not(feature = "metrics"))]
+        let _ = op;
+        e.into()
+    }
+}
+
 /// Macro for checking OpenCL error codes
 #[macro_export]
 macro_rules! cl_try {
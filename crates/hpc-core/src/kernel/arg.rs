@@ -0,0 +1,75 @@
+//! Typed kernel argument handles
+
+use opencl3::{memory::ClMem, types::cl_mem};
+use bytemuck::Pod;
+use std::marker::PhantomData;
+
+use crate::buffer::{GpuBuffer, InFlight, Ready};
+
+/// Element types with a known OpenCL C name
+pub trait ClType: Pod {
+    /// Type name as reported by `CL_KERNEL_ARG_TYPE_NAME`
+    const CL_NAME: &'static str;
+}
+
+macro_rules! cl_type {
+    ($($t:ty => $name:literal),* $(,)?) => {
+        $(impl ClType for $t { const CL_NAME: &'static str = $name; })*
+    };
+}
+
+cl_type! {
+    i8 => "char", u8 => "uchar",
+    i16 => "short", u16 => "ushort",
+    i32 => "int", u32 => "uint",
+    i64 => "long", u64 => "ulong",
+    f32 => "float", f64 => "double",
+}
+
+#[cfg(feature = "half")]
+cl_type! { half::f16 => "half" }
+
+/// Buffer handle tagged with its element type, see `GpuBuffer::as_arg`.
+/// `SafeKernel::arg_buffer` keeps the borrow `'a` of the buffer alive for
+/// as long as the kernel is used.
+#[derive(Clone, Copy, Debug)]
+pub struct KernelArg<'a, T: ClType> {
+    mem: cl_mem,
+    bytes: usize,
+    _buf: PhantomData<(&'a (), T)>,
+}
+
+impl<T: ClType> KernelArg<'_, T> {
+    /// Raw buffer handle
+    pub fn mem(&self) -> cl_mem {
+        self.mem
+    }
+
+    /// Buffer size in bytes
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl GpuBuffer<Ready> {
+    /// Kernel argument handle viewing this buffer as `[T]`
+    pub fn as_arg<T: ClType>(&self) -> KernelArg<'_, T> {
+        KernelArg { mem: self.raw().get(), bytes: self.len(), _buf: PhantomData }
+    }
+}
+
+impl GpuBuffer<InFlight> {
+    /// Kernel argument handle for a buffer whose operation is still pending.
+    ///
+    /// # Safety
+    ///
+    /// The kernel must not start before that operation has completed:
+    /// launch it with `SafeKernel::launch_with_deps` on the operation's
+    /// event, or on the in-order queue the operation was enqueued on.
+    /// Otherwise the kernel races with the pending transfer.
+    /// `SafeKernel::launch_after` binds the buffer itself and needs no
+    /// `unsafe`.
+    pub unsafe fn as_arg<T: ClType>(&self) -> KernelArg<'_, T> {
+        KernelArg { mem: self.raw().get(), bytes: self.len(), _buf: PhantomData }
+    }
+}
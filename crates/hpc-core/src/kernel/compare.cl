@@ -0,0 +1,14 @@
+// Zählt Elemente mit |a - b| > tol * max(1, |b|) (b = Referenz), wie hpc_core::verify.
+// NaN gilt als Abweichung. Ein Work-Item je Element; Elemente ab n werden ignoriert.
+__kernel void count_mismatch_f32(__global const float* a,
+                                 __global const float* b,
+                                 __global uint* count,
+                                 const float tol,
+                                 const uint n)
+{
+    const uint i = get_global_id(0);
+    if (i >= n) return;
+
+    const float diff = fabs(a[i] - b[i]);
+    if (!(diff <= tol * fmax(1.0f, fabs(b[i])))) atomic_inc(count);
+}
@@ -0,0 +1,83 @@
+//! Element-wise comparison of two `f32` buffers on the device
+
+use opencl3::{command_queue::CommandQueue, context::Context, program::Program};
+
+use super::SafeKernel;
+use crate::buffer::{GpuBuffer, Ready};
+use crate::error::{ClError, Result};
+use crate::program::build_from_source;
+
+const SOURCE: &str = include_str!("compare.cl");
+
+/// Mismatch counter for `f32` buffers with the program built once.
+///
+/// One kernel launch compares all elements and counts the mismatches with
+/// an atomic, so only 4 bytes travel back to the host instead of both
+/// buffers. Tolerance semantics match `verify::first_mismatch`.
+pub struct BufferCompare {
+    // der Kernel selbst entsteht je Lauf: er leiht die Puffer dieses Laufs
+    program: Program,
+}
+
+impl BufferCompare {
+    pub fn new(ctx: &Context) -> Result<Self> {
+        let program = build_from_source(ctx, SOURCE, "-cl-kernel-arg-info")?;
+        Ok(BufferCompare { program })
+    }
+
+    /// Number of elements of `a` differing from the reference `b` by more
+    /// than `tol * max(1, |b|)` (NaN always differs); blocking. Buffers of
+    /// different size are a `BufferSizeMismatch`, lengths that are not a
+    /// multiple of 4 bytes `SizeNotAligned`. The kernel indexes and counts
+    /// in 32 bits, so more than `u32::MAX` elements are `TooManyElements`.
+    pub fn run(
+        &mut self,
+        ctx: &Context,
+        queue: &CommandQueue,
+        a: &GpuBuffer<Ready>,
+        b: &GpuBuffer<Ready>,
+        tol: f32,
+    ) -> Result<u64> {
+        if a.len() != b.len() {
+            return Err(ClError::BufferSizeMismatch { expected: b.len(), actual: a.len() });
+        }
+        let n = crate::buffer::elem_count::<f32>(a.len())?;
+        if n == 0 {
+            return Ok(0);
+        }
+        // Zähler und Index sind uint: mit n <= u32::MAX läuft keiner über
+        let n32 = u32::try_from(n).map_err(|_| ClError::TooManyElements { count: n })?;
+
+        let count = GpuBuffer::from_slice(ctx, queue, &[0u32])?;
+        let mut kernel = SafeKernel::new(&self.program, "count_mismatch_f32")?;
+        kernel.arg_buffer(0, a.as_arg::<f32>())?;
+        kernel.arg_buffer(1, b.as_arg::<f32>())?;
+        kernel.arg_buffer(2, count.as_arg::<u32>())?;
+        kernel.arg_scalar(3, tol)?;
+        kernel.arg_scalar(4, n32)?;
+        kernel.launch(queue, &[n], None)?.wait()?;
+
+        Ok(count.read_to_vec::<u32>(queue)?[0] as u64)
+    }
+}
+
+impl GpuBuffer<Ready> {
+    /// Mismatching `f32` elements between this buffer and the reference
+    /// `other`, counted on the device (see `BufferCompare::run`). Builds the
+    /// comparison program on every call; keep a `BufferCompare` around when
+    /// comparing repeatedly.
+    pub fn mismatches(
+        &self,
+        other: &GpuBuffer<Ready>,
+        ctx: &Context,
+        queue: &CommandQueue,
+        tol: f32,
+    ) -> Result<u64> {
+        BufferCompare::new(ctx)?.run(ctx, queue, self, other, tol)
+    }
+
+    /// `mismatches(..) == 0`: both buffers hold the same `f32` values within `tol`
+    pub fn equals(&self, other: &GpuBuffer<Ready>, ctx: &Context, queue: &CommandQueue, tol: f32) -> Result<bool> {
+        Ok(self.mismatches(other, ctx, queue, tol)? == 0)
+    }
+}
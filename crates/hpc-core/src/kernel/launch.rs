@@ -0,0 +1,99 @@
+//! Kernel launches, optionally chained after a pending buffer operation
+
+use opencl3::{command_queue::CommandQueue, event::Event, memory::ClMem, types::cl_event};
+use std::ptr;
+
+use super::{ClType, SafeKernel};
+use crate::buffer::{GpuBuffer, GpuEventGuard, InFlight};
+use crate::error::{counted, Result};
+
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+impl SafeKernel<'_> {
+    /// Validate (see `validate`) and enqueue the kernel; the returned guard
+    /// waits for it on drop
+    pub fn launch(
+        &self,
+        queue: &CommandQueue,
+        global: &[usize],
+        local: Option<&[usize]>,
+    ) -> Result<GpuEventGuard> {
+        self.enqueue(queue, global, local, &[])
+    }
+
+    /// Launch once all of `deps` have completed (e.g. a marker from
+    /// `enqueue_marker`), without blocking the host
+    pub fn launch_with_deps(
+        &self,
+        queue: &CommandQueue,
+        global: &[usize],
+        local: Option<&[usize]>,
+        deps: &[&Event],
+    ) -> Result<GpuEventGuard> {
+        let wait: Vec<cl_event> = deps.iter().map(|e| e.get()).collect();
+        self.enqueue(queue, global, local, &wait)
+    }
+
+    /// Bind `buf` to `__global T*` argument `index` and launch after its
+    /// pending operation, without a host round-trip.
+    ///
+    /// The operation behind `guard` becomes a wait-list dependency of the
+    /// kernel, so the returned guard covers both. `buf` stays `InFlight`
+    /// until that guard is awaited, e.g. via `into_ready`. The binding is
+    /// dropped again after the launch: the kernel does not keep the handle
+    /// of a buffer it gives back, so the next launch needs a new one.
+    pub fn launch_after<T: ClType>(
+        &mut self,
+        queue: &CommandQueue,
+        global: &[usize],
+        local: Option<&[usize]>,
+        index: u32,
+        buf: GpuBuffer<InFlight>,
+        guard: GpuEventGuard,
+    ) -> Result<(GpuBuffer<InFlight>, GpuEventGuard)> {
+        self.bind_buffer::<T>(index, buf.raw().get(), buf.len())?;
+        let dep = guard.into_event();
+        let launched = self.enqueue(queue, global, local, &[dep.get()]);
+        self.args[index as usize] = None;
+        match launched {
+            Ok(g) => Ok((buf, g)),
+            Err(e) => {
+                // nichts eingereiht: wie beim Drop des Guards auf die Abhängigkeit warten
+                let _ = dep.wait();
+                Err(e)
+            }
+        }
+    }
+
+    fn enqueue(
+        &self,
+        queue: &CommandQueue,
+        global: &[usize],
+        local: Option<&[usize]>,
+        wait: &[cl_event],
+    ) -> Result<GpuEventGuard> {
+        self.validate(global, local)?;
+
+        #[cfg(feature = "metrics")]
+        let t = Instant::now();
+
+        let evt = queue.enqueue_nd_range_kernel(
+            self.kernel.get(),
+            global.len() as u32,
+            ptr::null(),
+            global.as_ptr(),
+            local.map_or(ptr::null(), |l| l.as_ptr()),
+            wait,
+        ).map_err(counted("kernel_launch"))?;
+
+        #[cfg(feature = "memtrace")]
+        crate::trace_on_complete(&evt, crate::memtracer::Dir::Kernel, 0);
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record("kernel_launch", t);
+
+        self.mark_launched();
+        Ok(GpuEventGuard::new(evt))
+    }
+}
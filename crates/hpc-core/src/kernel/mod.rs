@@ -0,0 +1,270 @@
+//! Kernel wrapper with typed, checked argument binding
+
+mod arg;
+mod compare;
+mod launch;
+mod reduce;
+mod tune;
+mod validate;
+
+pub use arg::{ClType, KernelArg};
+pub use compare::BufferCompare;
+pub use reduce::{ReduceSum, reduce_sum_f32};
+pub use tune::autotune;
+
+use opencl3::{
+    kernel::Kernel,
+    program::Program,
+    types::{cl_device_id, cl_mem},
+};
+use bytemuck::Pod;
+use std::{cell::Cell, marker::PhantomData};
+use crate::error::{ClError, Result};
+
+/// What was bound to a kernel argument slot
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgBinding {
+    /// `__global` buffer: handle and size in bytes
+    Buffer { mem: cl_mem, bytes: usize },
+    /// By-value scalar of `size` bytes
+    Scalar { size: usize },
+    /// `__local` scratch of `bytes` per work-group
+    Local { bytes: usize },
+}
+
+/// Kernel with per-argument bookkeeping.
+///
+/// Declared argument type names are read once via `clGetKernelArgInfo`.
+/// Many drivers only report them when the program was built with
+/// `-cl-kernel-arg-info`; without it the type check is skipped, and scalar
+/// sizes are only checked where `declare_scalar_size` was called.
+///
+/// Buffer arguments borrow their buffers for `'a`, so a bound buffer can
+/// neither be dropped nor moved while the kernel is still used:
+///
+/// ```compile_fail,E0505
+/// # use hpc_core::{GpuBuffer, Ready, SafeKernel};
+/// # use opencl3::{command_queue::CommandQueue, program::Program};
+/// fn run(queue: &CommandQueue, program: &Program, buf: GpuBuffer<Ready>) -> hpc_core::Result<()> {
+///     let mut kernel = SafeKernel::new(program, "scale")?;
+///     kernel.arg_buffer(0, buf.as_arg::<f32>())?;
+///     drop(buf);
+///     kernel.launch(queue, &[16], None)?.wait()?;
+///     Ok(())
+/// }
+/// ```
+pub struct SafeKernel<'a> {
+    kernel: Kernel,
+    arg_types: Vec<Option<String>>,
+    /// Scalar sizes given by the user for kernels without arg info
+    scalar_sizes: Vec<Option<usize>>,
+    args: Vec<Option<ArgBinding>>,
+    /// How often each argument was set since the last launch
+    sets_since_launch: Vec<Cell<u32>>,
+    /// `CL_KERNEL_WORK_GROUP_SIZE` on the program's first device
+    max_work_group: Option<usize>,
+    // gebundene Puffer müssen den Kernel überleben
+    _bufs: PhantomData<&'a ()>,
+}
+
+impl<'a> SafeKernel<'a> {
+    /// Create kernel `name` from a built program
+    pub fn new(program: &Program, name: &str) -> Result<Self> {
+        let mut k = Self::from_kernel(Kernel::create(program, name)?)?;
+        if let Some(&device) = program.get_devices()?.first() {
+            k.max_work_group = k.kernel.get_work_group_size(device as cl_device_id).ok();
+        }
+        Ok(k)
+    }
+
+    /// Wrap an existing kernel (the device is unknown, so `validate`
+    /// skips the work-group size limit)
+    pub fn from_kernel(kernel: Kernel) -> Result<Self> {
+        let n = kernel.num_args()?;
+        let arg_types = (0..n).map(|i| kernel.get_arg_type_name(i).ok()).collect();
+        Ok(SafeKernel {
+            kernel,
+            arg_types,
+            scalar_sizes: vec![None; n as usize],
+            args: vec![None; n as usize],
+            sets_since_launch: vec![Cell::new(0); n as usize],
+            max_work_group: None,
+            _bufs: PhantomData,
+        })
+    }
+
+    /// Bind a typed buffer to `__global T*` argument `index`.
+    ///
+    /// Fails with `ArgTypeMismatch` if the kernel declares a different
+    /// element type (or a non-pointer), and with `SizeNotAligned` if the
+    /// buffer is not a whole number of `T`. The buffer stays borrowed for
+    /// as long as the kernel is used.
+    pub fn arg_buffer<T: ClType>(&mut self, index: u32, arg: KernelArg<'a, T>) -> Result<()> {
+        self.bind_buffer::<T>(index, arg.mem(), arg.bytes())
+    }
+
+    /// Bind a by-value scalar to argument `index`.
+    ///
+    /// Fails with `ArgSizeMismatch` if `T` does not have the size of the
+    /// declared type (e.g. a `u64` for an `int`), taken from the arg info or
+    /// from `declare_scalar_size`; unknown type names (structs, `size_t`)
+    /// are not checked. A pointer argument is an `ArgTypeMismatch`.
+    pub fn arg_scalar<T: Pod>(&mut self, index: u32, value: T) -> Result<()> {
+        let given = std::mem::size_of::<T>();
+        let expected = match self.declared(index)? {
+            Some(declared) if declared.ends_with('*') => {
+                return Err(ClError::ArgTypeMismatch {
+                    index,
+                    declared: declared.to_string(),
+                    given: format!("{}-byte scalar", given),
+                });
+            }
+            Some(declared) => scalar_size(declared),
+            None => self.scalar_sizes[index as usize],
+        };
+        if let Some(expected) = expected.filter(|&e| e != given) {
+            return Err(ClError::ArgSizeMismatch { index, expected, given });
+        }
+
+        self.kernel.set_arg(index, &value)?;
+        self.bind(index, ArgBinding::Scalar { size: std::mem::size_of::<T>() });
+        Ok(())
+    }
+
+    /// Reserve `bytes` of `__local` memory per work-group for argument `index`
+    pub fn arg_local(&mut self, index: u32, bytes: usize) -> Result<()> {
+        self.declared(index)?;
+        self.kernel.set_arg_local_buffer(index, bytes)?;
+        self.bind(index, ArgBinding::Local { bytes });
+        Ok(())
+    }
+
+    /// Expected size in bytes of scalar argument `index`, for kernels whose
+    /// driver reports no arg info; checked by later `arg_scalar` calls
+    pub fn declare_scalar_size(&mut self, index: u32, bytes: usize) -> Result<()> {
+        self.declared(index)?;
+        self.scalar_sizes[index as usize] = Some(bytes);
+        Ok(())
+    }
+
+    /// Binding of argument `index`, `None` if not set yet
+    pub fn binding(&self, index: u32) -> Option<ArgBinding> {
+        self.args.get(index as usize).copied().flatten()
+    }
+
+    /// Indices without a binding, ascending
+    pub fn unset_args(&self) -> Vec<u32> {
+        (0..self.num_args()).filter(|&i| self.binding(i).is_none()).collect()
+    }
+
+    /// Indices set more than once since the last launch, ascending. Usually
+    /// harmless rebinding, but next to an unset index the typical
+    /// copy-paste bug: `arg_buffer(0, ..)` twice instead of 0 and 1.
+    pub fn args_set_twice(&self) -> Vec<u32> {
+        (0..self.num_args()).filter(|&i| self.sets_since_launch[i as usize].get() > 1).collect()
+    }
+
+    /// Number of kernel arguments
+    pub fn num_args(&self) -> u32 {
+        self.args.len() as u32
+    }
+
+    /// Underlying OpenCL kernel
+    pub fn kernel(&self) -> &Kernel {
+        &self.kernel
+    }
+
+    /// One line per argument: index, kind of binding, size or buffer
+    /// handle, plus declared name and type where the driver reports them.
+    /// For debugging kernels that read the wrong (or no) buffer.
+    pub fn dump_args(&self) -> String {
+        use std::fmt::Write;
+
+        let name = self.kernel.function_name().unwrap_or_else(|_| "?".into());
+        let mut out = format!("kernel {}: {} args\n", name, self.args.len());
+        for (i, binding) in self.args.iter().enumerate() {
+            let bound = match binding {
+                Some(ArgBinding::Buffer { mem, bytes }) => format!("buffer  {:p} {} B", *mem, bytes),
+                Some(ArgBinding::Scalar { size }) => format!("scalar  {} B", size),
+                Some(ArgBinding::Local { bytes }) => format!("local   {} B", bytes),
+                None => "<unset>".into(),
+            };
+            let arg_name = self.kernel.get_arg_name(i as u32).ok();
+            let declared = self.arg_types[i].as_deref();
+            let _ = write!(out, "  [{}] ", i);
+            if arg_name.is_some() || declared.is_some() {
+                let _ = writeln!(
+                    out,
+                    "{:<28} ({} {})",
+                    bound,
+                    declared.unwrap_or("?"),
+                    arg_name.as_deref().unwrap_or("?")
+                );
+            } else {
+                let _ = writeln!(out, "{}", bound);
+            }
+        }
+        out
+    }
+
+    // Typprüfung und Setzen ohne Borrow; launch_after löst die Bindung selbst wieder
+    fn bind_buffer<T: ClType>(&mut self, index: u32, mem: cl_mem, bytes: usize) -> Result<()> {
+        if let Some(declared) = self.declared(index)? {
+            let elem = declared.strip_suffix('*').map(str::trim);
+            if elem != Some(T::CL_NAME) {
+                return Err(ClError::ArgTypeMismatch {
+                    index,
+                    declared: declared.to_string(),
+                    given: format!("{}*", T::CL_NAME),
+                });
+            }
+        }
+        crate::buffer::elem_count::<T>(bytes)?;
+
+        self.kernel.set_arg(index, &mem)?;
+        self.bind(index, ArgBinding::Buffer { mem, bytes });
+        Ok(())
+    }
+
+    fn bind(&mut self, index: u32, binding: ArgBinding) {
+        self.args[index as usize] = Some(binding);
+        let n = &self.sets_since_launch[index as usize];
+        n.set(n.get() + 1);
+    }
+
+    // nach einem Launch zählt jedes Setzen wieder als erstes
+    fn mark_launched(&self) {
+        self.sets_since_launch.iter().for_each(|n| n.set(0));
+    }
+
+    // Declared type name of `index` (None = driver gives no arg info)
+    fn declared(&self, index: u32) -> Result<Option<&str>> {
+        match self.arg_types.get(index as usize) {
+            Some(t) => Ok(t.as_deref()),
+            // CL_INVALID_ARG_INDEX
+            None => Err(ClError::Api(-49)),
+        }
+    }
+}
+
+// Size of an OpenCL C scalar or vector type name ("int", "float4"),
+// None for names we do not know (structs, typedefs, size_t)
+fn scalar_size(name: &str) -> Option<usize> {
+    let name = name.trim();
+    let base = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    let elem = match base {
+        "char" | "uchar" => 1,
+        "short" | "ushort" | "half" => 2,
+        "int" | "uint" | "float" => 4,
+        "long" | "ulong" | "double" => 8,
+        _ => return None,
+    };
+    let lanes = match &name[base.len()..] {
+        "" => 1,
+        // 3er-Vektoren belegen Speicher wie 4er
+        "3" => 4,
+        "2" | "4" | "8" | "16" => name[base.len()..].parse().ok()?,
+        _ => return None,
+    };
+    Some(elem * lanes)
+}
@@ -0,0 +1,24 @@
+// Baumreduktion: jede Work-Group summiert 2 * local_size Elemente nach out[group].
+// local_size muss eine Zweierpotenz sein; Elemente ab n zählen als 0 (letzte Gruppe).
+__kernel void reduce_sum_f32(__global const float* in,
+                             __global float* out,
+                             __local float* scratch,
+                             const uint n)
+{
+    const uint lid = get_local_id(0);
+    const uint lsz = get_local_size(0);
+    const uint i = get_group_id(0) * lsz * 2 + lid;
+
+    float acc = 0.0f;
+    if (i < n)       acc  = in[i];
+    if (i + lsz < n) acc += in[i + lsz];
+    scratch[lid] = acc;
+    barrier(CLK_LOCAL_MEM_FENCE);
+
+    for (uint s = lsz / 2; s > 0; s >>= 1) {
+        if (lid < s) scratch[lid] += scratch[lid + s];
+        barrier(CLK_LOCAL_MEM_FENCE);
+    }
+
+    if (lid == 0) out[get_group_id(0)] = scratch[0];
+}
@@ -0,0 +1,85 @@
+//! Multi-pass `f32` sum reduction on top of `SafeKernel`
+
+use opencl3::{command_queue::CommandQueue, context::Context, program::Program};
+
+use super::SafeKernel;
+use crate::buffer::{GpuBuffer, Ready};
+use crate::error::{ClError, Result};
+use crate::program::build_from_source;
+
+const SOURCE: &str = include_str!("reduce.cl");
+
+/// Upper bound for the work-group size (and the `__local` scratch per group)
+const MAX_LOCAL: usize = 256;
+
+/// Sum reduction of `f32` buffers with the program built once.
+///
+/// Each pass folds `2 * local` elements per work-group into one partial sum,
+/// and passes repeat until a single value is left. Every pass is a regular
+/// `SafeKernel::launch`, so it shows up in memtrace and metrics like any
+/// other kernel.
+pub struct ReduceSum {
+    // der Kernel selbst entsteht je Lauf: er leiht die Puffer dieses Laufs
+    program: Program,
+}
+
+impl ReduceSum {
+    pub fn new(ctx: &Context) -> Result<Self> {
+        let program = build_from_source(ctx, SOURCE, "-cl-kernel-arg-info")?;
+        Ok(ReduceSum { program })
+    }
+
+    /// Sum of all `f32` in `buf` (blocking). An empty buffer sums to 0;
+    /// a length that is not a multiple of 4 is `SizeNotAligned`. The kernel
+    /// indexes in 32 bits, so larger buffers are `TooManyElements`.
+    pub fn run(&mut self, ctx: &Context, queue: &CommandQueue, buf: &GpuBuffer<Ready>) -> Result<f32> {
+        let mut n = buf.len() / std::mem::size_of::<f32>();
+        if buf.is_empty() {
+            return Ok(0.0);
+        }
+
+        let mut kernel = SafeKernel::new(&self.program, "reduce_sum_f32")?;
+        let max_wg = kernel.kernel().get_work_group_size(queue.device()?)?;
+        // Baumreduktion braucht eine Zweierpotenz
+        let local = prev_power_of_two(max_wg.min(MAX_LOCAL));
+        let groups = |n: usize| n.div_ceil(2 * local);
+        // der Index läuft bis ans Ende der letzten Gruppe, nicht nur bis n
+        u32::try_from(groups(n) * 2 * local).map_err(|_| ClError::TooManyElements { count: n })?;
+
+        // Ping-Pong-Puffer für die Teilsummen; der erste Durchlauf erzeugt die meisten
+        let partial = vec![0f32; groups(n).max(1)];
+        let ping = [
+            GpuBuffer::from_slice(ctx, queue, &partial)?,
+            GpuBuffer::from_slice(ctx, queue, &partial)?,
+        ];
+
+        kernel.arg_local(2, local * std::mem::size_of::<f32>())?;
+        let mut pass = 0;
+        loop {
+            let input = if pass == 0 { buf } else { &ping[(pass + 1) % 2] };
+            let output = &ping[pass % 2];
+            let g = groups(n);
+
+            kernel.arg_buffer(0, input.as_arg::<f32>())?;
+            kernel.arg_buffer(1, output.as_arg::<f32>())?;
+            kernel.arg_scalar(3, u32::try_from(n).map_err(|_| ClError::TooManyElements { count: n })?)?;
+            kernel.launch(queue, &[g * local], Some(&[local]))?.wait()?;
+
+            n = g;
+            if n == 1 {
+                return Ok(output.read_to_vec::<f32>(queue)?[0]);
+            }
+            pass += 1;
+        }
+    }
+}
+
+/// One-shot `ReduceSum`: builds the reduction program on every call, so keep
+/// a `ReduceSum` around when summing repeatedly
+pub fn reduce_sum_f32(ctx: &Context, queue: &CommandQueue, buf: &GpuBuffer<Ready>) -> Result<f32> {
+    ReduceSum::new(ctx)?.run(ctx, queue, buf)
+}
+
+fn prev_power_of_two(x: usize) -> usize {
+    if x == 0 { 1 } else { 1 << (usize::BITS - 1 - x.leading_zeros()) }
+}
@@ -0,0 +1,71 @@
+//! Local work-group size autotuning
+
+use opencl3::command_queue::CommandQueue;
+use std::ptr;
+
+use super::SafeKernel;
+use crate::error::{counted, ClError, Result};
+
+/// Launches per candidate; the device time is averaged over all of them
+const TRIALS: u32 = 3;
+
+/// Launch `kernel` with each local size in `candidates` and return the fastest.
+///
+/// Device time comes from event profiling, so `queue` must be created with
+/// `CL_QUEUE_PROFILING_ENABLE` (`ProfilingDisabled` otherwise). Candidates with a zero entry, a product above
+/// `CL_KERNEL_WORK_GROUP_SIZE`, or not dividing `global` are skipped; if none
+/// is left the result is `NoValidLocalSize`. Every trial is recorded as
+/// metric `autotune` and as a kernel record in memtrace with its device
+/// time. Kernel arguments must be set beforehand and are checked like for
+/// `SafeKernel::launch` (`ArgUnset`, ...).
+pub fn autotune(
+    queue: &CommandQueue,
+    kernel: &SafeKernel<'_>,
+    global: [usize; 3],
+    candidates: &[[usize; 3]],
+) -> Result<[usize; 3]> {
+    crate::queue::require_profiling(queue)?;
+    kernel.validate(&global, None)?;
+    let max_wg = kernel.kernel().get_work_group_size(queue.device()?)?;
+
+    let mut best: Option<([usize; 3], u64)> = None;
+    for local in candidates {
+        let valid = local.iter().all(|&l| l > 0)
+            && local.iter().product::<usize>() <= max_wg
+            && global.iter().zip(local).all(|(g, l)| g % l == 0);
+        if !valid {
+            continue;
+        }
+
+        let mut total_ns = 0u64;
+        for _ in 0..TRIALS {
+            #[cfg(feature = "memtrace")]
+            let token = crate::memtracer::is_auto_trace_enabled()
+                .then(|| crate::memtracer::start(crate::memtracer::Dir::Kernel, 0));
+
+            let event = queue.enqueue_nd_range_kernel(
+                kernel.kernel().get(), 3,
+                ptr::null(), global.as_ptr(),
+                local.as_ptr(), &[],
+            ).map_err(counted("kernel_launch"))?;
+            kernel.mark_launched();
+            event.wait()?;
+            let ns = event.profiling_command_end()? - event.profiling_command_start()?;
+            total_ns += ns;
+
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_duration("autotune", (ns / 1000) as u128);
+            #[cfg(feature = "memtrace")]
+            if let Some(token) = token {
+                token.finish_with_event(&event);
+            }
+        }
+
+        let mean_ns = total_ns / TRIALS as u64;
+        if best.is_none_or(|(_, b)| mean_ns < b) {
+            best = Some((*local, mean_ns));
+        }
+    }
+
+    best.map(|(local, _)| local).ok_or(ClError::NoValidLocalSize { candidates: candidates.len() })
+}
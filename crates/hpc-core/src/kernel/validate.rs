@@ -0,0 +1,67 @@
+//! Launch validation without enqueueing ("dry run")
+
+use super::{ArgBinding, SafeKernel};
+use crate::error::{ClError, Result};
+
+impl SafeKernel<'_> {
+    /// Check that a launch with `global`/`local` would be well-formed,
+    /// returning the first problem found:
+    ///
+    /// - every argument is bound (`ArgUnset` with the lowest unset index;
+    ///   if other arguments were set twice since the last launch, a hint
+    ///   naming them is logged as a warning)
+    /// - 1..=3 dimensions, no zero sizes, `local` has the same rank, divides
+    ///   `global` and stays within `CL_KERNEL_WORK_GROUP_SIZE` (`InvalidWorkSize`)
+    /// - no buffer argument is empty (`EmptyBufferArg` with its index)
+    ///
+    /// Nothing is enqueued.
+    pub fn validate(&self, global: &[usize], local: Option<&[usize]>) -> Result<()> {
+        for (i, binding) in self.args.iter().enumerate() {
+            match binding {
+                None => {
+                    let twice = self.args_set_twice();
+                    if !twice.is_empty() {
+                        diag_warn!(
+                            "SafeKernel: args {:?} unset, args {:?} set twice since last launch (wrong index?)",
+                            self.unset_args(), twice
+                        );
+                    }
+                    return Err(ClError::ArgUnset(i as u32));
+                }
+                Some(ArgBinding::Buffer { bytes: 0, .. }) => return Err(ClError::EmptyBufferArg(i as u32)),
+                Some(_) => {}
+            }
+        }
+
+        if global.is_empty() || global.len() > 3 {
+            return Err(ClError::InvalidWorkSize(format!("{} dimensions, expected 1..=3", global.len())));
+        }
+        if global.contains(&0) {
+            return Err(ClError::InvalidWorkSize(format!("zero global size in {:?}", global)));
+        }
+
+        if let Some(local) = local {
+            if local.len() != global.len() {
+                return Err(ClError::InvalidWorkSize(format!(
+                    "local {:?} and global {:?} differ in rank", local, global
+                )));
+            }
+            if local.contains(&0) {
+                return Err(ClError::InvalidWorkSize(format!("zero local size in {:?}", local)));
+            }
+            if let Some((g, l)) = global.iter().zip(local).find(|(g, l)| *g % *l != 0) {
+                return Err(ClError::InvalidWorkSize(format!(
+                    "global size {} is not a multiple of local size {}", g, l
+                )));
+            }
+            let wg: usize = local.iter().product();
+            if let Some(max) = self.max_work_group.filter(|&max| wg > max) {
+                return Err(ClError::InvalidWorkSize(format!(
+                    "work-group of {} items exceeds kernel limit {}", wg, max
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -3,30 +3,77 @@
 //! This crate provides safe wrappers and utilities for GPU computing.
 
 // Core modules (always available)
+#[macro_use]
+mod diag;
 mod error;
+mod backoff;
 mod buffer;
+mod device;
+mod kernel;
+mod profile;
+mod program;
+mod queue;
+mod units;
+pub mod memory;
+pub mod stm;
+pub mod verify;
 
 // Re-export core types
 pub use error::{ClError, Result};
-pub use buffer::{GpuBuffer, GpuEventGuard};
-pub use buffer::state::{State, Queued, InFlight, Ready};
+pub use backoff::{BackoffPolicy, retry};
+pub use buffer::{GpuBuffer, GpuEventGuard, HostPtrBuffer, RawMemGuard, ReadGuard, WriteGuard, Rect, SubBuffer, overlap_bytes, elem_count, into_ready_all};
+#[cfg(feature = "buffer_registry")]
+pub use buffer::assert_all_ready;
+pub use kernel::{SafeKernel, KernelArg, ClType, ArgBinding, BufferCompare, ReduceSum, autotune, reduce_sum_f32};
+pub use device::{DeviceInfo, DeviceKind, PlatformInfo, enumerate, print_platforms};
+pub use program::{ProgramCache, ProgramModule, program_hash};
+pub use queue::{enqueue_marker, queue_has_profiling};
+pub use profile::{transfer_profile, LinkFit, TransferProfile, TransferSample};
+pub use memory::{PinnedBuffer, StagingBuffer};
+pub use stm::{AbortCounters, CounterSnapshot, StmConfig, StmResult, run_stm};
+pub use units::{Unit, set_unit, current_unit, format_bytes, format_rate};
+pub use buffer::state::{State, Transition, Queued, InFlight, Ready, Mapped, MappedRead};
+#[cfg(feature = "gl-interop")]
+pub use buffer::state::GlReleased;
+#[cfg(feature = "gl-interop")]
+pub use buffer::GlBuffer;
 
 // Feature-gated modules
 #[cfg(feature = "metrics")]
 pub mod metrics;
 #[cfg(feature = "metrics")]
-pub use metrics::{record, summary, ALLOCS, ALLOC_BYTES};
+pub use metrics::{
+    record, record_bytes, summary, summary_by, summary_to, summary_to_by, SortBy, MetricsScope, ALLOCS, ALLOC_BYTES,
+    FAILURES, record_failure, failures,
+};
 
 #[cfg(feature = "memtrace")]
 pub mod memtracer;
+
+#[cfg(any(feature = "metrics", feature = "memtrace"))]
+pub mod report;
 #[cfg(feature = "memtrace")]
 pub use memtracer::{
-    start, flush_csv, reset,
+    start, flush_csv, flush_csv_to, flush_csv_append, flush_csv_append_to, flush_and_rotate, flush_all, FlushConfig,
+    flush_transfers, flush_aborts, flush_summary,
+    reset, rebase_timebase, log_len, with_records, slowest_transfers, load_csv, set_csv_float_precision, summary_oneline,
+    set_idle_warn_threshold, idle_exceeds_threshold, GroupBy, set_abort_group_by, abort_group_by,
+    set_run_metadata, clear_run_metadata,
     Dir, Operation, CopyToken, TracingScope,
     is_auto_trace_enabled, enable_auto_trace, disable_auto_trace,
     AbortEvent, AbortTokenGuard, set_abort_token, clear_abort_token,
-    log_abort, log_transfer, now_us,
+    log_abort, log_transfer, log_record, trace_barrier, now_us,
+    trace_abort, trace_abort_between, trace_gave_up, trace_commit, commit_counts, CAUSE_BUDGET_EXHAUSTED, flush_conflict_graph, flush_dot,
+    roi_begin, roi_end, current_roi, RoiGuard, set_current_device, current_device, start_sampler, stop_sampler, set_abort_sink, clear_abort_sink,
 };
+#[cfg(all(feature = "memtrace", feature = "json"))]
+pub use memtracer::{flush_json, flush_chrome_trace};
+#[cfg(feature = "perfetto")]
+pub use memtracer::flush_perfetto;
+#[cfg(feature = "memtrace_full")]
+pub use memtracer::{set_abort_full_limit, abort_full_limit, AbortFullLimit};
+#[cfg(all(feature = "memtrace", feature = "test-util"))]
+pub use memtracer::{set_clock, reset_clock, Clock};
 
 // FFI callback for memtrace
 #[cfg(feature = "memtrace")]
@@ -34,11 +81,30 @@ use std::ffi::c_void;
 
 #[cfg(feature = "memtrace")]
 pub extern "C" fn memtrace_callback(
-    _evt: opencl3::types::cl_event,
+    evt: opencl3::types::cl_event,
     _status: opencl3::types::cl_int,
     user_data: *mut c_void,
 ) {
     // SAFETY: Pointer was obtained via Box::into_raw, so it is non-null and uniquely owned.
     let tok: Box<CopyToken> = unsafe { Box::from_raw(user_data.cast()) };
-    tok.finish();
-}
\ No newline at end of file
+    // Event gehört der Queue/dem Guard: nur borgen, nicht releasen
+    let evt = std::mem::ManuallyDrop::new(opencl3::event::Event::new(evt));
+    tok.finish_with_event(&evt);
+}
+
+/// Trace the command behind `evt` with auto-trace on: a token started now
+/// is finished with the device time by `memtrace_callback` once `evt`
+/// completes. Called right after the enqueue.
+#[cfg(feature = "memtrace")]
+pub(crate) fn trace_on_complete(evt: &opencl3::event::Event, dir: memtracer::Dir, bytes: usize) {
+    use opencl3::event::CL_COMPLETE;
+    if !memtracer::is_auto_trace_enabled() {
+        return;
+    }
+    let ptr = Box::into_raw(Box::new(memtracer::start(dir, bytes))).cast::<c_void>();
+    if let Err(e) = evt.set_callback(CL_COMPLETE, memtrace_callback, ptr) {
+        diag_error!("callback failed: {e}");
+        // SAFETY: der Callback wurde nicht registriert, die Box gehört noch uns
+        unsafe { Box::from_raw(ptr.cast::<CopyToken>()) }.finish();
+    }
+}
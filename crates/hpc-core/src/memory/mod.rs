@@ -0,0 +1,7 @@
+//! Host-side memory helpers for transfers
+
+mod pinned;
+mod staging;
+
+pub use pinned::PinnedBuffer;
+pub use staging::StagingBuffer;
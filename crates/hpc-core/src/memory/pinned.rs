@@ -0,0 +1,123 @@
+//! Page-aligned (pageable, not page-locked) host staging buffer, optionally placed on a NUMA node
+
+use std::{
+    alloc::{self, Layout},
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+use crate::error::{ClError, Result};
+
+const PAGE: usize = 4096;
+
+#[cfg(feature = "numa")]
+mod ffi {
+    use std::ffi::{c_int, c_void};
+
+    #[link(name = "numa")]
+    unsafe extern "C" {
+        pub fn numa_available() -> c_int;
+        pub fn numa_max_node() -> c_int;
+        pub fn numa_alloc_onnode(size: usize, node: c_int) -> *mut c_void;
+        pub fn numa_free(start: *mut c_void, size: usize);
+    }
+}
+
+enum Backing {
+    Heap(Layout),
+    #[cfg(feature = "numa")]
+    Numa,
+}
+
+/// Page-aligned, zero-initialised host buffer for H2D/D2H staging.
+///
+/// Despite the name the memory is ordinary pageable memory: it is neither
+/// `mlock`ed nor registered with the driver, so transfers from it may
+/// still go through the driver's own staging copy. Page alignment only
+/// lets `CL_MEM_USE_HOST_PTR` wrap it without a copy. Page-locked memory
+/// comes from the driver: a buffer created with `CL_MEM_ALLOC_HOST_PTR`
+/// and mapped (`enqueue_map_typed`).
+///
+/// Derefs to `[u8]`, so it is passed to `enqueue_write`/`enqueue_read` like
+/// any slice and metrics/memtrace see the same byte count as for a `Vec`.
+pub struct PinnedBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    backing: Backing,
+}
+
+// SAFETY: PinnedBuffer owns its allocation exclusively, like a Box<[u8]>.
+unsafe impl Send for PinnedBuffer {}
+unsafe impl Sync for PinnedBuffer {}
+
+impl PinnedBuffer {
+    /// Allocate `len` bytes on the default heap, page aligned (pageable)
+    pub fn new(len: usize) -> Result<Self> {
+        let layout = Layout::from_size_align(len.max(1), PAGE)
+            .map_err(|e| ClError::AllocationFailed(e.to_string()))?;
+        // SAFETY: layout has non-zero size.
+        let raw = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(raw)
+            .ok_or_else(|| ClError::AllocationFailed(format!("{} bytes host memory", len)))?;
+        Ok(Self { ptr, len, backing: Backing::Heap(layout) })
+    }
+
+    /// Allocate `len` bytes on NUMA node `node` via libnuma (pageable, like `new`).
+    ///
+    /// Pick the node attached to the GPU's PCIe root complex; memory on the
+    /// remote socket costs H2D/D2H bandwidth.
+    #[cfg(feature = "numa")]
+    pub fn on_node(node: u32, len: usize) -> Result<Self> {
+        // SAFETY: plain libnuma queries without preconditions.
+        let (available, max_node) = unsafe { (ffi::numa_available(), ffi::numa_max_node()) };
+        if available < 0 {
+            return Err(ClError::AllocationFailed("libnuma: NUMA not available".into()));
+        }
+        if node as i64 > max_node as i64 {
+            return Err(ClError::AllocationFailed(format!(
+                "libnuma: node {} out of range (max {})", node, max_node
+            )));
+        }
+        // SAFETY: numa_alloc_onnode returns page-aligned, zeroed memory or null.
+        let raw = unsafe { ffi::numa_alloc_onnode(len.max(1), node as std::ffi::c_int) };
+        let ptr = NonNull::new(raw.cast::<u8>())
+            .ok_or_else(|| ClError::AllocationFailed(format!("{} bytes on NUMA node {}", len, node)))?;
+        Ok(Self { ptr, len, backing: Backing::Numa })
+    }
+
+    /// Length in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if buffer is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Deref for PinnedBuffer {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        // SAFETY: ptr is valid for len initialised bytes for the lifetime of self.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for PinnedBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // SAFETY: as in deref; &mut self gives exclusive access.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for PinnedBuffer {
+    fn drop(&mut self) {
+        match self.backing {
+            // SAFETY: allocated in `new` with exactly this layout.
+            Backing::Heap(layout) => unsafe { alloc::dealloc(self.ptr.as_ptr(), layout) },
+            // SAFETY: allocated by numa_alloc_onnode with size len.max(1).
+            #[cfg(feature = "numa")]
+            Backing::Numa => unsafe { ffi::numa_free(self.ptr.as_ptr().cast(), self.len.max(1)) },
+        }
+    }
+}
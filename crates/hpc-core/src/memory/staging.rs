@@ -0,0 +1,42 @@
+//! Reusable host staging area for device reads
+
+use super::PinnedBuffer;
+use crate::error::Result;
+
+/// Page-aligned host buffer that is reused across reads.
+///
+/// It only reallocates when a read needs more bytes than it holds, so a
+/// steady-state loop allocates once. Host allocations are not counted in
+/// the GPU allocation metrics.
+#[derive(Default)]
+pub struct StagingBuffer {
+    buf: Option<PinnedBuffer>,
+}
+
+impl StagingBuffer {
+    /// Empty staging area; the first read allocates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pre-allocate `bytes` so the first read does not allocate either
+    pub fn with_capacity(bytes: usize) -> Result<Self> {
+        Ok(StagingBuffer { buf: Some(PinnedBuffer::new(bytes)?) })
+    }
+
+    /// Bytes available without reallocating
+    pub fn capacity(&self) -> usize {
+        self.buf.as_ref().map_or(0, |b| b.len())
+    }
+
+    /// First `len` bytes, growing the allocation if needed
+    pub(crate) fn prepare(&mut self, len: usize) -> Result<&mut [u8]> {
+        if self.capacity() < len {
+            self.buf = Some(PinnedBuffer::new(len)?);
+        }
+        match self.buf.as_mut() {
+            Some(b) => Ok(&mut b[..len]),
+            None => Ok(&mut []),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "memtrace")]
+
+//! Live abort stream: one JSON line per `trace_abort`, as it happens
+
+use once_cell::sync::Lazy;
+use std::{
+    io::{self, BufWriter, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+struct AbortSink {
+    out: BufWriter<Box<dyn Write + Send>>,
+    every: u64,
+    seen: u64,
+}
+
+static SINK: Lazy<Mutex<Option<AbortSink>>> = Lazy::new(|| Mutex::new(None));
+// schneller Pfad ohne Lock, solange kein Sink gesetzt ist
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Additionally write every `sample_every`-th abort from `trace_abort`
+/// (1 = all, 0 counts as 1) to `out` as one JSON line:
+///
+/// `{"t_us":1718000000123456,"token":"stm","cause":"conflict","retries":2,"conflict_sz":64}`
+///
+/// `t_us` is wall-clock µs since the Unix epoch (the record's `now_us`
+/// timestamp plus `t0_unix_us`). Lines go through a `BufWriter` and are flushed one at a time, so
+/// a reader (pipe, socket, tailed file) sees each abort immediately. The
+/// log itself is unaffected. A write error prints once and removes the sink.
+pub fn set_abort_sink<W: Write + Send + 'static>(out: W, sample_every: u32) {
+    let sink = AbortSink {
+        out: BufWriter::new(Box::new(out)),
+        every: u64::from(sample_every.max(1)),
+        seen: 0,
+    };
+    let prev = SINK.lock().unwrap().replace(sink);
+    ACTIVE.store(true, Ordering::Relaxed);
+    if let Some(mut prev) = prev {
+        let _ = prev.out.flush();
+    }
+}
+
+/// Remove the abort sink, flushing what it still buffers
+pub fn clear_abort_sink() -> io::Result<()> {
+    ACTIVE.store(false, Ordering::Relaxed);
+    match SINK.lock().unwrap().take() {
+        Some(mut sink) => sink.out.flush(),
+        None => Ok(()),
+    }
+}
+
+pub(super) fn emit(t_us: u64, token: &str, cause: &str, retries: u32, conflict_sz: u32) {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut guard = SINK.lock().unwrap();
+    let Some(sink) = guard.as_mut() else {
+        return;
+    };
+    sink.seen += 1;
+    if (sink.seen - 1) % sink.every != 0 {
+        return;
+    }
+    let res = writeln!(
+        sink.out,
+        r#"{{"t_us":{},"token":"{}","cause":"{}","retries":{},"conflict_sz":{}}}"#,
+        t_us,
+        escape(token),
+        escape(cause),
+        retries,
+        conflict_sz
+    )
+    .and_then(|()| sink.out.flush());
+    if let Err(e) = res {
+        diag_error!("memtrace: abort sink disabled: {e}");
+        ACTIVE.store(false, Ordering::Relaxed);
+        *guard = None;
+    }
+}
+
+// JSON-String-Escaping für Token/Cause (frei wählbar, z.B. mit Anführungszeichen)
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -2,7 +2,7 @@
 
 use once_cell::sync::Lazy;
 use std::sync::Mutex;
-use super::{LOG, Record, Dir, Phase, AUTO_TRACE};
+use super::{LOG, Record, Dir, Phase, AUTO_TRACE, roi::roi_tags};
 
 /// Abort event information
 pub struct AbortEvent {
@@ -25,15 +25,11 @@ pub fn log_abort(ev: &AbortEvent) {
     }
     
     let mut log = LOG.lock().unwrap();
-    let prev_end = log.last().map(|r| r.t_end_us).unwrap_or(0);
-    let idle = if ev.t_start_us > prev_end { 
-        ev.t_start_us - prev_end 
-    } else { 
-        0 
-    };
+    let idle = ev.t_start_us.saturating_sub(super::prev_end(&log));
     let abort_tok = CURRENT_ABORT.lock().unwrap().clone();
 
     log.push(Record {
+        seq: super::next_seq(),
         t_start_us: ev.t_start_us,
         t_end_us: ev.t_end_us,
         bytes: 0,
@@ -45,6 +41,9 @@ pub fn log_abort(ev: &AbortEvent) {
         cause: Some(ev.cause.clone()),
         retries: Some(ev.retries),
         conflict_sz: Some(ev.conflict_sz),
+        conflict_with: None,
+        tags: roi_tags(),
+        device: super::current_device(),
     });
 }
 
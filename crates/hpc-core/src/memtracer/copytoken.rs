@@ -1,33 +1,74 @@
 #![cfg(feature = "memtrace")]
 
-use std::time::Instant;
-use super::{LOG, Record, Dir, Phase, T0, AUTO_TRACE, CURRENT_ABORT};
+use opencl3::event::Event;
+use super::{LOG, Record, Dir, Phase, AUTO_TRACE, BARRIER, CURRENT_ABORT, now_us, raw_now_us, from_raw_us, next_seq, prev_end, roi::roi_tags, current_device};
 
 /// Token for tracking copy operations
 pub struct CopyToken {
-    start: Instant,
+    /// µs since T0 at `start`, without origin: a `rebase_timebase` before
+    /// `finish` must not put the end before the start
+    start_raw_us: u64,
     bytes: usize,
     dir: Dir,
     finished: bool,
+    tags: Vec<(String, String)>,
+    tx_id: Option<u64>,
+    device: u16,
 }
 
 impl CopyToken {
+    /// Attach a `key=value` tag (e.g. `tile=3`) to the record this token
+    /// produces. `,`, `;`, `=`, `%` and line breaks are written as `%XX`
+    /// in memtrace.csv and decoded again by `load_csv`.
+    pub fn tag(mut self, key: &str, value: impl std::fmt::Display) -> Self {
+        self.tags.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Correlate the record with transaction `tx_id`, so `flush_dot` chains
+    /// it with the other transfers and kernels of that transaction
+    pub fn tx(mut self, tx_id: u64) -> Self {
+        self.tx_id = Some(tx_id);
+        self
+    }
+
     /// Finish and log the operation
     pub fn finish(mut self) {
         self.log_once();
     }
 
+    /// Finish with the device-side duration of `evt` instead of host timing.
+    ///
+    /// Timebase: profiling counters are nanoseconds on the device clock,
+    /// which is not synchronised with the host, so only differences are
+    /// used. The record starts at the token's host start plus the
+    /// QUEUED→START delay and lasts START→END. This assumes the token was
+    /// started right around the enqueue of the command. Falls back to host
+    /// timing if profiling info is unavailable (queue without
+    /// `CL_QUEUE_PROFILING_ENABLE`, or the command has not completed).
+    pub fn finish_with_event(mut self, evt: &Event) {
+        match device_span(evt) {
+            Some((delay_ns, dur_ns)) => {
+                let s = from_raw_us(self.start_raw_us).saturating_add(delay_ns / 1000);
+                self.push(s, s.saturating_add(dur_ns / 1000));
+            }
+            None => self.log_once(),
+        }
+    }
+
     fn log_once(&mut self) {
         if self.finished {
             return;
         }
         
-        let s = self.start.duration_since(*T0).as_micros() as u64;
-        let e = Instant::now().duration_since(*T0).as_micros() as u64;
-        
+        let s = from_raw_us(self.start_raw_us);
+        let e = now_us();
+        self.push(s, e);
+    }
+
+    fn push(&mut self, s: u64, e: u64) {
         let mut log = LOG.lock().unwrap();
-        let prev_end = log.last().map(|r| r.t_end_us).unwrap_or(0);
-        let idle = if s > prev_end { s - prev_end } else { 0 };
+        let idle = s.saturating_sub(prev_end(&log));
         let abort = CURRENT_ABORT.lock().unwrap().clone();
 
         let phase = match self.dir {
@@ -36,6 +77,7 @@ impl CopyToken {
         };
 
         log.push(Record {
+            seq: next_seq(),
             t_start_us: s,
             t_end_us: e,
             bytes: self.bytes,
@@ -43,10 +85,13 @@ impl CopyToken {
             idle_us: idle,
             abort_token: abort,
             phase,
-            tx_id: None,
+            tx_id: self.tx_id,
             cause: None,
             retries: None,
             conflict_sz: None,
+            conflict_with: None,
+            tags: std::mem::take(&mut self.tags),
+            device: self.device,
         });
 
         self.finished = true;
@@ -61,13 +106,26 @@ impl Drop for CopyToken {
     }
 }
 
-/// Start tracking a transfer
+// (QUEUED→START, START→END) in ns, None ohne Profiling-Info
+fn device_span(evt: &Event) -> Option<(u64, u64)> {
+    let queued = evt.profiling_command_queued().ok()?;
+    let start = evt.profiling_command_start().ok()?;
+    let end = evt.profiling_command_end().ok()?;
+    Some((start.saturating_sub(queued), end.saturating_sub(start)))
+}
+
+/// Start tracking a transfer; an open region of interest and the current
+/// device are recorded now, even if the token finishes (e.g. in an event
+/// callback) after `roi_end`
 pub fn start(dir: Dir, bytes: usize) -> CopyToken {
     CopyToken {
-        start: Instant::now(),
+        start_raw_us: raw_now_us(),
         bytes,
         dir,
         finished: false,
+        tags: roi_tags(),
+        tx_id: None,
+        device: current_device(),
     }
 }
 
@@ -79,10 +137,10 @@ pub fn log_transfer(t_start_us: u64, t_end_us: u64, bytes: usize, dir: Dir) {
     
     let abort = CURRENT_ABORT.lock().unwrap().clone();
     let mut log = LOG.lock().unwrap();
-    let prev_end = log.last().map(|r| r.t_end_us).unwrap_or(0);
-    let idle = if t_start_us > prev_end { t_start_us - prev_end } else { 0 };
+    let idle = t_start_us.saturating_sub(prev_end(&log));
     
     log.push(Record {
+        seq: next_seq(),
         t_start_us,
         t_end_us,
         bytes,
@@ -94,5 +152,57 @@ pub fn log_transfer(t_start_us: u64, t_end_us: u64, bytes: usize, dir: Dir) {
         cause: None,
         retries: None,
         conflict_sz: None,
+        conflict_with: None,
+        tags: roi_tags(),
+        device: current_device(),
     });
-}
\ No newline at end of file
+}
+/// Mark a synchronisation point (`queue.finish()`, barrier) at `now_us()`:
+/// a zero-length, zero-byte record with phase `Barrier` on the kernel lane
+/// and `label` as tag `label`, so idle gaps in a timeline have a cause.
+/// Its `idle_us` is 0: the gap is charged to the next operation, and
+/// summaries ignore barriers.
+pub fn trace_barrier(label: &str) {
+    if !AUTO_TRACE.load(std::sync::atomic::Ordering::Relaxed) {
+        return;
+    }
+
+    let t = now_us();
+    let abort = CURRENT_ABORT.lock().unwrap().clone();
+    let mut tags = vec![("label".to_string(), label.to_string())];
+    tags.extend(roi_tags());
+    let mut log = LOG.lock().unwrap();
+
+    log.push(Record {
+        seq: next_seq(),
+        t_start_us: t,
+        t_end_us: t,
+        bytes: 0,
+        dir: Dir::Kernel,
+        idle_us: 0,
+        abort_token: abort,
+        phase: Phase::Custom(BARRIER),
+        tx_id: None,
+        cause: None,
+        retries: None,
+        conflict_sz: None,
+        conflict_with: None,
+        tags,
+        device: current_device(),
+    });
+}
+
+/// Push a caller-built record (custom instrumentation). Skipped while
+/// auto-tracing is off.
+///
+/// The record is stored as-is except for `seq`, which is assigned here:
+/// the caller is responsible for consistent timestamps (take them from
+/// `now_us()`), for `idle_us` and for the region tag (`current_roi()`).
+pub fn log_record(mut r: Record) {
+    if !AUTO_TRACE.load(std::sync::atomic::Ordering::Relaxed) {
+        return;
+    }
+    let mut log = LOG.lock().unwrap();
+    r.seq = next_seq();
+    log.push(r);
+}
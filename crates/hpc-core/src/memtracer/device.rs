@@ -0,0 +1,26 @@
+#![cfg(feature = "memtrace")]
+
+//! Per-thread device index, stored in every record for multi-device runs
+
+use std::cell::Cell;
+
+thread_local! {
+    // je Thread: Dispatcher geben üblicherweise jedem Gerät eigene Threads
+    static CURRENT_DEVICE: Cell<u16> = const { Cell::new(0) };
+}
+
+/// Tag records logged from this thread with device `id` (the `device`
+/// column of memtrace.csv, 0 until set). Use one index per device, e.g.
+/// its position in `Platform::get_devices`, so per-device throughput and
+/// idle can be computed from one log.
+///
+/// `CopyToken`s take the device at `start`, so tokens finished in an event
+/// callback on a driver thread keep the right one.
+pub fn set_current_device(id: u16) {
+    CURRENT_DEVICE.with(|d| d.set(id));
+}
+
+/// Device index of this thread, see `set_current_device`
+pub fn current_device() -> u16 {
+    CURRENT_DEVICE.with(Cell::get)
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "memtrace")]
+
+//! Graphviz export of per-transaction dependency chains
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use super::{LOG, Phase, Record};
+
+/// Write the transfers and kernels that carry a `tx_id` (`CopyToken::tx`)
+/// as a Graphviz DOT graph (`dot -Tsvg memtrace.dot`).
+///
+/// Each transaction is one cluster; its records become nodes labeled with
+/// direction, bytes and duration, chained by edges in start order (e.g.
+/// H2D → KRN → D2H). The chain is the job's timeline order, not a
+/// dependency analysis: overlapping records are still drawn one after
+/// the other. Records without `tx_id` and aborts are left out.
+pub fn flush_dot<P: AsRef<Path>>(path: P) -> crate::Result<()> {
+    let log = LOG.lock().unwrap();
+    let mut by_tx: BTreeMap<u64, Vec<&Record>> = BTreeMap::new();
+    for r in log.iter().filter(|r| !matches!(r.phase, Phase::Abort)) {
+        if let Some(tx) = r.tx_id {
+            by_tx.entry(tx).or_default().push(r);
+        }
+    }
+
+    let mut f = BufWriter::new(File::create(path)?);
+    writeln!(f, "digraph memtrace {{")?;
+    writeln!(f, "  rankdir=LR;")?;
+    writeln!(f, "  node [shape=box, fontname=\"monospace\"];")?;
+    for (tx, mut recs) in by_tx {
+        // stabil: gleichzeitige Starts bleiben in Log-Reihenfolge
+        recs.sort_by_key(|r| r.t_start_us);
+        writeln!(f, "  subgraph cluster_tx{} {{", tx)?;
+        writeln!(f, "    label=\"tx {} ({} µs)\";", tx, span_us(&recs))?;
+        for (i, r) in recs.iter().enumerate() {
+            writeln!(
+                f,
+                "    tx{}_{} [label=\"{}\\n{}\\n{} µs\"];",
+                tx,
+                i,
+                r.dir.as_str(),
+                crate::format_bytes(r.bytes as u64),
+                r.duration_us()
+            )?;
+        }
+        for i in 1..recs.len() {
+            writeln!(f, "    tx{}_{} -> tx{}_{};", tx, i - 1, tx, i)?;
+        }
+        writeln!(f, "  }}")?;
+    }
+    writeln!(f, "}}")?;
+    f.flush()?;
+    Ok(())
+}
+
+// Erster Start bis letztes Ende der Transaktion
+fn span_us(recs: &[&Record]) -> u64 {
+    let start = recs.iter().map(|r| r.t_start_us).min().unwrap_or(0);
+    let end = recs.iter().map(|r| r.t_end_us).max().unwrap_or(0);
+    end.saturating_sub(start)
+}
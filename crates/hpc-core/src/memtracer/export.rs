@@ -0,0 +1,120 @@
+#![cfg(feature = "json")]
+
+//! JSON exporters: raw record dump and Chrome/Perfetto trace
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::{fs::File, io::BufWriter, path::Path};
+
+use super::{LOG, Dir, Phase, Record};
+
+/// JSON view of one record (enums as their CSV strings)
+#[derive(Serialize)]
+pub(super) struct RecordJson<'a> {
+    seq: u64,
+    t_start_us: u64,
+    t_end_us: u64,
+    bytes: usize,
+    dir: &'static str,
+    idle_us: u64,
+    abort_token: Option<&'a str>,
+    phase: &'static str,
+    tx_id: Option<u64>,
+    cause: Option<&'a str>,
+    retries: Option<u32>,
+    conflict_sz: Option<usize>,
+    conflict_with: Option<&'a str>,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    tags: Map<String, Value>,
+    device: u16,
+}
+
+impl<'a> From<&'a Record> for RecordJson<'a> {
+    fn from(r: &'a Record) -> Self {
+        RecordJson {
+            seq: r.seq,
+            t_start_us: r.t_start_us,
+            t_end_us: r.t_end_us,
+            bytes: r.bytes,
+            dir: r.dir.as_str(),
+            idle_us: r.idle_us,
+            abort_token: r.abort_token.as_deref(),
+            phase: r.phase.as_str(),
+            tx_id: r.tx_id,
+            cause: r.cause.as_deref(),
+            retries: r.retries,
+            conflict_sz: r.conflict_sz,
+            conflict_with: r.conflict_with.as_deref(),
+            tags: tag_map(r),
+            device: r.device,
+        }
+    }
+}
+
+// Tags als JSON-Objekt; bei doppelten Schlüsseln gewinnt der letzte
+fn tag_map(r: &Record) -> Map<String, Value> {
+    r.tags.iter().map(|(k, v)| (k.clone(), Value::from(v.as_str()))).collect()
+}
+
+/// Write every record (transfers, kernels and aborts) as one JSON array
+pub fn flush_json<P: AsRef<Path>>(path: P) -> crate::Result<()> {
+    let log = LOG.lock().unwrap();
+    let records: Vec<RecordJson> = log.iter().map(RecordJson::from).collect();
+    let f = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(f, &records).map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+/// Write the log in Chrome trace-event format (`chrome://tracing`, Perfetto).
+///
+/// Transfers/kernels become complete events ("X") on one lane per direction,
+/// aborts instant events ("i") on a lane per abort token.
+pub fn flush_chrome_trace<P: AsRef<Path>>(path: P) -> crate::Result<()> {
+    let log = LOG.lock().unwrap();
+    let mut abort_lanes: Vec<&str> = Vec::new();
+    let events: Vec<Value> = log.iter().map(|r| chrome_event(r, &mut abort_lanes)).collect();
+
+    let f = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(f, &json!({ "traceEvents": events, "displayTimeUnit": "ms" }))
+        .map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+/// Chrome trace event of one record; `abort_lanes` assigns one lane per
+/// abort token in order of first appearance
+pub(super) fn chrome_event<'a>(r: &'a Record, abort_lanes: &mut Vec<&'a str>) -> Value {
+    if matches!(r.phase, Phase::Abort) {
+        let token = r.abort_token.as_deref().unwrap_or("");
+        let lane = match abort_lanes.iter().position(|t| *t == token) {
+            Some(i) => i,
+            None => {
+                abort_lanes.push(token);
+                abort_lanes.len() - 1
+            }
+        };
+        json!({
+            "name": r.cause.as_deref().unwrap_or("abort"),
+            "cat": "abort",
+            "ph": "i",
+            "s": "t",
+            "ts": r.t_start_us,
+            "pid": 2,
+            "tid": lane,
+            "args": { "token": token, "tx_id": r.tx_id, "retries": r.retries },
+        })
+    } else {
+        let tid = match r.dir { Dir::H2D => 0, Dir::D2H => 1, Dir::Kernel => 2 };
+        let mut args = tag_map(r);
+        args.insert("bytes".into(), r.bytes.into());
+        json!({
+            "name": r.phase.as_str(),
+            "cat": r.dir.as_str(),
+            "ph": "X",
+            "ts": r.t_start_us,
+            "dur": r.t_end_us.saturating_sub(r.t_start_us),
+            "pid": 1,
+            "tid": tid,
+            "args": args,
+        })
+    }
+}
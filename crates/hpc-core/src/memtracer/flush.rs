@@ -0,0 +1,907 @@
+#![cfg(feature = "memtrace")]
+
+//! Writers for the global `LOG`: transfer CSV, abort CSVs and summary
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering},
+};
+#[cfg(feature = "memtrace_full")]
+use std::sync::Mutex;
+use super::{
+    LOG, RUN_METADATA, origin_unix_us, Record, Dir, Phase, CAUSE_BUDGET_EXHAUSTED, peak_concurrency, push_edges,
+};
+
+/// Decimal places of `retries_avg`/`conflict_avg` in memtrace_abort.csv
+static CSV_FLOAT_PRECISION: AtomicUsize = AtomicUsize::new(3);
+
+/// Set the decimal places of the averages in memtrace_abort.csv (default 3)
+pub fn set_csv_float_precision(n: usize) {
+    CSV_FLOAT_PRECISION.store(n, Ordering::Relaxed);
+}
+
+/// Aggregation key of memtrace_abort.csv, see `set_abort_group_by`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One row per token over all causes:
+    /// `abort_token,count,...,last_us,gave_up_count,commits,abort_rate`
+    Token,
+    /// One row per cause over all tokens: `cause,count,...,last_us`
+    Cause,
+    /// One row per `(abort_token, cause)`
+    #[default]
+    Both,
+}
+
+static ABORT_GROUP_BY: AtomicU8 = AtomicU8::new(GroupBy::Both as u8);
+
+/// Choose the rows of memtrace_abort.csv (default `GroupBy::Both`); the
+/// columns adapt, the key columns come first
+pub fn set_abort_group_by(group_by: GroupBy) {
+    ABORT_GROUP_BY.store(group_by as u8, Ordering::Relaxed);
+}
+
+/// The key chosen with `set_abort_group_by`
+pub fn abort_group_by() -> GroupBy {
+    match ABORT_GROUP_BY.load(Ordering::Relaxed) {
+        x if x == GroupBy::Token as u8 => GroupBy::Token,
+        x if x == GroupBy::Cause as u8 => GroupBy::Cause,
+        _ => GroupBy::Both,
+    }
+}
+
+/// Which abort records memtrace_abort_full.csv keeps, see `set_abort_full_limit`
+#[cfg(feature = "memtrace_full")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AbortFullLimit {
+    /// Every abort record
+    #[default]
+    All,
+    /// The first `n` aborts in log order
+    First(usize),
+    /// A uniform random sample of `n` aborts, written in log order; the
+    /// same `seed` picks the same records from the same log
+    Sample { n: usize, seed: u64 },
+}
+
+#[cfg(feature = "memtrace_full")]
+impl AbortFullLimit {
+    // Obergrenze; None = unbegrenzt
+    fn cap(self) -> Option<usize> {
+        match self {
+            AbortFullLimit::All => None,
+            AbortFullLimit::First(n) | AbortFullLimit::Sample { n, .. } => Some(n),
+        }
+    }
+}
+
+#[cfg(feature = "memtrace_full")]
+static ABORT_FULL_LIMIT: Mutex<AbortFullLimit> = Mutex::new(AbortFullLimit::All);
+
+/// Cap the rows of memtrace_abort_full.csv (default `AbortFullLimit::All`).
+///
+/// memtrace_abort.csv and the summary still count every abort; the summary
+/// gets an `abort_full_omitted` line with the number of records left out.
+/// The cap applies per written file, so `flush_csv_append` caps each
+/// appended batch on its own.
+#[cfg(feature = "memtrace_full")]
+pub fn set_abort_full_limit(limit: AbortFullLimit) {
+    *ABORT_FULL_LIMIT.lock().unwrap() = limit;
+}
+
+/// The limit set with `set_abort_full_limit`
+#[cfg(feature = "memtrace_full")]
+pub fn abort_full_limit() -> AbortFullLimit {
+    *ABORT_FULL_LIMIT.lock().unwrap()
+}
+
+// Warnschwelle idle_total_us / wall_us als f64-Bits; NaN-Bits = aus
+static IDLE_WARN_THRESHOLD: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// Warn when GPU idle exceeds `fraction` of the traced wall time
+/// (`Some(0.5)` = more than half idle), e.g. as a CI gate against transfer
+/// or sync bottlenecks. `None` (the default) turns the check off.
+///
+/// Every summary flush then adds a `warning: idle ...` line to
+/// memtrace_summary.txt and logs it as a warning; `idle_exceeds_threshold`
+/// gives the same verdict as a flag, e.g. for a nonzero exit status.
+pub fn set_idle_warn_threshold(fraction: Option<f64>) {
+    let bits = fraction.filter(|f| !f.is_nan()).map_or(u64::MAX, f64::to_bits);
+    IDLE_WARN_THRESHOLD.store(bits, Ordering::Relaxed);
+}
+
+fn idle_warn_threshold() -> Option<f64> {
+    Some(f64::from_bits(IDLE_WARN_THRESHOLD.load(Ordering::Relaxed))).filter(|f| !f.is_nan())
+}
+
+/// Whether the current log is above the `set_idle_warn_threshold`
+/// fraction; always `false` while no threshold is set
+pub fn idle_exceeds_threshold() -> bool {
+    Summary::from_log(&LOG.lock().unwrap()).idle_warning().is_some()
+}
+
+/// Sequence number of the next `flush_and_rotate` segment
+static ROTATION_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// High-water mark: records `LOG[..FLUSHED]` have already been written by a
+/// flush. Cleared by `reset` and `flush_and_rotate`.
+pub(super) static FLUSHED: AtomicUsize = AtomicUsize::new(0);
+
+// Warnung beim erneuten Schreiben nur einmal pro Prozess
+static REFLUSH_WARNED: AtomicBool = AtomicBool::new(false);
+
+// Ausgabeziel: Verzeichnis + Präfix vor jedem Dateinamen ("" = Standardnamen)
+struct Target<'a> {
+    dir: &'a Path,
+    prefix: &'a str,
+}
+
+impl Target<'_> {
+    fn cwd() -> Target<'static> {
+        Target { dir: Path::new("."), prefix: "" }
+    }
+
+    fn join(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}{}", self.prefix, name))
+    }
+}
+
+/// Write all output files (memtrace.csv, abort CSVs, memtrace_summary.txt)
+/// from one consistent snapshot of the log.
+///
+/// Snapshot semantics: the files are overwritten with the *whole* log, so
+/// records already written by an earlier flush are written again. Copying
+/// the outputs of several flushes together therefore double-counts; call
+/// `reset()` between batches or use `flush_csv_append`. A one-time warning
+/// points this out.
+///
+/// Warnings and errors go to stderr, or through the `log` crate with
+/// feature "log". A file that cannot be created or written is logged as an
+/// error instead of panicking; use `flush_all` to get it as a `Result`.
+pub fn flush_csv() {
+    flush_csv_to(".");
+}
+
+/// Like `flush_csv`, but writes the files into `dir` (which must exist).
+/// Also stops a running `start_sampler` thread.
+pub fn flush_csv_to<P: AsRef<Path>>(dir: P) {
+    if let Err(e) = super::stop_sampler() {
+        diag_warn!("memtrace: stopping sampler failed: {}", e);
+    }
+    let dir = Target { dir: dir.as_ref(), prefix: "" };
+    let log = LOG.lock().unwrap();
+    mark_flushed(log.len());
+    report(write_all(&dir, &log, &super::commit_counts()));
+}
+
+// Schreibfehler der Flush-Funktionen ohne Rückgabewert melden statt paniken
+fn report(res: io::Result<()>) {
+    if let Err(e) = res {
+        diag_error!("memtrace: flush failed: {}", e);
+    }
+}
+
+// Hochwassermarke auf `len` setzen; warnt (einmal), wenn davon schon
+// Records geschrieben waren
+pub(super) fn mark_flushed(len: usize) {
+    let flushed = FLUSHED.swap(len, Ordering::Relaxed).min(len);
+    if flushed > 0 && !REFLUSH_WARNED.swap(true, Ordering::Relaxed) {
+        diag_warn!(
+            "memtrace: flush writes {} already flushed records again; \
+             use reset() between batches or flush_csv_append",
+            flushed
+        );
+    }
+}
+
+/// Incremental flush: append only the records logged since the previous
+/// flush to memtrace.csv (and memtrace_abort_full.csv), writing header and
+/// metadata only when the file is new or empty.
+///
+/// The aggregates (memtrace_abort.csv, memtrace_summary.txt) are rewritten
+/// from the whole log, so they never count a record twice. Mixing this with
+/// `flush_csv` on the same files duplicates rows.
+pub fn flush_csv_append() {
+    flush_csv_append_to(".");
+}
+
+/// Like `flush_csv_append`, but for the files in `dir` (which must exist)
+pub fn flush_csv_append_to<P: AsRef<Path>>(dir: P) {
+    let dir = Target { dir: dir.as_ref(), prefix: "" };
+    let log = LOG.lock().unwrap();
+    let from = FLUSHED.swap(log.len(), Ordering::Relaxed).min(log.len());
+    report((|| {
+        append_transfers(&dir, &log[from..])?;
+        write_abort_agg(&dir, &log, &super::commit_counts())?;
+        #[cfg(feature = "memtrace_full")]
+        append_aborts_full(&dir, &log[from..])?;
+        write_summary(&dir, &log)
+    })());
+}
+
+/// Checkpoint a long run: move all current records out of the log and
+/// write them as `<prefix>_<seq>_memtrace.csv` etc. into `dir` (which must
+/// exist). Returns the sequence number used, counting up from 0.
+///
+/// Taking the records happens under one lock, so each record lands in
+/// exactly one segment. Abort aggregates and the summary cover that
+/// segment only.
+pub fn flush_and_rotate<P: AsRef<Path>>(dir: P, prefix: &str) -> u64 {
+    let (log, commits) = {
+        let mut guard = LOG.lock().unwrap();
+        FLUSHED.store(0, Ordering::Relaxed);
+        (std::mem::take(&mut *guard), super::take_commit_counts())
+    };
+    let seq = ROTATION_SEQ.fetch_add(1, Ordering::Relaxed);
+    let prefix = format!("{}_{:04}_", prefix, seq);
+    report(write_all(&Target { dir: dir.as_ref(), prefix: &prefix }, &log, &commits));
+    seq
+}
+
+fn write_all(dir: &Target, log: &[Record], commits: &CommitMap) -> io::Result<()> {
+    write_transfers(dir, log)?;
+    write_aborts(dir, log, commits)?;
+    write_summary(dir, log)
+}
+
+/// Write only memtrace.csv
+pub fn flush_transfers() {
+    report(write_transfers(&Target::cwd(), &LOG.lock().unwrap()));
+}
+
+/// Write only memtrace_abort.csv (and memtrace_abort_full.csv with `memtrace_full`)
+pub fn flush_aborts() {
+    report(write_aborts(&Target::cwd(), &LOG.lock().unwrap(), &super::commit_counts()));
+}
+
+/// Write only memtrace_summary.txt
+pub fn flush_summary() {
+    report(write_summary(&Target::cwd(), &LOG.lock().unwrap()));
+}
+
+// Fehler tragen den Pfad, sonst ist "permission denied" im Log nicht zuzuordnen
+fn with_path<T>(path: &Path, res: io::Result<T>) -> io::Result<T> {
+    res.map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+pub(super) fn create(path: &Path) -> io::Result<BufWriter<File>> {
+    with_path(path, File::create(path)).map(BufWriter::new)
+}
+
+fn write_transfers(dir: &Target, log: &[Record]) -> io::Result<()> {
+    // A) Transfer/Kernel Events → memtrace.csv
+    let path = dir.join("memtrace.csv");
+    let mut f = create(&path)?;
+    with_path(&path, transfer_header(&mut f).and_then(|_| transfer_rows(&mut f, log)))
+}
+
+fn append_transfers(dir: &Target, log: &[Record]) -> io::Result<()> {
+    let path = dir.join("memtrace.csv");
+    let (f, new) = open_append(&path)?;
+    let mut f = BufWriter::new(f);
+    if new {
+        with_path(&path, transfer_header(&mut f))?;
+    }
+    with_path(&path, transfer_rows(&mut f, log))
+}
+
+// zum Anhängen öffnen; true, wenn die Datei neu oder leer ist
+fn open_append(path: &Path) -> io::Result<(File, bool)> {
+    let f = with_path(path, OpenOptions::new().create(true).append(true).open(path))?;
+    let new = f.metadata().map_or(true, |m| m.len() == 0);
+    Ok((f, new))
+}
+
+pub(super) fn transfer_header<W: Write>(f: &mut W) -> io::Result<()> {
+    for (k, v) in RUN_METADATA.lock().unwrap().iter() {
+        // Zeilenumbrüche würden den Kommentar beenden
+        writeln!(f, "# {}={}", k, v.replace(['\n', '\r'], " "))?;
+    }
+    writeln!(f, "seq,t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase,tags,device")
+}
+
+fn transfer_rows<W: Write>(f: &mut W, log: &[Record]) -> io::Result<()> {
+    for r in log.iter().filter(|r| !matches!(r.phase, Phase::Abort)) {
+        transfer_row(f, r)?;
+    }
+    f.flush()
+}
+
+// eine Zeile von memtrace.csv; Aborts filtert der Aufrufer
+pub(super) fn transfer_row<W: Write>(f: &mut W, r: &Record) -> io::Result<()> {
+    let dir = match r.dir { Dir::H2D => "H2D", Dir::D2H => "D2H", Dir::Kernel => "Kernel" };
+    let phase = r.phase.as_str();
+    let tags: Vec<String> = r.tags.iter().map(|(k, v)| format!("{}={}", escape_tag(k), escape_tag(v))).collect();
+    writeln!(
+        f,
+        "{},{},{},{},{},{},{},{},{},{}",
+        r.seq,
+        r.t_start_us,
+        r.t_end_us,
+        r.bytes,
+        dir,
+        r.idle_us,
+        r.abort_token.as_deref().unwrap_or(""),
+        phase,
+        tags.join(";"),
+        r.device
+    )
+}
+
+// Trennzeichen der CSV (und `%` selbst) in Tags als %XX, damit jeder Tag
+// die Spalte übersteht; load_csv dekodiert
+fn escape_tag(s: &str) -> std::borrow::Cow<'_, str> {
+    const SPECIAL: &[char] = &[',', ';', '=', '%', '\n', '\r'];
+    if !s.contains(SPECIAL) {
+        return s.into();
+    }
+    let mut out = String::with_capacity(s.len() + 8);
+    for c in s.chars() {
+        if SPECIAL.contains(&c) {
+            out.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            out.push(c);
+        }
+    }
+    out.into()
+}
+
+/// Aggregate of the abort records of one `(abort_token, cause)` pair.
+///
+/// Counters and sums saturate at `u64::MAX` instead of wrapping (release)
+/// or panicking (debug); once a sum is saturated its average is a lower bound.
+#[derive(Clone, Debug)]
+pub struct AbortAgg {
+    pub count: u64,
+    pub retries_sum: u64,
+    pub conflict_sum: u64,
+    pub conflict_min: usize,
+    pub conflict_max: usize,
+    pub first_us: u64,
+    pub last_us: u64,
+}
+
+impl Default for AbortAgg {
+    fn default() -> Self {
+        AbortAgg {
+            count: 0,
+            retries_sum: 0,
+            conflict_sum: 0,
+            conflict_min: usize::MAX,
+            conflict_max: 0,
+            first_us: 0,
+            last_us: 0,
+        }
+    }
+}
+
+impl AbortAgg {
+    /// Fold one abort record into the aggregate
+    pub fn add(&mut self, r: &Record) {
+        self.count = self.count.saturating_add(1);
+        self.retries_sum = self.retries_sum.saturating_add(r.retries.unwrap_or(0) as u64);
+        let c = r.conflict_sz.unwrap_or(0);
+        self.conflict_sum = self.conflict_sum.saturating_add(c as u64);
+        self.conflict_min = self.conflict_min.min(c);
+        self.conflict_max = self.conflict_max.max(c);
+        if self.first_us == 0 || r.t_start_us < self.first_us { self.first_us = r.t_start_us; }
+        if r.t_end_us > self.last_us { self.last_us = r.t_end_us; }
+    }
+
+    /// Fold another aggregate into this one, e.g. several causes of a token
+    pub fn merge(&mut self, other: &AbortAgg) {
+        if other.count == 0 {
+            return;
+        }
+        self.count = self.count.saturating_add(other.count);
+        self.retries_sum = self.retries_sum.saturating_add(other.retries_sum);
+        self.conflict_sum = self.conflict_sum.saturating_add(other.conflict_sum);
+        self.conflict_min = self.conflict_min.min(other.conflict_min);
+        self.conflict_max = self.conflict_max.max(other.conflict_max);
+        if self.first_us == 0 || other.first_us < self.first_us { self.first_us = other.first_us; }
+        if other.last_us > self.last_us { self.last_us = other.last_us; }
+    }
+
+    pub fn retries_avg(&self) -> f64 {
+        if self.count > 0 { self.retries_sum as f64 / self.count as f64 } else { 0.0 }
+    }
+
+    pub fn conflict_avg(&self) -> f64 {
+        if self.count > 0 { self.conflict_sum as f64 / self.count as f64 } else { 0.0 }
+    }
+}
+
+fn write_aborts(dir: &Target, log: &[Record], commits: &CommitMap) -> io::Result<()> {
+    write_abort_agg(dir, log, commits)?;
+
+    // Optional: Voll-Log der Aborts → memtrace_abort_full.csv (nur wenn Feature aktiv)
+    #[cfg(feature = "memtrace_full")]
+    {
+        let path = dir.join("memtrace_abort_full.csv");
+        let mut ff = create(&path)?;
+        with_path(&path, abort_full_header(&mut ff).and_then(|_| abort_full_rows(&mut ff, log)))?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "memtrace_full")]
+fn append_aborts_full(dir: &Target, log: &[Record]) -> io::Result<()> {
+    let path = dir.join("memtrace_abort_full.csv");
+    let (f, new) = open_append(&path)?;
+    let mut ff = BufWriter::new(f);
+    if new {
+        with_path(&path, abort_full_header(&mut ff))?;
+    }
+    with_path(&path, abort_full_rows(&mut ff, log))
+}
+
+/// Abort-Aggregate je `(abort_token, cause)`
+pub(super) type AbortAggMap = HashMap<(String, String), AbortAgg>;
+
+/// Commits je `abort_token` (`trace_commit`)
+pub(super) type CommitMap = HashMap<String, u64>;
+
+// Abort-Record in die Aggregate aufnehmen; andere Records filtert der Aufrufer
+pub(super) fn add_abort(agg: &mut AbortAggMap, r: &Record) {
+    let token = r.abort_token.as_deref().unwrap_or("").to_string();
+    let cause = r.cause.as_deref().unwrap_or("").to_string();
+    agg.entry((token, cause)).or_default().add(r);
+}
+
+fn write_abort_agg(dir: &Target, log: &[Record], commits: &CommitMap) -> io::Result<()> {
+    // B) Abort-Events (aggregiert) → memtrace_abort.csv
+    let mut agg = AbortAggMap::new();
+    for r in log.iter().filter(|r| matches!(r.phase, Phase::Abort)) {
+        add_abort(&mut agg, r);
+    }
+    let path = dir.join("memtrace_abort.csv");
+    let mut f = create(&path)?;
+    with_path(&path, abort_agg_csv(&mut f, &agg, commits))
+}
+
+/// memtrace_abort.csv with the rows of `abort_group_by()`.
+///
+/// `Both`: one row per `(abort_token, cause)`; `commits` and
+/// `abort_rate = aborts / (aborts + commits)` are per token and repeat on
+/// each of its rows (aborts without the `budget_exhausted` marker records).
+/// Tokens with commits but no aborts get one row with an empty cause.
+/// `Token`: the same per token, its statistics over all causes except the
+/// marker, which only counts in `gave_up_count`. `Cause`: per cause over
+/// all tokens, without the per-token columns.
+pub(super) fn abort_agg_csv<W: Write>(fa: &mut W, agg: &AbortAggMap, commits: &CommitMap) -> io::Result<()> {
+    // je Token über alle Causes: aufgegebene Transaktionen und echte Aborts
+    let mut per_token: HashMap<&str, (u64, u64)> = HashMap::new();
+    for ((token, cause), a) in agg.iter() {
+        let (gave_up, aborts) = per_token.entry(token.as_str()).or_insert((0, 0));
+        if cause == CAUSE_BUDGET_EXHAUSTED {
+            *gave_up = gave_up.saturating_add(a.count);
+        } else {
+            *aborts = aborts.saturating_add(a.count);
+        }
+    }
+
+    const STATS: &str = "count,retries_avg,conflict_avg,conflict_min,conflict_max,first_us,last_us";
+    const PER_TOKEN: &str = "gave_up_count,commits,abort_rate";
+    let prec = CSV_FLOAT_PRECISION.load(Ordering::Relaxed);
+    let stats = |a: &AbortAgg| {
+        let c_min = if a.conflict_min == usize::MAX { 0 } else { a.conflict_min };
+        format!(
+            "{},{:.prec$},{:.prec$},{},{},{},{}",
+            a.count, a.retries_avg(), a.conflict_avg(), c_min, a.conflict_max, a.first_us, a.last_us
+        )
+    };
+    // gave_up_count,commits,abort_rate eines Tokens
+    let token_cols = |token: &str| {
+        let (gave_up, aborts) = per_token.get(token).copied().unwrap_or((0, 0));
+        let c = commits.get(token).copied().unwrap_or(0);
+        let total = aborts.saturating_add(c);
+        let rate = if total > 0 { aborts as f64 / total as f64 } else { 0.0 };
+        format!("{},{},{:.prec$}", gave_up, c, rate)
+    };
+    let commit_only = commits.keys().filter(|t| !per_token.contains_key(t.as_str()));
+
+    match abort_group_by() {
+        GroupBy::Both => {
+            writeln!(fa, "abort_token,cause,{},{}", STATS, PER_TOKEN)?;
+            for ((token, cause), a) in agg.iter() {
+                writeln!(fa, "{},{},{},{}", token, cause, stats(a), token_cols(token))?;
+            }
+            for token in commit_only {
+                writeln!(fa, "{},,{},{}", token, stats(&AbortAgg::default()), token_cols(token))?;
+            }
+        }
+        GroupBy::Token => {
+            let mut by_token: HashMap<&str, AbortAgg> = HashMap::new();
+            for ((token, cause), a) in agg.iter() {
+                let t = by_token.entry(token.as_str()).or_default();
+                if cause != CAUSE_BUDGET_EXHAUSTED {
+                    t.merge(a);
+                }
+            }
+            for token in commit_only {
+                by_token.entry(token.as_str()).or_default();
+            }
+            writeln!(fa, "abort_token,{},{}", STATS, PER_TOKEN)?;
+            for (token, a) in by_token.iter() {
+                writeln!(fa, "{},{},{}", token, stats(a), token_cols(token))?;
+            }
+        }
+        GroupBy::Cause => {
+            let mut by_cause: HashMap<&str, AbortAgg> = HashMap::new();
+            for ((_, cause), a) in agg.iter() {
+                by_cause.entry(cause.as_str()).or_default().merge(a);
+            }
+            writeln!(fa, "cause,{}", STATS)?;
+            for (cause, a) in by_cause.iter() {
+                writeln!(fa, "{},{}", cause, stats(a))?;
+            }
+        }
+    }
+    fa.flush()
+}
+
+#[cfg(feature = "memtrace_full")]
+pub(super) fn abort_full_header<W: Write>(ff: &mut W) -> io::Result<()> {
+    writeln!(ff, "seq,tx_id,cause,retries,conflict_sz,t_start_us,t_end_us,abort_token")
+}
+
+#[cfg(feature = "memtrace_full")]
+fn abort_full_rows<W: Write>(ff: &mut W, log: &[Record]) -> io::Result<()> {
+    let mut pick = AbortFullPick::new();
+    for r in log.iter().filter(|r| matches!(r.phase, Phase::Abort)) {
+        pick.add(r);
+    }
+    pick.write(ff)
+}
+
+/// Abort records for memtrace_abort_full.csv under `abort_full_limit()`,
+/// fed one at a time in log order
+#[cfg(feature = "memtrace_full")]
+pub(super) struct AbortFullPick<'a> {
+    limit: AbortFullLimit,
+    seen: usize,
+    // (Position unter den Aborts, Record); bei Sample ein Reservoir
+    kept: Vec<(usize, &'a Record)>,
+}
+
+#[cfg(feature = "memtrace_full")]
+impl<'a> AbortFullPick<'a> {
+    pub(super) fn new() -> Self {
+        AbortFullPick { limit: abort_full_limit(), seen: 0, kept: Vec::new() }
+    }
+
+    pub(super) fn add(&mut self, r: &'a Record) {
+        let i = self.seen;
+        self.seen += 1;
+        match self.limit {
+            AbortFullLimit::All => self.kept.push((i, r)),
+            AbortFullLimit::First(n) => {
+                if i < n {
+                    self.kept.push((i, r));
+                }
+            }
+            // Reservoir-Sampling (Algorithmus R): Abort i ersetzt mit
+            // Wahrscheinlichkeit n/(i+1) einen zufälligen Platz
+            AbortFullLimit::Sample { n, seed } => {
+                if i < n {
+                    self.kept.push((i, r));
+                } else {
+                    let j = (crate::backoff::splitmix64(seed ^ i as u64) % (i as u64 + 1)) as usize;
+                    if j < n {
+                        self.kept[j] = (i, r);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Write the kept records in log order
+    pub(super) fn write<W: Write>(mut self, ff: &mut W) -> io::Result<()> {
+        self.kept.sort_unstable_by_key(|&(i, _)| i);
+        for (_, r) in self.kept {
+            abort_full_row(ff, r)?;
+        }
+        ff.flush()
+    }
+}
+
+#[cfg(feature = "memtrace_full")]
+fn abort_full_row<W: Write>(ff: &mut W, r: &Record) -> io::Result<()> {
+    writeln!(
+        ff,
+        "{},{},{},{},{},{},{},{}",
+        r.seq,
+        r.tx_id.unwrap_or(0),
+        r.cause.as_deref().unwrap_or(""),
+        r.retries.unwrap_or(0),
+        r.conflict_sz.unwrap_or(0),
+        r.t_start_us,
+        r.t_end_us,
+        r.abort_token.as_deref().unwrap_or("")
+    )
+}
+
+/// Aggregate of one trace, written as memtrace_summary.txt (and .json)
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+struct Summary {
+    events_total: usize,
+    idle_total_us: u64,
+    idle_before_h2d_us: u64,
+    idle_before_d2h_us: u64,
+    idle_before_kernel_us: u64,
+    wall_us: u64,
+    bytes_h2d: u64,
+    bytes_d2h: u64,
+    aborts: usize,
+    gave_up: usize,
+    max_concurrent_ops: usize,
+    suspicious_records: usize,
+    t0_unix_us: u64,
+    slowest: Vec<SlowOp>,
+    // Aborts, die set_abort_full_limit aus memtrace_abort_full.csv weglässt
+    #[cfg(feature = "memtrace_full")]
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    abort_full_omitted: Option<usize>,
+}
+
+/// Number of longest operations listed in memtrace_summary.txt
+/// (`slowest_transfers` returns any number)
+pub const SLOWEST_IN_SUMMARY: usize = 3;
+
+// Eintrag der slowest-Zeile
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+struct SlowOp {
+    dir: &'static str,
+    bytes: usize,
+    duration_us: u64,
+    t_start_us: u64,
+}
+
+impl Summary {
+    fn from_log(log: &[Record]) -> Self {
+        let mut acc = SummaryAcc::default();
+        log.iter().for_each(|r| acc.add(r));
+        acc.finish()
+    }
+
+    // Warnzeile, falls die Lücken zwischen den Ops (idle_before_*, ohne
+    // Vorlauf vor der ersten Op wie wall_us) über der Schwelle liegen
+    fn idle_warning(&self) -> Option<String> {
+        let threshold = idle_warn_threshold()?;
+        if self.wall_us == 0 {
+            return None;
+        }
+        let idle = self.idle_before_h2d_us + self.idle_before_d2h_us + self.idle_before_kernel_us;
+        let fraction = idle as f64 / self.wall_us as f64;
+        (fraction > threshold).then(|| {
+            format!(
+                "warning: GPU idle {:.1}% of wall time ({} of {} µs) exceeds threshold {:.1}%",
+                fraction * 100.0,
+                idle,
+                self.wall_us,
+                threshold * 100.0
+            )
+        })
+    }
+}
+
+/// `Summary` built one record at a time, in log order.
+///
+/// Idle gaps are attributed to the direction of the operation that follows
+/// them (`idle_before_*_us`), measured from the end of the previous
+/// transfer or kernel; abort records count only as aborts, barriers not at
+/// all, and the lead-in before the first operation is not counted.
+#[derive(Default)]
+pub(super) struct SummaryAcc {
+    events_total: usize,
+    idle_total_us: u64,
+    idle: [u64; 3],
+    bytes: [u64; 2],
+    aborts: usize,
+    gave_up: usize,
+    suspicious: usize,
+    first_start: Option<u64>,
+    prev_end: Option<u64>,
+    // Start/Ende-Kanten für max_concurrent_ops
+    edges: Vec<(u64, i32)>,
+    // die SLOWEST_IN_SUMMARY längsten Ops, längste zuerst
+    slowest: Vec<SlowOp>,
+}
+
+impl SummaryAcc {
+    pub(super) fn add(&mut self, r: &Record) {
+        if r.is_barrier() {
+            return;
+        }
+        self.events_total += 1;
+        self.idle_total_us += r.idle_us;
+        match r.dir {
+            Dir::H2D => self.bytes[0] += r.bytes as u64,
+            Dir::D2H => self.bytes[1] += r.bytes as u64,
+            Dir::Kernel => {}
+        }
+        if r.is_suspicious() {
+            self.suspicious += 1;
+        }
+        if matches!(r.phase, Phase::Abort) {
+            // Aufgabe-Marker zählen nur als gave_up, wie in memtrace_abort.csv
+            if r.cause.as_deref() == Some(CAUSE_BUDGET_EXHAUSTED) {
+                self.gave_up += 1;
+            } else {
+                self.aborts += 1;
+            }
+            return;
+        }
+
+        if let Some(end) = self.prev_end {
+            let slot = match r.dir { Dir::H2D => 0, Dir::D2H => 1, Dir::Kernel => 2 };
+            self.idle[slot] += r.t_start_us.saturating_sub(end);
+        }
+        self.first_start = Some(self.first_start.map_or(r.t_start_us, |start| start.min(r.t_start_us)));
+        self.prev_end = Some(self.prev_end.map_or(r.t_end_us, |end| end.max(r.t_end_us)));
+        push_edges(&mut self.edges, r);
+
+        // gleich lange bleiben in Log-Reihenfolge
+        let dur = r.duration_us();
+        let pos = self.slowest.partition_point(|s| s.duration_us >= dur);
+        if pos < SLOWEST_IN_SUMMARY {
+            self.slowest.insert(pos, SlowOp { dir: r.dir.as_str(), bytes: r.bytes, duration_us: dur, t_start_us: r.t_start_us });
+            self.slowest.truncate(SLOWEST_IN_SUMMARY);
+        }
+    }
+
+    fn finish(self) -> Summary {
+        Summary {
+            events_total: self.events_total,
+            idle_total_us: self.idle_total_us,
+            idle_before_h2d_us: self.idle[0],
+            idle_before_d2h_us: self.idle[1],
+            idle_before_kernel_us: self.idle[2],
+            wall_us: match (self.first_start, self.prev_end) {
+                (Some(start), Some(end)) => end.saturating_sub(start),
+                _ => 0,
+            },
+            bytes_h2d: self.bytes[0],
+            bytes_d2h: self.bytes[1],
+            aborts: self.aborts,
+            gave_up: self.gave_up,
+            max_concurrent_ops: peak_concurrency(self.edges),
+            suspicious_records: self.suspicious,
+            t0_unix_us: origin_unix_us(),
+            slowest: self.slowest,
+            #[cfg(feature = "memtrace_full")]
+            // memtrace_abort_full.csv hat auch eine Zeile je Aufgabe-Marker
+            abort_full_omitted: abort_full_limit().cap().map(|n| (self.aborts + self.gave_up).saturating_sub(n)),
+        }
+    }
+
+    /// Write memtrace_summary.txt (and .json with feature "json") into `dir`
+    pub(super) fn write(self, dir: &Path) -> io::Result<()> {
+        write_summary_file(&Target { dir, prefix: "" }, &self.finish())
+    }
+}
+
+fn write_summary(dir: &Target, log: &[Record]) -> io::Result<()> {
+    write_summary_file(dir, &Summary::from_log(log))
+}
+
+fn write_summary_file(dir: &Target, sum: &Summary) -> io::Result<()> {
+    // C) Summary → memtrace_summary.txt (+ memtrace_summary.json mit Feature "json")
+    let path = dir.join("memtrace_summary.txt");
+    let mut fs = create(&path)?;
+    with_path(&path, summary_txt(&mut fs, sum))?;
+    if let Some(warning) = sum.idle_warning() {
+        diag_warn!("memtrace: {}", warning);
+    }
+
+    #[cfg(feature = "json")]
+    {
+        let path = dir.join("memtrace_summary.json");
+        let mut fj = create(&path)?;
+        with_path(&path, serde_json::to_writer_pretty(&mut fj, sum).map_err(io::Error::from).and_then(|()| fj.flush()))?;
+    }
+    Ok(())
+}
+
+fn summary_txt<W: Write>(fs: &mut W, sum: &Summary) -> io::Result<()> {
+    writeln!(fs, "events_total: {}", sum.events_total)?;
+    writeln!(fs, "idle_total_us: {}", sum.idle_total_us)?;
+    writeln!(fs, "idle_before_h2d_us: {}", sum.idle_before_h2d_us)?;
+    writeln!(fs, "idle_before_d2h_us: {}", sum.idle_before_d2h_us)?;
+    writeln!(fs, "idle_before_kernel_us: {}", sum.idle_before_kernel_us)?;
+    writeln!(fs, "wall_us: {}", sum.wall_us)?;
+    writeln!(fs, "bytes_h2d: {}", sum.bytes_h2d)?;
+    writeln!(fs, "bytes_d2h: {}", sum.bytes_d2h)?;
+    writeln!(fs, "bytes_h2d_human: {}", crate::format_bytes(sum.bytes_h2d))?;
+    writeln!(fs, "bytes_d2h_human: {}", crate::format_bytes(sum.bytes_d2h))?;
+    writeln!(fs, "aborts: {}", sum.aborts)?;
+    writeln!(fs, "gave_up: {}", sum.gave_up)?;
+    writeln!(fs, "max_concurrent_ops: {}", sum.max_concurrent_ops)?;
+    writeln!(fs, "suspicious_records: {}", sum.suspicious_records)?;
+    writeln!(fs, "t0_unix_us: {}", sum.t0_unix_us)?;
+    let slowest: Vec<String> = sum
+        .slowest
+        .iter()
+        .map(|s| format!("{} {} {} µs @{}", s.dir, crate::format_bytes(s.bytes as u64), s.duration_us, s.t_start_us))
+        .collect();
+    writeln!(fs, "slowest: {}", if slowest.is_empty() { "-".to_string() } else { slowest.join(", ") })?;
+    #[cfg(feature = "memtrace_full")]
+    if let Some(omitted) = sum.abort_full_omitted {
+        writeln!(fs, "abort_full_omitted: {}", omitted)?;
+    }
+    if sum.suspicious_records > 0 {
+        writeln!(
+            fs,
+            "warning: {} record(s) end before they start or move bytes in 0 µs; check instrumentation",
+            sum.suspicious_records
+        )?;
+    }
+    if let Some(warning) = sum.idle_warning() {
+        writeln!(fs, "{}", warning)?;
+    }
+    fs.flush()
+}
+
+/// The summary totals on one grep-able line for log aggregators, e.g.
+/// `ev=1234 idle=56ms h2d=128MiB d2h=128MiB aborts=7 h2d_bw=9.1GiB/s d2h_bw=8.7GiB/s`,
+/// in the units of `current_unit()` (MB and GB/s for `Unit::Decimal`).
+/// A bandwidth is `-` while that direction has no timed transfers.
+pub fn summary_oneline() -> String {
+    let log = LOG.lock().unwrap();
+    let sum = Summary::from_log(&log);
+    let (mega, mega_name) = crate::current_unit().mega();
+    let (giga, giga_name) = crate::current_unit().giga();
+
+    // Giga/s über die Summe der Transferzeiten einer Richtung
+    let bw = |h2d: bool, bytes: u64| {
+        let us: u64 = log.iter()
+            .filter(|r| if h2d { matches!(r.dir, Dir::H2D) } else { matches!(r.dir, Dir::D2H) })
+            .map(|r| r.duration_us())
+            .sum();
+        if us == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.1}{}/s", bytes as f64 / giga / (us as f64 / 1e6), giga_name)
+        }
+    };
+
+    format!(
+        "ev={} idle={}ms h2d={}{} d2h={}{} aborts={} h2d_bw={} d2h_bw={}",
+        sum.events_total,
+        sum.idle_total_us / 1000,
+        (sum.bytes_h2d as f64 / mega) as u64,
+        mega_name,
+        (sum.bytes_d2h as f64 / mega) as u64,
+        mega_name,
+        sum.aborts,
+        bw(true, sum.bytes_h2d),
+        bw(false, sum.bytes_d2h),
+    )
+}
+
+/// Write the abort contention graph as `token_a,token_b,count` edges,
+/// heaviest pairs first. Aborts without a known partner have an empty `token_b`.
+pub fn flush_conflict_graph<P: AsRef<Path>>(path: P) -> crate::Result<()> {
+    let mut edges: BTreeMap<(String, String), u64> = BTreeMap::new();
+    {
+        let log = LOG.lock().unwrap();
+        for r in log.iter().filter(|r| matches!(r.phase, Phase::Abort)) {
+            let a = r.abort_token.clone().unwrap_or_default();
+            let b = r.conflict_with.clone().unwrap_or_default();
+            *edges.entry((a, b)).or_insert(0) += 1;
+        }
+    }
+
+    let mut sorted: Vec<_> = edges.into_iter().collect();
+    sorted.sort_by_key(|e| std::cmp::Reverse(e.1));
+
+    let mut f = File::create(path)?;
+    writeln!(f, "token_a,token_b,count")?;
+    for ((a, b), count) in sorted {
+        writeln!(f, "{},{},{}", a, b, count)?;
+    }
+    Ok(())
+}
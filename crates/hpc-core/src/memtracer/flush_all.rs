@@ -0,0 +1,197 @@
+//! All output formats from one lock and one pass over the log
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use super::{LOG, Phase};
+use super::flush::{self, AbortAggMap, SummaryAcc};
+
+/// Outputs of `flush_all`; file names as written by the single flushes
+#[derive(Clone, Debug)]
+pub struct FlushConfig {
+    /// Target directory (must exist)
+    pub dir: PathBuf,
+    /// memtrace.csv
+    pub csv: bool,
+    /// memtrace_abort.csv (and memtrace_abort_full.csv with `memtrace_full`)
+    pub aborts: bool,
+    /// memtrace_summary.txt (and .json with `json`)
+    pub summary: bool,
+    /// memtrace.json, as `flush_json` (needs `json`)
+    pub json: bool,
+    /// memtrace_chrome.json, as `flush_chrome_trace` (needs `json`)
+    pub chrome: bool,
+    /// memtrace.perfetto-trace, as `flush_perfetto` (needs `perfetto`)
+    pub perfetto: bool,
+    /// Only records logged inside a region of interest (`roi_begin`); the
+    /// summary then covers just those, `idle_us` stays as logged
+    pub roi_only: bool,
+}
+
+impl FlushConfig {
+    /// The `flush_csv_to` set (CSV, aborts, summary) into `dir`
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        FlushConfig {
+            dir: dir.into(),
+            csv: true,
+            aborts: true,
+            summary: true,
+            json: false,
+            chrome: false,
+            perfetto: false,
+            roi_only: false,
+        }
+    }
+
+    /// Every output the enabled features can produce
+    pub fn everything<P: Into<PathBuf>>(dir: P) -> Self {
+        FlushConfig {
+            json: cfg!(feature = "json"),
+            chrome: cfg!(feature = "json"),
+            perfetto: cfg!(feature = "perfetto"),
+            ..Self::new(dir)
+        }
+    }
+}
+
+/// Write the outputs selected in `config` under one lock of `LOG`, in a
+/// single iteration over the records; for large logs much cheaper than
+/// calling the individual flushes one after another.
+///
+/// Output is identical to the single flushes. Formats whose feature is
+/// disabled are skipped with a warning. With `csv` the flushed high-water
+/// mark moves as for `flush_csv`, unless `roi_only` left records out. A
+/// running `start_sampler` thread is stopped first.
+pub fn flush_all(config: FlushConfig) -> crate::Result<()> {
+    super::stop_sampler()?;
+    let dir = config.dir.as_path();
+    let create = |name: &str| -> std::io::Result<BufWriter<File>> { flush::create(&dir.join(name)) };
+
+    let log = LOG.lock().unwrap();
+
+    let mut csv = if config.csv {
+        // nur eine vollständige CSV zählt als geflusht, sonst fehlten die
+        // Records außerhalb der Regionen später in flush_csv_append
+        if !config.roi_only {
+            flush::mark_flushed(log.len());
+        }
+        let mut f = create("memtrace.csv")?;
+        flush::transfer_header(&mut f)?;
+        Some(f)
+    } else {
+        None
+    };
+    let mut aborts = config.aborts.then(AbortAggMap::new);
+    #[cfg(feature = "memtrace_full")]
+    let mut aborts_full = if config.aborts {
+        let mut f = create("memtrace_abort_full.csv")?;
+        flush::abort_full_header(&mut f)?;
+        Some((f, flush::AbortFullPick::new()))
+    } else {
+        None
+    };
+    let mut summary = config.summary.then(SummaryAcc::default);
+
+    #[cfg(feature = "json")]
+    let mut json = if config.json {
+        let mut f = create("memtrace.json")?;
+        f.write_all(b"[")?;
+        Some(f)
+    } else {
+        None
+    };
+    #[cfg(feature = "json")]
+    let mut chrome = if config.chrome {
+        let mut f = create("memtrace_chrome.json")?;
+        // Schlüssel sortiert wie serde_json::Map, damit die Ausgabe gleich bleibt
+        f.write_all(b"{\"displayTimeUnit\":\"ms\",\"traceEvents\":[")?;
+        Some((f, Vec::new()))
+    } else {
+        None
+    };
+    #[cfg(not(feature = "json"))]
+    if config.json || config.chrome {
+        diag_warn!("flush_all: json/chrome need feature \"json\"; skipped");
+    }
+    #[cfg(feature = "perfetto")]
+    let mut perfetto = if config.perfetto {
+        Some(super::perfetto::PerfettoWriter::new(create("memtrace.perfetto-trace")?)?)
+    } else {
+        None
+    };
+    #[cfg(not(feature = "perfetto"))]
+    if config.perfetto {
+        diag_warn!("flush_all: perfetto needs feature \"perfetto\"; skipped");
+    }
+
+    let selected = log.iter().filter(|r| !config.roi_only || r.roi().is_some());
+    // i nur für die JSON-Trennzeichen
+    #[cfg_attr(not(feature = "json"), allow(unused_variables))]
+    for (i, r) in selected.enumerate() {
+        let is_abort = matches!(r.phase, Phase::Abort);
+        if let Some(f) = csv.as_mut().filter(|_| !is_abort) {
+            flush::transfer_row(f, r)?;
+        }
+        if let Some(agg) = aborts.as_mut().filter(|_| is_abort) {
+            flush::add_abort(agg, r);
+        }
+        #[cfg(feature = "memtrace_full")]
+        if let Some((_, pick)) = aborts_full.as_mut().filter(|_| is_abort) {
+            pick.add(r);
+        }
+        if let Some(acc) = summary.as_mut() {
+            acc.add(r);
+        }
+        #[cfg(feature = "json")]
+        if let Some(f) = json.as_mut() {
+            if i > 0 {
+                f.write_all(b",")?;
+            }
+            serde_json::to_writer(&mut *f, &super::export::RecordJson::from(r)).map_err(std::io::Error::from)?;
+        }
+        #[cfg(feature = "json")]
+        if let Some((f, lanes)) = chrome.as_mut() {
+            if i > 0 {
+                f.write_all(b",")?;
+            }
+            let ev = super::export::chrome_event(r, lanes);
+            serde_json::to_writer(&mut *f, &ev).map_err(std::io::Error::from)?;
+        }
+        #[cfg(feature = "perfetto")]
+        if let Some(w) = perfetto.as_mut() {
+            w.add(r)?;
+        }
+    }
+
+    if let Some(mut f) = csv {
+        f.flush()?;
+    }
+    if let Some(agg) = aborts {
+        flush::abort_agg_csv(&mut create("memtrace_abort.csv")?, &agg, &super::commit_counts())?;
+    }
+    #[cfg(feature = "memtrace_full")]
+    if let Some((mut f, pick)) = aborts_full {
+        pick.write(&mut f)?;
+    }
+    if let Some(acc) = summary {
+        acc.write(dir)?;
+    }
+    #[cfg(feature = "json")]
+    if let Some(mut f) = json {
+        f.write_all(b"]")?;
+        f.flush()?;
+    }
+    #[cfg(feature = "json")]
+    if let Some((mut f, _)) = chrome {
+        f.write_all(b"]}")?;
+        f.flush()?;
+    }
+    #[cfg(feature = "perfetto")]
+    if let Some(w) = perfetto {
+        w.finish()?;
+    }
+    Ok(())
+}
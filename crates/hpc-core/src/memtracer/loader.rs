@@ -0,0 +1,121 @@
+#![cfg(feature = "memtrace")]
+
+//! Trace loader: parses `memtrace.csv` back into `Record`s
+
+use std::{fs, path::Path};
+use crate::error::{ClError, Result};
+use super::{Record, Dir, Phase};
+
+const HEADER: &str = "seq,t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase,tags,device";
+/// Traces written before the `tags` column existed
+const HEADER_V1: &str = "t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase";
+/// Traces written before the `device` column existed
+const HEADER_V2: &str = "t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase,tags";
+/// Traces written before the `seq` column existed
+const HEADER_V3: &str = "t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase,tags,device";
+// ohne seq
+const COLUMNS: usize = 9;
+
+/// Load a transfer CSV written by `flush_csv` for offline analysis.
+///
+/// Abort-only fields (`tx_id`, `cause`, ...) are not part of the transfer
+/// CSV and come back as `None`. Metadata comment lines are skipped; files
+/// from before the `tags`/`device` columns load with empty tags and device 0,
+/// files without the `seq` column (no header naming it) number their
+/// records in file order.
+pub fn load_csv<P: AsRef<Path>>(path: P) -> Result<Vec<Record>> {
+    let text = fs::read_to_string(path)?;
+    let mut out = Vec::new();
+    // welches Format gilt, sagt der (letzte) Header; ohne Header das alte
+    let mut with_seq = false;
+
+    for (idx, line) in text.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line.trim_end_matches('\r');
+        // `# key=value` lines carry run metadata, see `set_run_metadata`
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line == HEADER || line == HEADER_V1 || line == HEADER_V2 || line == HEADER_V3 {
+            with_seq = line == HEADER;
+            continue;
+        }
+        let fallback_seq = out.len() as u64;
+        out.push(
+            parse_line(line, with_seq, fallback_seq).map_err(|reason| ClError::TraceParse { line: line_no, reason })?,
+        );
+    }
+
+    Ok(out)
+}
+
+fn parse_line(line: &str, with_seq: bool, fallback_seq: u64) -> std::result::Result<Record, String> {
+    let (seq, line) = if with_seq {
+        let (seq, rest) = line.split_once(',').ok_or("missing columns after seq")?;
+        (seq.parse::<u64>().map_err(|_| format!("invalid seq: '{}'", seq))?, rest)
+    } else {
+        (fallback_seq, line)
+    };
+    let cols: Vec<&str> = line.split(',').collect();
+    if !(COLUMNS - 2..=COLUMNS).contains(&cols.len()) {
+        return Err(format!("expected {} to {} columns, found {}", COLUMNS - 2, COLUMNS, cols.len()));
+    }
+
+    let num = |name: &str, v: &str| -> std::result::Result<u64, String> {
+        v.parse::<u64>().map_err(|_| format!("invalid {}: '{}'", name, v))
+    };
+
+    let dir: Dir = cols[3].parse().map_err(|_| format!("unknown dir: '{}'", cols[3]))?;
+    let phase: Phase = cols[6].parse().map_err(|_| format!("unknown phase: '{}'", cols[6]))?;
+    let tags = cols
+        .get(7)
+        .map_or("", |t| *t)
+        .split(';')
+        .filter(|t| !t.is_empty())
+        .map(|t| match t.split_once('=') {
+            Some((k, v)) => Ok((unescape_tag(k)?, unescape_tag(v)?)),
+            None => Err(format!("invalid tag: '{}'", t)),
+        })
+        .collect::<std::result::Result<Vec<_>, String>>()?;
+
+    Ok(Record {
+        seq,
+        t_start_us: num("t_start_us", cols[0])?,
+        t_end_us: num("t_end_us", cols[1])?,
+        bytes: num("bytes", cols[2])? as usize,
+        dir,
+        idle_us: num("idle_us", cols[4])?,
+        abort_token: if cols[5].is_empty() { None } else { Some(cols[5].to_string()) },
+        phase,
+        tx_id: None,
+        cause: None,
+        retries: None,
+        conflict_sz: None,
+        conflict_with: None,
+        tags,
+        device: match cols.get(8) {
+            Some(d) => d.parse().map_err(|_| format!("invalid device: '{}'", d))?,
+            None => 0,
+        },
+    })
+}
+
+// %XX aus escape_tag zurückwandeln
+fn unescape_tag(s: &str) -> std::result::Result<String, String> {
+    if !s.contains('%') {
+        return Ok(s.to_string());
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('%') {
+        out.push_str(&rest[..i]);
+        let byte = rest
+            .get(i + 1..i + 3)
+            .and_then(|h| u8::from_str_radix(h, 16).ok())
+            .ok_or_else(|| format!("invalid escape in tag: '{}'", s))?;
+        out.push(byte as char);
+        rest = &rest[i + 3..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
@@ -4,24 +4,52 @@
 
 mod copytoken;
 mod aborttoken;
-
-pub use copytoken::{CopyToken, start, log_transfer};
+mod loader;
+mod flush;
+mod flush_all;
+mod export;
+mod perfetto;
+mod roi;
+mod device;
+mod dot;
+mod sampler;
+mod abort_stream;
+
+pub use copytoken::{CopyToken, start, log_transfer, log_record, trace_barrier};
 pub use aborttoken::{
     AbortEvent, log_abort, 
     set_abort_token, clear_abort_token, AbortTokenGuard,
     CURRENT_ABORT,
 };
+pub use loader::load_csv;
+pub use roi::{roi_begin, roi_end, current_roi, RoiGuard, ROI_TAG};
+pub use device::{set_current_device, current_device};
+pub use flush_all::{flush_all, FlushConfig};
+pub use dot::flush_dot;
+pub use sampler::{start_sampler, stop_sampler};
+pub use abort_stream::{set_abort_sink, clear_abort_sink};
+#[cfg(feature = "json")]
+pub use export::{flush_json, flush_chrome_trace};
+#[cfg(feature = "perfetto")]
+pub use perfetto::flush_perfetto;
+pub use flush::{
+    flush_csv, flush_csv_to, flush_csv_append, flush_csv_append_to, flush_transfers, flush_aborts, flush_summary,
+    flush_conflict_graph, flush_and_rotate, set_csv_float_precision, summary_oneline, AbortAgg,
+    SLOWEST_IN_SUMMARY, set_idle_warn_threshold, idle_exceeds_threshold, GroupBy, set_abort_group_by,
+    abort_group_by,
+};
+#[cfg(feature = "memtrace_full")]
+pub use flush::{set_abort_full_limit, abort_full_limit, AbortFullLimit};
 
 use once_cell::sync::Lazy;
 use std::{
+    cell::RefCell,
     collections::HashMap,
-    fs::File,
-    io::Write,
     sync::{
-        atomic::{AtomicBool, Ordering},
-        Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
     },
-    time::Instant,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 /// Transfer direction
@@ -60,12 +88,27 @@ impl Dir {
     }
 }
 
+impl std::str::FromStr for Dir {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "H2D" => Ok(Dir::H2D),
+            "D2H" => Ok(Dir::D2H),
+            "Kernel" | "KRN" => Ok(Dir::Kernel),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Phase of operation
 #[derive(Clone, Copy, Debug)]
 pub enum Phase {
     Transfer,
     Kernel,
     Abort,
+    /// User-defined stage (e.g. "map", "barrier"), written verbatim to the
+    /// phase column; must not contain a comma
+    Custom(&'static str),
 }
 
 impl Phase {
@@ -75,6 +118,25 @@ impl Phase {
             Phase::Transfer => "Transfer",
             Phase::Kernel => "Kernel",
             Phase::Abort => "Abort",
+            Phase::Custom(label) => label,
+        }
+    }
+}
+
+/// Phase label of `trace_barrier` records
+pub(super) const BARRIER: &str = "Barrier";
+
+/// Parses the built-in phases and `Barrier`; other `Custom` labels are
+/// rejected (they cannot be told apart from a corrupted column)
+impl std::str::FromStr for Phase {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Transfer" => Ok(Phase::Transfer),
+            "Kernel" => Ok(Phase::Kernel),
+            "Abort" => Ok(Phase::Abort),
+            BARRIER => Ok(Phase::Custom(BARRIER)),
+            _ => Err(()),
         }
     }
 }
@@ -82,6 +144,48 @@ impl Phase {
 /// Global start time reference
 pub static T0: Lazy<Instant> = Lazy::new(Instant::now);
 
+/// Wall-clock time of `T0` in µs since the Unix epoch, to shift the
+/// process-relative timestamps of different runs onto one timeline
+pub static T0_UNIX_US: Lazy<u64> = Lazy::new(|| {
+    let since_t0 = T0.elapsed();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    micros(now.saturating_sub(since_t0))
+});
+
+// Ursprung von now_us in µs seit T0; 0 bis zum ersten rebase_timebase
+static ORIGIN_US: AtomicU64 = AtomicU64::new(0);
+
+/// Move `t = 0` of all following `now_us` timestamps to "now", e.g. at the
+/// start of a second measurement phase. With `shift_existing`, buffered
+/// transfer/kernel/barrier records are moved onto the new origin as well
+/// (records older than it clamp to 0); otherwise they keep the old origin.
+///
+/// A `CopyToken` still open at the rebase is logged on the new origin, its
+/// start clamped to 0 like a shifted record, so its end never precedes
+/// its start.
+///
+/// Exports and `t0_unix_us` in the summary always refer to the current
+/// origin. Unshifted records from an earlier
+/// phase therefore overlap the new phase in a merged trace: flush (or
+/// `reset`) before rebasing, or pass `shift_existing` if one timeline is
+/// wanted.
+pub fn rebase_timebase(shift_existing: bool) {
+    let mut log = LOG.lock().unwrap();
+    let origin = raw_now_us();
+    let delta = origin.saturating_sub(ORIGIN_US.swap(origin, Ordering::Relaxed));
+    if shift_existing {
+        for r in log.iter_mut() {
+            r.t_start_us = r.t_start_us.saturating_sub(delta);
+            r.t_end_us = r.t_end_us.saturating_sub(delta);
+        }
+    }
+}
+
+// Wanduhrzeit des aktuellen Ursprungs: t0_unix_us und Abort-Stream
+pub(super) fn origin_unix_us() -> u64 {
+    T0_UNIX_US.saturating_add(ORIGIN_US.load(Ordering::Relaxed))
+}
+
 /// Auto-trace enable flag
 pub static AUTO_TRACE: AtomicBool = AtomicBool::new(true);
 
@@ -104,8 +208,10 @@ pub fn is_auto_trace_enabled() -> bool {
 }
 
 /// Log record
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Record {
+    /// Position in logging order, gap-free from 0 since the last `reset`
+    pub seq: u64,
     pub t_start_us: u64,
     pub t_end_us: u64,
     pub bytes: usize,
@@ -117,120 +223,215 @@ pub struct Record {
     pub cause: Option<String>,
     pub retries: Option<u32>,
     pub conflict_sz: Option<usize>,
+    pub conflict_with: Option<String>,
+    /// Per-event `(key, value)` tags, see `CopyToken::tag`
+    pub tags: Vec<(String, String)>,
+    /// Device index of the logging thread, see `set_current_device`
+    pub device: u16,
+}
+
+impl Record {
+    /// `t_end_us - t_start_us`, clamped to 0 for skewed records
+    #[inline]
+    pub fn duration_us(&self) -> u64 {
+        self.t_end_us.saturating_sub(self.t_start_us)
+    }
+
+    /// Region of interest the record was logged in (tag [`ROI_TAG`])
+    pub fn roi(&self) -> Option<&str> {
+        self.tags.iter().find(|(k, _)| k == ROI_TAG).map(|(_, v)| v.as_str())
+    }
+
+    /// Marker from `trace_barrier`: not an operation, so summaries and
+    /// idle accounting skip it
+    pub fn is_barrier(&self) -> bool {
+        matches!(self.phase, Phase::Custom(BARRIER))
+    }
+
+    /// End before start, or a transfer of bytes that took no time; either
+    /// breaks bandwidth math and points at an instrumentation problem.
+    /// Aborts are points in time and never suspicious.
+    pub fn is_suspicious(&self) -> bool {
+        !matches!(self.phase, Phase::Abort)
+            && (self.t_end_us < self.t_start_us || (self.t_end_us == self.t_start_us && self.bytes > 0))
+    }
 }
 
 /// Global log storage
 pub static LOG: Lazy<Mutex<Vec<Record>>> =
     Lazy::new(|| Mutex::new(Vec::with_capacity(4096)));
 
-#[cfg(feature = "memtrace")]
-pub fn flush_csv() {
-    let log = LOG.lock().unwrap();
-
-    // A) Transfer/Kernel Events → memtrace.csv
-    let mut f = File::create("memtrace.csv").expect("memtrace.csv nicht anlegbar");
-    writeln!(f, "t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase").unwrap();
-    for r in log.iter().filter(|r| !matches!(r.phase, Phase::Abort)) {
-        let dir = match r.dir { Dir::H2D => "H2D", Dir::D2H => "D2H", Dir::Kernel => "Kernel" };
-        let phase = match r.phase { Phase::Kernel => "Kernel", Phase::Transfer => "Transfer", Phase::Abort => "Abort" };
-        writeln!(
-            f,
-            "{},{},{},{},{},{},{}",
-            r.t_start_us,
-            r.t_end_us,
-            r.bytes,
-            dir,
-            r.idle_us,
-            r.abort_token.as_deref().unwrap_or(""),
-            phase
-        ).unwrap();
+// Ende des letzten Records außer Barrieren: Idle läuft über Barrieren hinweg
+pub(super) fn prev_end(log: &[Record]) -> u64 {
+    log.iter().rev().find(|r| !r.is_barrier()).map_or(0, |r| r.t_end_us)
+}
+
+// nächste Record::seq; nur unter dem LOG-Lock ziehen, damit seq der
+// Reihenfolge im Log entspricht
+static SEQ: AtomicU64 = AtomicU64::new(0);
+
+pub(super) fn next_seq() -> u64 {
+    SEQ.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Run metadata written as `# key=value` comment lines at the top of memtrace.csv
+pub static RUN_METADATA: Lazy<Mutex<Vec<(String, String)>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Set a run metadata entry (e.g. git SHA, device name); replaces an existing key
+pub fn set_run_metadata<K: Into<String>, V: Into<String>>(key: K, value: V) {
+    let (key, value) = (key.into(), value.into());
+    let mut meta = RUN_METADATA.lock().unwrap();
+    match meta.iter_mut().find(|(k, _)| *k == key) {
+        Some(entry) => entry.1 = value,
+        None => meta.push((key, value)),
     }
+}
+
+/// Remove all run metadata entries
+pub fn clear_run_metadata() {
+    RUN_METADATA.lock().unwrap().clear();
+}
 
-    // B) Abort-Events (aggregiert) → memtrace_abort.csv
-    #[derive(Default, Clone)]
-    struct Agg {
-        count: u64,
-        retries_sum: u64,
-        conflict_sum: u64,
-        conflict_min: usize,
-        conflict_max: usize,
-        first_us: u64,
-        last_us: u64,
+/// Peak number of simultaneously in-flight transfer/kernel records.
+///
+/// Sweep-line over half-open `[t_start_us, t_end_us)` intervals: an op ending
+/// exactly when the next starts does not overlap it, zero-length records are
+/// ignored. Aborts and barriers are excluded (they are points, not operations).
+pub fn max_concurrent_ops(records: &[Record]) -> usize {
+    let mut events: Vec<(u64, i32)> = Vec::with_capacity(records.len() * 2);
+    for r in records.iter().filter(|r| !matches!(r.phase, Phase::Abort) && !r.is_barrier()) {
+        push_edges(&mut events, r);
     }
+    peak_concurrency(events)
+}
 
-    let mut agg: HashMap<(String, String), Agg> = HashMap::new();
-    for r in log.iter().filter(|r| matches!(r.phase, Phase::Abort)) {
-        let token = r.abort_token.as_deref().unwrap_or("").to_string();
-        let cause = r.cause.as_deref().unwrap_or("").to_string();
-        let entry = agg.entry((token, cause)).or_insert_with(|| Agg {
-            conflict_min: usize::MAX,
-            ..Default::default()
-        });
-        entry.count += 1;
-        entry.retries_sum += r.retries.unwrap_or(0) as u64;
-        let c = r.conflict_sz.unwrap_or(0);
-        entry.conflict_sum += c as u64;
-        if c < entry.conflict_min { entry.conflict_min = c; }
-        if c > entry.conflict_max { entry.conflict_max = c; }
-        if entry.first_us == 0 || r.t_start_us < entry.first_us { entry.first_us = r.t_start_us; }
-        if r.t_end_us > entry.last_us { entry.last_us = r.t_end_us; }
+// Start- und End-Kante eines Records; Nulllängen zählen nicht
+fn push_edges(events: &mut Vec<(u64, i32)>, r: &Record) {
+    if r.t_end_us > r.t_start_us {
+        events.push((r.t_start_us, 1));
+        events.push((r.t_end_us, -1));
     }
+}
 
-    let mut fa = File::create("memtrace_abort.csv").expect("memtrace_abort.csv nicht anlegbar");
-    writeln!(fa, "abort_token,cause,count,retries_avg,conflict_avg,conflict_min,conflict_max,first_us,last_us").unwrap();
-    for ((token, cause), a) in agg.iter() {
-        let r_avg = if a.count > 0 { a.retries_sum as f64 / a.count as f64 } else { 0.0 };
-        let c_avg = if a.count > 0 { a.conflict_sum as f64 / a.count as f64 } else { 0.0 };
-        let c_min = if a.conflict_min == usize::MAX { 0 } else { a.conflict_min };
-        writeln!(
-            fa,
-            "{},{},{},{:.3},{:.3},{},{},{},{}",
-            token, cause, a.count, r_avg, c_avg, c_min, a.conflict_max, a.first_us, a.last_us
-        ).unwrap();
+fn peak_concurrency(mut events: Vec<(u64, i32)>) -> usize {
+    // bei gleicher Zeit: Enden (-1) vor Starts (+1)
+    events.sort_unstable();
+
+    let (mut cur, mut max) = (0i32, 0i32);
+    for (_, delta) in events {
+        cur += delta;
+        max = max.max(cur);
     }
+    max as usize
+}
 
-    // Optional: Voll-Log der Aborts → memtrace_abort_full.csv (nur wenn Feature aktiv)
-    #[cfg(feature = "memtrace_full")]
-    {
-        let mut ff = File::create("memtrace_abort_full.csv").expect("memtrace_abort_full.csv nicht anlegbar");
-        writeln!(ff, "tx_id,cause,retries,conflict_sz,t_start_us,t_end_us,abort_token").unwrap();
-        for r in log.iter().filter(|r| matches!(r.phase, Phase::Abort)) {
-            writeln!(
-                ff,
-                "{},{},{},{},{},{},{}",
-                r.tx_id.unwrap_or(0),
-                r.cause.as_deref().unwrap_or(""),
-                r.retries.unwrap_or(0),
-                r.conflict_sz.unwrap_or(0),
-                r.t_start_us,
-                r.t_end_us,
-                r.abort_token.as_deref().unwrap_or("")
-            ).unwrap();
-        }
+/// The `n` longest non-abort records in `LOG` (transfers, kernels,
+/// barriers), longest first, equal durations by start time. For hunting
+/// single outliers such as a stalled first transfer.
+pub fn slowest_transfers(n: usize) -> Vec<Record> {
+    with_records(|log| slowest(log, n).into_iter().cloned().collect())
+}
+
+fn slowest(records: &[Record], n: usize) -> Vec<&Record> {
+    let mut ops: Vec<&Record> = records.iter().filter(|r| !matches!(r.phase, Phase::Abort)).collect();
+    // Teilsortierung: nur die n längsten ordnen
+    let longest_first = |a: &&Record, b: &&Record| b.duration_us().cmp(&a.duration_us());
+    if n < ops.len() {
+        ops.select_nth_unstable_by(n, longest_first);
+        ops.truncate(n);
     }
+    ops.sort_by(|a, b| longest_first(a, b).then(a.t_start_us.cmp(&b.t_start_us)));
+    ops
+}
+
+/// Run `f` on all records currently in `LOG`, without copying them, e.g.
+/// `with_records(|r| r.iter().filter(|r| r.bytes > 1 << 20).count())`.
+///
+/// The log stays locked while `f` runs: every traced operation on other
+/// threads blocks until it returns, and calling anything that logs or
+/// flushes from inside `f` deadlocks. Keep `f` short and clone out what is
+/// needed for longer work.
+pub fn with_records<R>(f: impl FnOnce(&[Record]) -> R) -> R {
+    f(&LOG.lock().unwrap())
+}
 
-    // C) Summary → memtrace_summary.txt
-    let total_events = log.len();
-    let total_idle: u64 = log.iter().map(|r| r.idle_us).sum();
-    let bytes_h2d: u64 = log.iter()
-        .filter(|r| matches!(r.dir, Dir::H2D))
-        .map(|r| r.bytes as u64).sum();
-    let bytes_d2h: u64 = log.iter()
-        .filter(|r| matches!(r.dir, Dir::D2H))
-        .map(|r| r.bytes as u64).sum();
-    let aborts = log.iter().filter(|r| matches!(r.phase, Phase::Abort)).count();
-
-    let mut fs = File::create("memtrace_summary.txt").expect("memtrace_summary.txt nicht anlegbar");
-    writeln!(fs, "events_total: {}", total_events).unwrap();
-    writeln!(fs, "idle_total_us: {}", total_idle).unwrap();
-    writeln!(fs, "bytes_h2d: {}", bytes_h2d).unwrap();
-    writeln!(fs, "bytes_d2h: {}", bytes_d2h).unwrap();
-    writeln!(fs, "aborts: {}", aborts).unwrap();
+/// Number of records currently buffered in `LOG` (not yet cleared by `reset`)
+#[inline]
+pub fn log_len() -> usize {
+    LOG.lock().unwrap().len()
+}
+
+// Commits je Token (trace_commit): nur gezählt, kein Record. Ein Atomic
+// pro Token, Slots werden nie entfernt (reset setzt sie auf 0), damit die
+// Thread-Caches gültig bleiben
+static COMMIT_SLOTS: Lazy<RwLock<HashMap<String, Arc<AtomicU64>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+thread_local! {
+    // Slots der Tokens dieses Threads: Commits ohne globalen Lock
+    static COMMIT_CACHE: RefCell<Vec<(Box<str>, Arc<AtomicU64>)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn commit_slot(abort_token: &str) -> Arc<AtomicU64> {
+    if let Some(slot) = COMMIT_SLOTS.read().unwrap().get(abort_token) {
+        return slot.clone();
+    }
+    COMMIT_SLOTS.write().unwrap().entry(abort_token.to_string()).or_default().clone()
+}
+
+/// Count a committed transaction of `abort_token`, the counterpart of
+/// `trace_abort`. A no-op while auto-trace is disabled.
+///
+/// Commits are the common case, so only a per-token atomic counter is
+/// kept (no record, no timestamp); after the first commit of a token on a
+/// thread it is found in a thread-local cache without any lock.
+/// memtrace_abort.csv turns it into the `commits` and `abort_rate` columns.
+pub fn trace_commit(abort_token: &str) {
+    if !AUTO_TRACE.load(Ordering::Relaxed) {
+        return;
+    }
+    COMMIT_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let i = match cache.iter().position(|(t, _)| &**t == abort_token) {
+            Some(i) => i,
+            None => {
+                cache.push((abort_token.into(), commit_slot(abort_token)));
+                cache.len() - 1
+            }
+        };
+        cache[i].1.fetch_add(1, Ordering::Relaxed);
+    });
+}
+
+/// Commits counted by `trace_commit` per token since the last `reset`
+pub fn commit_counts() -> HashMap<String, u64> {
+    COMMIT_SLOTS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(t, n)| (t.clone(), n.load(Ordering::Relaxed)))
+        .filter(|&(_, n)| n > 0)
+        .collect()
+}
+
+// Zähler auslesen und nullen; jeder Commit landet in genau einem Ergebnis
+pub(super) fn take_commit_counts() -> HashMap<String, u64> {
+    COMMIT_SLOTS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(t, n)| (t.clone(), n.swap(0, Ordering::Relaxed)))
+        .filter(|&(_, n)| n > 0)
+        .collect()
 }
 
 /// Reset all logs
 pub fn reset() {
-    LOG.lock().unwrap().clear();
+    let mut log = LOG.lock().unwrap();
+    log.clear();
+    SEQ.store(0, Ordering::Relaxed);
+    take_commit_counts();
+    flush::FLUSHED.store(0, Ordering::Relaxed);
 }
 
 /// RAII scope for temporarily changing trace state
@@ -263,18 +464,95 @@ impl Drop for TracingScope {
     }
 }
 
-/// Get current time in microseconds since T0
+/// Get current time in microseconds since T0 (or since the last
+/// `rebase_timebase`).
+///
+/// All trace records (transfers, kernels, barriers, aborts) take their
+/// timestamps from here, as `u64` µs. The ceiling
+/// `u64::MAX` µs is about 584,000 years; conversions saturate there instead
+/// of truncating, and debug builds assert it is never reached.
+///
+/// With feature `test-util`, a clock installed via `set_clock` replaces the
+/// real one.
 #[inline]
 pub fn now_us() -> u64 {
-    Instant::now().duration_since(*T0).as_micros() as u64
+    from_raw_us(raw_now_us())
+}
+
+// µs seit T0 auf den aktuellen Ursprung umgerechnet; vor dem Ursprung 0
+#[inline]
+pub(super) fn from_raw_us(raw: u64) -> u64 {
+    raw.saturating_sub(ORIGIN_US.load(Ordering::Relaxed))
 }
 
+// µs seit T0, ohne Ursprung
+#[inline]
+pub(super) fn raw_now_us() -> u64 {
+    #[cfg(feature = "test-util")]
+    if let Some(clock) = CLOCK.read().unwrap().as_ref() {
+        return clock();
+    }
+    micros(Instant::now().saturating_duration_since(*T0))
+}
+
+/// Replacement clock for `now_us`, in µs since T0
+#[cfg(feature = "test-util")]
+pub type Clock = Box<dyn Fn() -> u64 + Send + Sync>;
+
+#[cfg(feature = "test-util")]
+static CLOCK: Lazy<std::sync::RwLock<Option<Clock>>> = Lazy::new(|| std::sync::RwLock::new(None));
+
+/// Make every trace timestamp come from `clock` (µs since T0), so tests can
+/// assert exact `t_start_us`/`idle_us` values. Process-wide; tests sharing
+/// a binary must not install different clocks concurrently.
+#[cfg(feature = "test-util")]
+pub fn set_clock(clock: Clock) {
+    *CLOCK.write().unwrap() = Some(clock);
+}
+
+/// Back to the real `Instant`-based clock
+#[cfg(feature = "test-util")]
+pub fn reset_clock() {
+    *CLOCK.write().unwrap() = None;
+}
+
+// einzige Stelle für u128 → u64: sättigt statt still abzuschneiden
+#[inline]
+fn micros(d: Duration) -> u64 {
+    let us = d.as_micros();
+    debug_assert!(us <= u64::MAX as u128, "timestamp {us} µs exceeds u64");
+    u64::try_from(us).unwrap_or(u64::MAX)
+}
+
+/// Abort cause marking a transaction abandoned after its retry budget ran out
+pub const CAUSE_BUDGET_EXHAUSTED: &str = "budget_exhausted";
+
+/// Log an abort of transaction `tx_id`. Like `trace_commit` a no-op while
+/// auto-trace is disabled, so abort rates stay consistent.
 #[cfg(feature = "memtrace")]
 pub fn trace_abort(tx_id: u64, cause: &str, retries: u32, conflict_sz: u32, abort_token: &str) {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    let t_us = now.as_micros() as u64;
+    trace_abort_between(tx_id, cause, retries, conflict_sz, abort_token, "");
+}
+
+/// Like `trace_abort`, additionally recording the token it conflicted with
+/// (empty = unknown partner)
+#[cfg(feature = "memtrace")]
+pub fn trace_abort_between(
+    tx_id: u64,
+    cause: &str,
+    retries: u32,
+    conflict_sz: u32,
+    abort_token: &str,
+    conflicting_token: &str,
+) {
+    if !AUTO_TRACE.load(Ordering::Relaxed) {
+        return;
+    }
+    let t_us = now_us();
+    // der Stream hat keinen Summary-Ursprung, daher absolute Unix-µs
+    abort_stream::emit(origin_unix_us().saturating_add(t_us), abort_token, cause, retries, conflict_sz);
     LOG.lock().unwrap().push(Record {
+        seq: next_seq(),
         t_start_us: t_us,
         t_end_us:   t_us,
         bytes: 0,
@@ -286,5 +564,15 @@ pub fn trace_abort(tx_id: u64, cause: &str, retries: u32, conflict_sz: u32, abor
         cause: Some(cause.to_string()),
         retries: Some(retries),
         conflict_sz: Some(conflict_sz as usize),
+        conflict_with: if conflicting_token.is_empty() { None } else { Some(conflicting_token.to_string()) },
+        tags: roi::roi_tags(),
+        device: current_device(),
     });
 }
+
+/// Mark transaction `tx_id` as given up after `retries` attempts
+/// (logged as an abort with cause [`CAUSE_BUDGET_EXHAUSTED`])
+#[cfg(feature = "memtrace")]
+pub fn trace_gave_up(tx_id: u64, retries: u32, abort_token: &str) {
+    trace_abort(tx_id, CAUSE_BUDGET_EXHAUSTED, retries, 0, abort_token);
+}
@@ -0,0 +1,199 @@
+#![cfg(feature = "perfetto")]
+
+//! Perfetto protobuf exporter.
+//!
+//! Encodes the few messages needed (`Trace`, `TracePacket`,
+//! `TrackDescriptor`, `TrackEvent`, `DebugAnnotation`) by hand in the
+//! protobuf wire format, so no protobuf dependency or build step is needed.
+//! Field numbers follow perfetto's `protos/perfetto/trace/`.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use super::{LOG, Dir, Phase, Record};
+
+// Sequenz-ID aller Pakete dieses Exporters
+const SEQ_ID: u64 = 1;
+// Track-UUIDs: Prozesse, Richtungs-Lanes, Abort-Lanes ab ABORT_LANE_BASE
+const TRANSFER_PROCESS: u64 = 1;
+const ABORT_PROCESS: u64 = 2;
+const ABORT_LANE_BASE: u64 = 100;
+
+// TrackEvent.Type
+const SLICE_BEGIN: u64 = 1;
+const SLICE_END: u64 = 2;
+const INSTANT: u64 = 3;
+
+/// Write the log as a Perfetto protobuf trace (open in ui.perfetto.dev).
+///
+/// Same layout as `flush_chrome_trace`: transfers and kernels are slices on
+/// one track per direction, aborts instant events on one track per abort
+/// token with `tx_id`, `retries` and `conflict_sz` as annotations. Packets
+/// are streamed, so multi-million-record logs need no intermediate
+/// document in memory.
+pub fn flush_perfetto<P: AsRef<Path>>(path: P) -> crate::Result<()> {
+    let log = LOG.lock().unwrap();
+    let mut w = PerfettoWriter::new(BufWriter::new(File::create(path)?))?;
+    for r in log.iter() {
+        w.add(r)?;
+    }
+    w.finish()?;
+    Ok(())
+}
+
+/// Streaming encoder: track descriptors up front, then packets per record
+pub(super) struct PerfettoWriter<'a, W: Write> {
+    out: W,
+    packet: Vec<u8>,
+    abort_lanes: Vec<&'a str>,
+}
+
+impl<'a, W: Write> PerfettoWriter<'a, W> {
+    pub(super) fn new(mut out: W) -> std::io::Result<Self> {
+        let mut packet = Vec::with_capacity(256);
+        write_packet(&mut out, &mut packet, |p| process_track(p, TRANSFER_PROCESS, "hpc-core transfers"))?;
+        write_packet(&mut out, &mut packet, |p| process_track(p, ABORT_PROCESS, "hpc-core aborts"))?;
+        for dir in [Dir::H2D, Dir::D2H, Dir::Kernel] {
+            write_packet(&mut out, &mut packet, |p| {
+                child_track(p, dir_track(dir), TRANSFER_PROCESS, dir.as_str())
+            })?;
+        }
+        Ok(PerfettoWriter { out, packet, abort_lanes: Vec::new() })
+    }
+
+    pub(super) fn add(&mut self, r: &'a Record) -> std::io::Result<()> {
+        let (out, packet) = (&mut self.out, &mut self.packet);
+        if matches!(r.phase, Phase::Abort) {
+            let token = r.abort_token.as_deref().unwrap_or("");
+            let lane = match self.abort_lanes.iter().position(|t| *t == token) {
+                Some(i) => i,
+                None => {
+                    self.abort_lanes.push(token);
+                    let uuid = ABORT_LANE_BASE + self.abort_lanes.len() as u64 - 1;
+                    write_packet(out, packet, |p| child_track(p, uuid, ABORT_PROCESS, token))?;
+                    self.abort_lanes.len() - 1
+                }
+            };
+            write_packet(out, packet, |p| {
+                event(p, r.t_start_us, INSTANT, ABORT_LANE_BASE + lane as u64, Some(r.cause.as_deref().unwrap_or("abort")), r)
+            })
+        } else {
+            let track = dir_track(r.dir);
+            write_packet(out, packet, |p| {
+                event(p, r.t_start_us, SLICE_BEGIN, track, Some(r.phase.as_str()), r)
+            })?;
+            write_packet(out, packet, |p| event(p, r.t_end_us, SLICE_END, track, None, r))
+        }
+    }
+
+    pub(super) fn finish(mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn dir_track(dir: Dir) -> u64 {
+    match dir { Dir::H2D => 10, Dir::D2H => 11, Dir::Kernel => 12 }
+}
+
+// Trace.packet (Feld 1) aus dem von `fill` gebauten TracePacket
+fn write_packet<W: Write>(
+    out: &mut W,
+    buf: &mut Vec<u8>,
+    fill: impl FnOnce(&mut Vec<u8>),
+) -> std::io::Result<()> {
+    buf.clear();
+    uint(buf, 10, SEQ_ID); // trusted_packet_sequence_id
+    fill(buf);
+    let mut head = Vec::with_capacity(11);
+    key(&mut head, 1, 2);
+    varint(&mut head, buf.len() as u64);
+    out.write_all(&head)?;
+    out.write_all(buf)
+}
+
+fn process_track(p: &mut Vec<u8>, uuid: u64, name: &str) {
+    let mut process = Vec::new();
+    uint(&mut process, 1, uuid); // pid
+    string(&mut process, 6, name); // process_name
+
+    let mut desc = Vec::new();
+    uint(&mut desc, 1, uuid);
+    bytes(&mut desc, 3, &process);
+    bytes(p, 60, &desc); // track_descriptor
+}
+
+fn child_track(p: &mut Vec<u8>, uuid: u64, parent: u64, name: &str) {
+    let mut desc = Vec::new();
+    uint(&mut desc, 1, uuid);
+    string(&mut desc, 2, name);
+    uint(&mut desc, 5, parent); // parent_uuid
+    bytes(p, 60, &desc);
+}
+
+fn event(p: &mut Vec<u8>, ts_us: u64, kind: u64, track: u64, name: Option<&str>, r: &Record) {
+    uint(p, 8, ts_us.saturating_mul(1000)); // timestamp (ns)
+
+    let mut ev = Vec::new();
+    uint(&mut ev, 9, kind);
+    uint(&mut ev, 11, track);
+    if let Some(name) = name {
+        string(&mut ev, 23, name);
+        annotation(&mut ev, "bytes", r.bytes as u64);
+        if let Some(tx) = r.tx_id {
+            annotation(&mut ev, "tx_id", tx);
+        }
+        if let Some(retries) = r.retries {
+            annotation(&mut ev, "retries", retries as u64);
+        }
+        if let Some(sz) = r.conflict_sz {
+            annotation(&mut ev, "conflict_sz", sz as u64);
+        }
+        for (k, v) in &r.tags {
+            let mut a = Vec::new();
+            string(&mut a, 10, k);
+            string(&mut a, 6, v); // string_value
+            bytes(&mut ev, 4, &a);
+        }
+    }
+    bytes(p, 11, &ev); // track_event
+}
+
+// DebugAnnotation mit name (10) und uint_value (3)
+fn annotation(ev: &mut Vec<u8>, name: &str, value: u64) {
+    let mut a = Vec::new();
+    string(&mut a, 10, name);
+    uint(&mut a, 3, value);
+    bytes(ev, 4, &a);
+}
+
+// ---- Protobuf-Wire-Format ----
+
+fn varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+fn key(buf: &mut Vec<u8>, field: u32, wire_type: u8) {
+    varint(buf, ((field as u64) << 3) | wire_type as u64);
+}
+
+fn uint(buf: &mut Vec<u8>, field: u32, v: u64) {
+    key(buf, field, 0);
+    varint(buf, v);
+}
+
+fn bytes(buf: &mut Vec<u8>, field: u32, data: &[u8]) {
+    key(buf, field, 2);
+    varint(buf, data.len() as u64);
+    buf.extend_from_slice(data);
+}
+
+fn string(buf: &mut Vec<u8>, field: u32, s: &str) {
+    bytes(buf, field, s.as_bytes());
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "memtrace")]
+
+//! Regions of interest: records logged between `roi_begin` and `roi_end`
+//! carry the region name as tag [`ROI_TAG`], so a flush can keep only them
+//! (`FlushConfig::roi_only`)
+
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+/// Tag key holding the innermost enclosing region
+pub const ROI_TAG: &str = "roi";
+
+thread_local! {
+    // offene Regionen, innerste zuletzt; je Thread wie CURRENT_DEVICE
+    static ROI_STACK: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Open region `name` (e.g. "solve") on this thread; regions nest, records
+/// get the name of the innermost one. Records logged from other threads
+/// are not tagged, open a region there as well.
+pub fn roi_begin(name: &str) {
+    ROI_STACK.with_borrow_mut(|stack| stack.push(name.to_string()));
+}
+
+/// Close the innermost open region of this thread
+pub fn roi_end() {
+    if ROI_STACK.with_borrow_mut(Vec::pop).is_none() {
+        diag_warn!("memtrace: roi_end() without open region");
+    }
+}
+
+/// Name of the innermost open region of this thread
+pub fn current_roi() -> Option<String> {
+    ROI_STACK.with_borrow(|stack| stack.last().cloned())
+}
+
+/// RAII region: `roi_begin` on creation, `roi_end` on drop. Not `Send`,
+/// since it has to close the region on the thread that opened it.
+pub struct RoiGuard(PhantomData<*const ()>);
+
+impl RoiGuard {
+    pub fn new(name: &str) -> Self {
+        roi_begin(name);
+        RoiGuard(PhantomData)
+    }
+}
+
+impl Drop for RoiGuard {
+    fn drop(&mut self) {
+        roi_end();
+    }
+}
+
+// Tags eines neuen Records: ("roi", Name) innerhalb einer Region, sonst leer
+pub(super) fn roi_tags() -> Vec<(String, String)> {
+    current_roi().map(|name| vec![(ROI_TAG.to_string(), name)]).unwrap_or_default()
+}
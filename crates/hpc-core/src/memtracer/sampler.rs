@@ -0,0 +1,103 @@
+#![cfg(feature = "memtrace")]
+
+//! Background sampler: a coarse resource time series next to the event trace
+
+use once_cell::sync::Lazy;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use super::{log_len, now_us};
+
+struct Sampler {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<io::Result<()>>,
+}
+
+// höchstens ein Sampler pro Prozess
+static SAMPLER: Lazy<Mutex<Option<Sampler>>> = Lazy::new(|| Mutex::new(None));
+
+/// Start a background thread that every `interval` appends one row
+/// `t_us,inflight_buffers,log_len,alloc_bytes` to the CSV at `path`. The
+/// first row is written right away and the last one when stopped, so the
+/// final row always reflects the state at `stop_sampler`.
+///
+/// `inflight_buffers` needs feature `buffer_registry` and `alloc_bytes`
+/// feature `metrics`; without them the column stays empty. Rows are
+/// flushed as they are written, so an unclean exit loses at most the
+/// current one. Stopped by `stop_sampler`, `flush_csv`/`flush_csv_to` and
+/// `flush_all`; a running sampler is stopped before a new one starts.
+///
+/// Nothing stops the thread at program exit (statics are not dropped):
+/// call `stop_sampler` or one of the flushes before `main` returns to get
+/// the final row; otherwise the exit just ends the thread.
+pub fn start_sampler<P: AsRef<Path>>(interval: Duration, path: P) -> crate::Result<()> {
+    // Lock über Stop, Spawn und Eintragen: parallele Starts lassen sonst
+    // einen Thread zurück, den niemand mehr stoppen kann
+    let mut slot = SAMPLER.lock().unwrap();
+    if let Some(s) = slot.take() {
+        s.join()?;
+    }
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "t_us,inflight_buffers,log_len,alloc_bytes")?;
+    out.flush()?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&stop);
+    let handle = thread::Builder::new().name("memtrace-sampler".into()).spawn(move || {
+        loop {
+            write_sample(&mut out)?;
+            if flag.load(Ordering::Relaxed) {
+                return out.flush();
+            }
+            // park statt sleep: stop_sampler weckt sofort auf
+            thread::park_timeout(interval);
+        }
+    })?;
+    *slot = Some(Sampler { stop, handle });
+    Ok(())
+}
+
+/// Stop the sampler (if one runs) and wait for its last row; returns the
+/// write error that ended it early, if any
+pub fn stop_sampler() -> crate::Result<()> {
+    let Some(s) = SAMPLER.lock().unwrap().take() else {
+        return Ok(());
+    };
+    s.join()
+}
+
+impl Sampler {
+    fn join(self) -> crate::Result<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.thread().unpark();
+        match self.handle.join() {
+            Ok(res) => Ok(res?),
+            Err(_) => Err(io::Error::other("memtrace sampler thread panicked").into()),
+        }
+    }
+}
+
+fn write_sample<W: Write>(out: &mut W) -> io::Result<()> {
+    #[cfg(feature = "buffer_registry")]
+    use crate::buffer::State;
+    #[cfg(feature = "buffer_registry")]
+    let inflight = crate::buffer::count_in_state(crate::buffer::InFlight::NAME).to_string();
+    #[cfg(not(feature = "buffer_registry"))]
+    let inflight = "";
+    #[cfg(feature = "metrics")]
+    let alloc = crate::metrics::ALLOC_BYTES.load(Ordering::Relaxed).to_string();
+    #[cfg(not(feature = "metrics"))]
+    let alloc = "";
+
+    writeln!(out, "{},{},{},{}", now_us(), inflight, log_len(), alloc)?;
+    out.flush()
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "json")]
+
+//! Baseline snapshots for latency regression gates
+
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::{BufReader, BufWriter}, path::Path};
+
+use super::snapshot;
+
+/// Stored per-op latency of a baseline run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BaselineOp {
+    name: String,
+    count: usize,
+    mean_us: u128,
+    p95_us: u128,
+}
+
+/// An op whose latency exceeded its baseline by more than the tolerance
+#[derive(Debug, Clone)]
+pub struct Regression {
+    pub op: String,
+    /// `"mean_us"` or `"p95_us"`
+    pub metric: &'static str,
+    pub baseline_us: u128,
+    pub current_us: u128,
+}
+
+impl Regression {
+    /// `current / baseline`
+    pub fn ratio(&self) -> f64 {
+        self.current_us as f64 / self.baseline_us as f64
+    }
+}
+
+/// Write the current `snapshot()` latencies to `path` as JSON
+pub fn save_baseline<P: AsRef<Path>>(path: P) -> crate::Result<()> {
+    let ops: Vec<BaselineOp> = snapshot()
+        .ops
+        .into_iter()
+        .map(|o| BaselineOp { name: o.name.to_owned(), count: o.count, mean_us: o.mean_us, p95_us: o.p95_us })
+        .collect();
+    let f = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(f, &ops).map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+/// Compare the current `snapshot()` against a baseline written by
+/// `save_baseline`. An op regresses when its mean or p95 exceeds the
+/// baseline by more than `tolerance` (0.1 = 10 %). Ops missing on either
+/// side and zero baselines are skipped.
+pub fn compare_baseline<P: AsRef<Path>>(path: P, tolerance: f64) -> crate::Result<Vec<Regression>> {
+    let f = BufReader::new(File::open(path)?);
+    let baseline: Vec<BaselineOp> = serde_json::from_reader(f).map_err(std::io::Error::from)?;
+
+    let mut out = Vec::new();
+    for cur in snapshot().ops {
+        let Some(base) = baseline.iter().find(|b| b.name == cur.name) else {
+            continue;
+        };
+        for (metric, b, c) in [("mean_us", base.mean_us, cur.mean_us), ("p95_us", base.p95_us, cur.p95_us)] {
+            if b > 0 && c as f64 > b as f64 * (1.0 + tolerance) {
+                out.push(Regression { op: cur.name.to_owned(), metric, baseline_us: b, current_us: c });
+            }
+        }
+    }
+    Ok(out)
+}
@@ -1,24 +1,259 @@
 #![cfg(feature = "metrics")]
 
-use once_cell::sync::Lazy;
+mod recorder;
+mod baseline;
+
+pub use recorder::{
+    record, record_bytes, record_duration, record_retries, TIMES, BYTES, RETRIES,
+    enable, disable, is_enabled, MetricsScope, METRICS_ENABLED,
+    only,
+};
+pub(crate) use recorder::record_bytes_on_complete;
+#[cfg(feature = "json")]
+pub use baseline::{save_baseline, compare_baseline, Regression};
+
 use std::{
-    sync::Mutex,
-    time::Instant,
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        RwLock,
+    },
 };
 
-/// Global timing records
-pub static TIMES: Lazy<Mutex<Vec<(&'static str, u128)>>> =
-    Lazy::new(|| Mutex::new(Vec::with_capacity(1024)));
+use once_cell::sync::Lazy;
+
+// Buffer‑Allokationen
+
+pub static ALLOCS:      AtomicUsize = AtomicUsize::new(0);
+pub static ALLOC_BYTES: AtomicUsize = AtomicUsize::new(0);
 
-/// Record timing for an operation
+/// Allokation zählen (no-op wenn Metrics zur Laufzeit deaktiviert)
 #[inline]
-pub fn record(name: &'static str, start: Instant) {
-    let dur = start.elapsed().as_micros();
-    TIMES.lock().unwrap().push((name, dur));
+pub fn record_alloc(bytes: usize) {
+    if !is_enabled() {
+        return;
+    }
+    ALLOCS.fetch_add(1, Ordering::Relaxed);
+    ALLOC_BYTES.fetch_add(bytes, Ordering::Relaxed);
 }
 
-/// Record timing with explicit duration
+// Fehlgeschlagene Operationen
+
+/// Failed calls per op name: OpenCL errors of wrapped operations
+/// (`enqueue_write`, `kernel_launch`, ...); counted regardless of the
+/// `only` allowlist, drained by `summary()`
+pub static FAILURES: Lazy<RwLock<HashMap<&'static str, AtomicU64>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Fehlschlag zählen (no-op wenn Metrics zur Laufzeit deaktiviert)
 #[inline]
-pub fn record_duration(name: &'static str, duration_us: u128) {
-    TIMES.lock().unwrap().push((name, duration_us));
+pub fn record_failure(name: &'static str) {
+    if !is_enabled() {
+        return;
+    }
+    // schneller Pfad: Op schon bekannt, nur Lese-Lock + relaxed Inkrement
+    if let Some(n) = FAILURES.read().unwrap().get(name) {
+        n.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    FAILURES.write().unwrap().entry(name).or_default().fetch_add(1, Ordering::Relaxed);
+}
+
+/// Failure counts by op name, sorted by name (without draining)
+pub fn failures() -> Vec<(&'static str, u64)> {
+    let mut out: Vec<_> = FAILURES
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(name, n)| (*name, n.load(Ordering::Relaxed)))
+        .collect();
+    out.sort_unstable();
+    out
+}
+
+/// Latenz-Statistik einer Operation
+#[derive(Debug, Clone)]
+pub struct OpStats {
+    pub name: &'static str,
+    pub count: usize,
+    pub mean_us: u128,
+    pub p95_us: u128,
+    pub total_us: u128,
+    /// Bytes moved by samples recorded with `record_bytes` (0 = none)
+    pub bytes: u64,
+    /// Summed duration of those samples
+    pub bytes_us: u128,
+}
+
+impl OpStats {
+    fn from_samples(name: &'static str, mut v: Vec<u128>, (bytes, bytes_us): (u64, u128)) -> Self {
+        v.sort_unstable();
+        let total_us: u128 = v.iter().sum();
+        OpStats {
+            name,
+            count: v.len(),
+            mean_us: total_us / v.len() as u128,
+            p95_us: v[((v.len() * 95) / 100).saturating_sub(1)],
+            total_us,
+            bytes,
+            bytes_us,
+        }
+    }
+
+    /// Size-weighted throughput Σbytes / Σµs in GB/s (dezimal); unlike a
+    /// mean of per-call rates this matches what a profiler reports
+    pub fn throughput_gbps(&self) -> Option<f64> {
+        if self.bytes == 0 || self.bytes_us == 0 {
+            None
+        } else {
+            Some(self.bytes as f64 / self.bytes_us as f64 / 1e3)
+        }
+    }
+}
+
+/// Momentaufnahme aller Latenzen (ohne `TIMES` zu leeren), nach Name sortiert
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub ops: Vec<OpStats>,
+    pub allocs: usize,
+    pub alloc_bytes: usize,
+    /// See `failures()`
+    pub failures: Vec<(&'static str, u64)>,
+}
+
+pub fn snapshot() -> MetricsSnapshot {
+    let mut map: HashMap<&'static str, Vec<u128>> = HashMap::new();
+    for &(name, us) in TIMES.lock().unwrap().iter() {
+        map.entry(name).or_default().push(us);
+    }
+    let bytes = BYTES.lock().unwrap();
+    let mut ops: Vec<OpStats> = map
+        .into_iter()
+        .map(|(name, v)| OpStats::from_samples(name, v, bytes.get(name).copied().unwrap_or_default()))
+        .collect();
+    ops.sort_by_key(|o| o.name);
+
+    MetricsSnapshot {
+        ops,
+        allocs: ALLOCS.load(Ordering::Relaxed),
+        alloc_bytes: ALLOC_BYTES.load(Ordering::Relaxed),
+        failures: failures(),
+    }
+}
+
+/// Write every recorded sample as `op,sample_index,latency_us` in recording
+/// order (`sample_index` counts per op). Does not drain `TIMES`, so
+/// `summary()` still works afterwards. Byte volumes from `record_bytes` are
+/// only kept as per-op totals and are not part of this file.
+pub fn dump_samples_csv<P: AsRef<Path>>(path: P) -> crate::Result<()> {
+    let mut f = BufWriter::new(File::create(path)?);
+    writeln!(f, "op,sample_index,latency_us")?;
+    let mut next_index: HashMap<&'static str, usize> = HashMap::new();
+    for &(name, us) in TIMES.lock().unwrap().iter() {
+        let idx = next_index.entry(name).or_insert(0);
+        writeln!(f, "{},{},{}", name, idx, us)?;
+        *idx += 1;
+    }
+    f.flush()?;
+    Ok(())
+}
+
+/// Sort key of the op list in `summary_by`; every key sorts descending
+/// (largest first), ties by name, so the output is deterministic
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortBy {
+    #[default]
+    Mean,
+    P95,
+    Count,
+    Total,
+}
+
+impl SortBy {
+    fn sort(self, ops: &mut [OpStats]) {
+        ops.sort_by(|a, b| {
+            let (ka, kb) = match self {
+                SortBy::Mean => (a.mean_us, b.mean_us),
+                SortBy::P95 => (a.p95_us, b.p95_us),
+                SortBy::Count => (a.count as u128, b.count as u128),
+                SortBy::Total => (a.total_us, b.total_us),
+            };
+            kb.cmp(&ka).then(a.name.cmp(b.name))
+        });
+    }
+}
+
+/// Print the metrics report to stdout and drain `TIMES` (and `RETRIES`); slowest op (by
+/// mean latency) first
+pub fn summary() {
+    summary_by(SortBy::Mean);
+}
+
+/// Like `summary`, with the op list sorted by `key`
+pub fn summary_by(key: SortBy) {
+    summary_to_by(&mut std::io::stdout().lock(), key).unwrap();
+}
+
+/// Write the report of `summary()` to any sink (log buffer, file, ...)
+pub fn summary_to<W: Write>(w: &mut W) -> std::io::Result<()> {
+    summary_to_by(w, SortBy::Mean)
+}
+
+/// Like `summary_to`, with the op list sorted by `key`
+pub fn summary_to_by<W: Write>(w: &mut W, key: SortBy) -> std::io::Result<()> {
+    // API‑Latenzen hrouping
+    let mut map: HashMap<&'static str, Vec<u128>> = HashMap::new();
+    {
+        let mut times = TIMES.lock().unwrap();
+        for (name, us) in times.drain(..) {
+            map.entry(name).or_default().push(us);
+        }
+    }
+    let bytes: HashMap<&'static str, (u64, u128)> = BYTES.lock().unwrap().drain().collect();
+    let mut ops: Vec<OpStats> = map
+        .into_iter()
+        .map(|(name, v)| OpStats::from_samples(name, v, bytes.get(name).copied().unwrap_or_default()))
+        .collect();
+    key.sort(&mut ops);
+
+    writeln!(w, "── metrics summary ──")?;
+    for st in &ops {
+        writeln!(w, "{:<18} mean={:>5} µs   p95={:>5} µs", st.name, st.mean_us, st.p95_us)?;
+
+        // Latenz oben ist pro Aufruf; Durchsatz gewichtet nach Größe
+        if st.throughput_gbps().is_some() {
+            writeln!(w, "    ↳ throughput (Σbytes/Σµs) = {}", crate::format_rate(st.bytes, st.bytes_us))?;
+        }
+    }
+
+    // Retries pro Commit: Anzahlen statt µs, Mittel meist < 1
+    let mut retries = std::mem::take(&mut *RETRIES.lock().unwrap());
+    if !retries.is_empty() {
+        retries.sort_unstable();
+        let mean = retries.iter().map(|&r| r as f64).sum::<f64>() / retries.len() as f64;
+        let p95 = retries[((retries.len() * 95) / 100).saturating_sub(1)];
+        writeln!(w, "{:<18} mean={:>5.2} retries/commit   p95={:>3}", crate::stm::STM_RETRY, mean, p95)?;
+    }
+
+    /* Allokations‑Zähler */
+    let allocs = ALLOCS.load(Ordering::Relaxed);
+    let bytes  = ALLOC_BYTES.load(Ordering::Relaxed);
+    writeln!(w, "GPU allocations: {}   ({})", allocs, crate::format_bytes(bytes as u64))?;
+
+    // Fehlschläge je Op; Erfolgsquote relativ zu den erfolgreichen Samples
+    let mut failed: Vec<(&'static str, u64)> = FAILURES
+        .write()
+        .unwrap()
+        .drain()
+        .map(|(name, n)| (name, n.into_inner()))
+        .collect();
+    failed.sort_unstable();
+    for (name, n) in failed {
+        let ok = ops.iter().find(|o| o.name == name).map_or(0, |o| o.count as u64);
+        writeln!(w, "failed {:<18} {:>6}   ({} ok)", name, n, ok)?;
+    }
+    Ok(())
 }
@@ -2,7 +2,11 @@
 
 use once_cell::sync::Lazy;
 use std::{
-    sync::Mutex,
+    collections::HashMap,
+    sync::{
+        Mutex, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
     time::Instant,
 };
 
@@ -10,15 +14,162 @@ use std::{
 pub static TIMES: Lazy<Mutex<Vec<(&'static str, u128)>>> =
     Lazy::new(|| Mutex::new(Vec::with_capacity(1024)));
 
+/// Per-op totals of samples recorded via `record_bytes`: (Σ bytes, Σ µs)
+pub static BYTES: Lazy<Mutex<HashMap<&'static str, (u64, u128)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Retries per committed STM transaction (`AbortCounters::commit_after`);
+/// counts, not µs, so they are kept apart from `TIMES`
+pub static RETRIES: Lazy<Mutex<Vec<u32>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Runtime enable flag (mirrors `memtracer::AUTO_TRACE`)
+pub static METRICS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Enable metrics recording
+#[inline]
+pub fn enable() {
+    METRICS_ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Disable metrics recording; `record()` becomes a relaxed load + return
+#[inline]
+pub fn disable() {
+    METRICS_ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Check if metrics recording is enabled
+#[inline]
+pub fn is_enabled() -> bool {
+    METRICS_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Op-name allowlist; empty = record everything
+static ALLOWLIST: Lazy<RwLock<Vec<&'static str>>> = Lazy::new(|| RwLock::new(Vec::new()));
+/// Fast path: skip the allowlist lock while no allowlist is set
+static HAS_ALLOWLIST: AtomicBool = AtomicBool::new(false);
+
+/// Only record the given op names from now on; an empty slice records everything
+pub fn only(names: &[&'static str]) {
+    let mut list = ALLOWLIST.write().unwrap();
+    list.clear();
+    list.extend_from_slice(names);
+    HAS_ALLOWLIST.store(!list.is_empty(), Ordering::Relaxed);
+}
+
+#[inline]
+fn wanted(name: &str) -> bool {
+    is_enabled()
+        && (!HAS_ALLOWLIST.load(Ordering::Relaxed)
+            || ALLOWLIST.read().unwrap().contains(&name))
+}
+
 /// Record timing for an operation
 #[inline]
 pub fn record(name: &'static str, start: Instant) {
+    if !wanted(name) {
+        return;
+    }
     let dur = start.elapsed().as_micros();
     TIMES.lock().unwrap().push((name, dur));
 }
 
+/// Record timing for an operation that moved `bytes`; feeds the latency
+/// stats like `record` plus the size-weighted throughput of `name`.
+/// Only meaningful for blocking calls, where `start..now` spans the copy.
+#[inline]
+pub fn record_bytes(name: &'static str, start: Instant, bytes: usize) {
+    if !wanted(name) {
+        return;
+    }
+    let dur = start.elapsed().as_micros();
+    TIMES.lock().unwrap().push((name, dur));
+    let mut totals = BYTES.lock().unwrap();
+    let entry = totals.entry(name).or_insert((0, 0));
+    entry.0 += bytes as u64;
+    entry.1 += dur;
+}
+
+/// `record_bytes` for a non-blocking transfer: the sample is taken when
+/// `evt` completes, so it spans enqueue to completion. If the callback
+/// cannot be registered the sample is recorded immediately instead.
+pub(crate) fn record_bytes_on_complete(
+    evt: &opencl3::event::Event,
+    name: &'static str,
+    start: Instant,
+    bytes: usize,
+) {
+    struct Pending {
+        name: &'static str,
+        start: Instant,
+        bytes: usize,
+    }
+
+    extern "C" fn on_complete(
+        _evt: opencl3::types::cl_event,
+        _status: opencl3::types::cl_int,
+        user_data: *mut std::ffi::c_void,
+    ) {
+        // SAFETY: Pointer was obtained via Box::into_raw below and is consumed once.
+        let p: Box<Pending> = unsafe { Box::from_raw(user_data.cast()) };
+        record_bytes(p.name, p.start, p.bytes);
+    }
+
+    if !wanted(name) {
+        return;
+    }
+    let ptr = Box::into_raw(Box::new(Pending { name, start, bytes })).cast::<std::ffi::c_void>();
+    if evt.set_callback(opencl3::event::CL_COMPLETE, on_complete, ptr).is_err() {
+        // SAFETY: the callback was not registered, so `ptr` is still ours
+        let p: Box<Pending> = unsafe { Box::from_raw(ptr.cast()) };
+        record_bytes(p.name, p.start, p.bytes);
+    }
+}
+
 /// Record timing with explicit duration
 #[inline]
 pub fn record_duration(name: &'static str, duration_us: u128) {
+    if !wanted(name) {
+        return;
+    }
     TIMES.lock().unwrap().push((name, duration_us));
-}
\ No newline at end of file
+}
+
+/// Record the retries of one committed transaction under op `STM_RETRY`
+/// (allowlist via `only` like any op)
+#[inline]
+pub fn record_retries(retries: u32) {
+    if !wanted(crate::stm::STM_RETRY) {
+        return;
+    }
+    RETRIES.lock().unwrap().push(retries);
+}
+
+/// RAII scope for temporarily changing metrics state
+#[derive(Debug)]
+pub struct MetricsScope {
+    prev: bool,
+}
+
+impl MetricsScope {
+    #[inline]
+    pub fn new(enable: bool) -> Self {
+        let prev = METRICS_ENABLED.swap(enable, Ordering::Relaxed);
+        MetricsScope { prev }
+    }
+
+    #[inline]
+    pub fn enabled() -> Self {
+        Self::new(true)
+    }
+
+    #[inline]
+    pub fn disabled() -> Self {
+        Self::new(false)
+    }
+}
+
+impl Drop for MetricsScope {
+    fn drop(&mut self) {
+        METRICS_ENABLED.store(self.prev, Ordering::Relaxed);
+    }
+}
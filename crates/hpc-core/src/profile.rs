@@ -0,0 +1,177 @@
+//! Link characterization: transfer time vs. size, fitted to latency + bytes / bandwidth
+
+use opencl3::{command_queue::CommandQueue, context::Context};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::Instant,
+};
+
+use crate::buffer::{GpuBuffer, Ready};
+use crate::error::Result;
+
+// Wiederholungen je Größe; das Minimum filtert Ausreißer (Scheduler, Caches)
+const REPS: usize = 5;
+
+/// Fixed overhead and asymptotic bandwidth of one transfer direction,
+/// the least-squares fit of `t(n) = latency_us + n / bandwidth`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinkFit {
+    /// Per-transfer overhead in µs (intercept, clamped to 0)
+    pub latency_us: f64,
+    /// Asymptotic bandwidth in GB/s (decimal, 1/slope)
+    pub bandwidth_gbps: f64,
+}
+
+impl LinkFit {
+    /// Fit `(bytes, µs)` samples; `None` with fewer than two distinct sizes
+    /// or if time does not grow with size
+    pub fn fit(samples: &[(usize, f64)]) -> Option<LinkFit> {
+        let n = samples.len() as f64;
+        let mean_x = samples.iter().map(|&(b, _)| b as f64).sum::<f64>() / n;
+        let mean_y = samples.iter().map(|&(_, t)| t).sum::<f64>() / n;
+        let (mut cov, mut var) = (0.0, 0.0);
+        for &(b, t) in samples {
+            let dx = b as f64 - mean_x;
+            cov += dx * (t - mean_y);
+            var += dx * dx;
+        }
+        if var == 0.0 || cov <= 0.0 {
+            return None;
+        }
+        // Steigung in µs/Byte → Bytes/µs = 1e-3 GB/s
+        let slope = cov / var;
+        Some(LinkFit {
+            latency_us: (mean_y - slope * mean_x).max(0.0),
+            bandwidth_gbps: 1e-3 / slope,
+        })
+    }
+
+    /// Transfer size that reaches half the asymptotic bandwidth
+    /// (`latency × bandwidth`); batch smaller transfers up to at least this
+    pub fn half_bandwidth_bytes(&self) -> usize {
+        (self.latency_us * self.bandwidth_gbps * 1e3) as usize
+    }
+}
+
+/// Best-of time per size and direction
+#[derive(Clone, Copy, Debug)]
+pub struct TransferSample {
+    pub bytes: usize,
+    pub h2d_us: f64,
+    pub d2h_us: f64,
+}
+
+/// Result of `transfer_profile`
+#[derive(Clone, Debug)]
+pub struct TransferProfile {
+    pub samples: Vec<TransferSample>,
+    pub h2d: Option<LinkFit>,
+    pub d2h: Option<LinkFit>,
+}
+
+/// Time blocking H2D and D2H transfers of each size in `sizes` (best of
+/// several round trips through one `GpuBuffer`, after a warm-up) and fit
+/// latency + bytes / bandwidth per direction.
+///
+/// Small transfers are dominated by the fixed overhead, large ones by the
+/// bandwidth; `half_bandwidth_bytes` tells where the crossover is. Use
+/// sizes spanning a few KiB to tens of MiB. Host memory is pageable `Vec`
+/// storage, so the bandwidth is what ordinary transfers get, not the
+/// pinned peak. With `memtrace` the transfers are traced like any others.
+/// Sizes of 0 have nothing to time and are skipped.
+pub fn transfer_profile(ctx: &Context, queue: &CommandQueue, sizes: &[usize]) -> Result<TransferProfile> {
+    let mut samples = Vec::with_capacity(sizes.len());
+    // 0 Bytes: kein Puffer anlegbar, sonst bräche der ganze Lauf ab
+    for &bytes in sizes.iter().filter(|&&b| b > 0) {
+        let host = vec![0xA5u8; bytes];
+        let mut back = vec![0u8; bytes];
+        let mut buf = GpuBuffer::<Ready>::from_slice(ctx, queue, &host)?;
+        let (mut h2d_us, mut d2h_us) = (f64::INFINITY, f64::INFINITY);
+
+        // Durchlauf 0 wärmt Treiber und Caches auf und zählt nicht
+        for rep in 0..=REPS {
+            let t = Instant::now();
+            buf.enqueue_write_exclusive(queue, &host)?.wait()?;
+            let up = t.elapsed().as_secs_f64() * 1e6;
+
+            let t = Instant::now();
+            buf.enqueue_read_shared(queue, &mut back)?.wait()?;
+            let down = t.elapsed().as_secs_f64() * 1e6;
+
+            if rep > 0 {
+                h2d_us = h2d_us.min(up);
+                d2h_us = d2h_us.min(down);
+            }
+        }
+        samples.push(TransferSample { bytes, h2d_us, d2h_us });
+    }
+
+    let h2d = LinkFit::fit(&samples.iter().map(|s| (s.bytes, s.h2d_us)).collect::<Vec<_>>());
+    let d2h = LinkFit::fit(&samples.iter().map(|s| (s.bytes, s.d2h_us)).collect::<Vec<_>>());
+    Ok(TransferProfile { samples, h2d, d2h })
+}
+
+impl TransferProfile {
+    /// Print the samples and both fits as a table to stdout
+    pub fn print(&self) {
+        self.print_to(&mut std::io::stdout().lock()).unwrap();
+    }
+
+    /// Write the table of `print()` to any sink (log buffer, file, ...)
+    pub fn print_to<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        let (giga, giga_name) = crate::current_unit().giga();
+        let rate = |bytes: usize, us: f64| {
+            if us > 0.0 { format!("{:.2}", bytes as f64 / (us * 1e-6) / giga) } else { "-".into() }
+        };
+
+        writeln!(w, "── transfer profile ──")?;
+        let unit = format!("{}/s", giga_name);
+        writeln!(w, "{:>12} {:>10} {:>10} {:>10} {:>10}", "bytes", "H2D µs", "D2H µs", unit.clone() + "↑", unit + "↓")?;
+        for s in &self.samples {
+            writeln!(
+                w,
+                "{:>12} {:>10.1} {:>10.1} {:>10} {:>10}",
+                crate::format_bytes(s.bytes as u64),
+                s.h2d_us,
+                s.d2h_us,
+                rate(s.bytes, s.h2d_us),
+                rate(s.bytes, s.d2h_us),
+            )?;
+        }
+        for (dir, fit) in [("H2D", self.h2d), ("D2H", self.d2h)] {
+            match fit {
+                Some(f) => writeln!(
+                    w,
+                    "{}: latency {:.1} µs, bandwidth {:.2} {}/s, half bandwidth at {}",
+                    dir,
+                    f.latency_us,
+                    f.bandwidth_gbps * 1e9 / giga,
+                    giga_name,
+                    crate::format_bytes(f.half_bandwidth_bytes() as u64),
+                )?,
+                None => writeln!(w, "{}: no fit (need at least two sizes)", dir)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write `bytes,h2d_us,d2h_us` rows; the fits go first as
+    /// `# key=value` comment lines as in memtrace.csv
+    pub fn write_csv<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut f = BufWriter::new(File::create(path)?);
+        for (dir, fit) in [("h2d", self.h2d), ("d2h", self.d2h)] {
+            if let Some(fit) = fit {
+                writeln!(f, "# {}_latency_us={:.3}", dir, fit.latency_us)?;
+                writeln!(f, "# {}_bandwidth_gbps={:.3}", dir, fit.bandwidth_gbps)?;
+            }
+        }
+        writeln!(f, "bytes,h2d_us,d2h_us")?;
+        for s in &self.samples {
+            writeln!(f, "{},{:.3},{:.3}", s.bytes, s.h2d_us, s.d2h_us)?;
+        }
+        f.flush()?;
+        Ok(())
+    }
+}
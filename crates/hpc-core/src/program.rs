@@ -0,0 +1,259 @@
+//! Program building: cache keyed by source + build options, multi-kernel modules
+
+use opencl3::{context::Context, device::Device, program::Program};
+use std::collections::{HashMap, hash_map::Entry};
+use std::{fs, io, path::{Path, PathBuf}};
+
+use crate::error::{ClError, Result};
+use crate::kernel::SafeKernel;
+
+/// Stable 64-bit hash of kernel source and build options (FNV-1a).
+///
+/// Unlike `DefaultHasher` the value does not change between processes or
+/// toolchains, so it can tie a trace to the exact kernel that produced it.
+pub fn program_hash(source: &str, options: &str) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut h = OFFSET;
+    // 0-Byte trennt Quelle und Optionen ("ab"+"c" != "a"+"bc")
+    for b in source.bytes().chain([0]).chain(options.bytes()) {
+        h ^= b as u64;
+        h = h.wrapping_mul(PRIME);
+    }
+    h
+}
+
+/// `Program::create_and_build_from_source`, timed as `program_build` in
+/// metrics; a failed build returns the build log as `BuildFailed`
+pub(crate) fn build_from_source(ctx: &Context, source: &str, options: &str) -> Result<Program> {
+    #[cfg(feature = "metrics")]
+    let t = std::time::Instant::now();
+
+    let program = Program::create_and_build_from_source(ctx, source, options).map_err(ClError::BuildFailed)?;
+
+    #[cfg(feature = "metrics")]
+    crate::metrics::record("program_build", t);
+
+    Ok(program)
+}
+
+/// Builds each (source, options) pair once per context and hands out the
+/// cached program; a program is only valid in the context it was built for.
+///
+/// With feature `metrics`, compilations are recorded as `program_build`,
+/// loads from the disk cache as `program_build_disk` and in-memory hits as
+/// `program_build_cached`, so `summary()` shows what the cache saves.
+#[derive(Default)]
+pub struct ProgramCache {
+    // Schlüssel (cl_context, program_hash): das Programm hält seinen Kontext
+    // am Leben, die Adresse bleibt also eindeutig, solange der Eintrag lebt
+    programs: HashMap<(usize, u64), Program>,
+    hashes: Vec<u64>,
+    record_hash: bool,
+    disk_dir: Option<PathBuf>,
+}
+
+impl ProgramCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also write the program hashes as `kernel_hash` run metadata
+    /// (comma-separated in build order; no-op without `memtrace`)
+    pub fn record_hash(mut self, on: bool) -> Self {
+        self.record_hash = on;
+        self
+    }
+
+    /// Persist program binaries under `dir` so that a later process skips
+    /// compilation.
+    ///
+    /// Entries are keyed by source, build options and the vendor id, name,
+    /// OpenCL version and driver version of every device in the context,
+    /// in context order; after a driver upgrade the
+    /// key changes and the program is rebuilt. Binaries the driver rejects
+    /// are rebuilt and overwritten. Write failures only cost the next
+    /// process a rebuild and are not reported.
+    pub fn disk_cache<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.disk_dir = Some(dir.into());
+        self
+    }
+
+    /// Return the program for `source` + `options` in `ctx`, building it on
+    /// first use in that context
+    pub fn get_or_build(&mut self, ctx: &Context, source: &str, options: &str) -> Result<&Program> {
+        #[cfg(feature = "metrics")]
+        let t = std::time::Instant::now();
+
+        let hash = program_hash(source, options);
+        let key = (ctx.get() as usize, hash);
+        match self.programs.entry(key) {
+            Entry::Occupied(_) => {
+                #[cfg(feature = "metrics")]
+                crate::metrics::record("program_build_cached", t);
+            }
+            Entry::Vacant(slot) => {
+                let program = match &self.disk_dir {
+                    Some(dir) => build_cached(ctx, source, options, hash, dir)?,
+                    None => build_from_source(ctx, source, options)?,
+                };
+                slot.insert(program);
+                if self.hashes.contains(&hash) {
+                    return Ok(&self.programs[&key]);
+                }
+                self.hashes.push(hash);
+
+                #[cfg(feature = "memtrace")]
+                if self.record_hash {
+                    let joined: Vec<String> = self.hashes.iter().map(|h| format!("{:016x}", h)).collect();
+                    crate::memtracer::set_run_metadata("kernel_hash", joined.join(","));
+                }
+            }
+        }
+        Ok(&self.programs[&key])
+    }
+
+    /// Hashes of all built programs, in build order (once per source and
+    /// options, however many contexts it was built for)
+    pub fn hashes(&self) -> &[u64] {
+        &self.hashes
+    }
+
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+}
+
+// Datei-Header der Binär-Caches; danach: u32 Anzahl, je Gerät u64 Länge + Bytes
+const BINARY_MAGIC: &[u8; 4] = b"HPCB";
+
+fn build_cached(ctx: &Context, source: &str, options: &str, hash: u64, dir: &Path) -> Result<Program> {
+    let mut devices = String::new();
+    for &id in ctx.devices() {
+        let device = Device::new(id);
+        devices.push_str(&format!("{:x}", device.vendor_id()?));
+        devices.push('\0');
+        devices.push_str(&device.name()?);
+        devices.push('\0');
+        devices.push_str(&device.version()?);
+        devices.push('\0');
+        devices.push_str(&device.driver_version()?);
+        devices.push('\0');
+    }
+    let path = dir.join(format!("{:016x}.clbin", program_hash(&format!("{:016x}", hash), &devices)));
+
+    #[cfg(feature = "metrics")]
+    let t = std::time::Instant::now();
+
+    if let Some(program) = load_binaries(ctx, &path, options) {
+        #[cfg(feature = "metrics")]
+        crate::metrics::record("program_build_disk", t);
+        return Ok(program);
+    }
+    let program = build_from_source(ctx, source, options)?;
+    let _ = store_binaries(&program, &path);
+    Ok(program)
+}
+
+// None bei fehlender, beschädigter oder vom Treiber abgelehnter Datei
+fn load_binaries(ctx: &Context, path: &Path, options: &str) -> Option<Program> {
+    let data = fs::read(path).ok()?;
+    let mut rest = data.strip_prefix(BINARY_MAGIC)?;
+    let mut take = |n: usize| -> Option<&[u8]> {
+        if rest.len() < n {
+            return None;
+        }
+        let (head, tail) = rest.split_at(n);
+        rest = tail;
+        Some(head)
+    };
+
+    let count = u32::from_le_bytes(take(4)?.try_into().ok()?) as usize;
+    if count != ctx.devices().len() {
+        return None;
+    }
+    let mut binaries = Vec::with_capacity(count);
+    for _ in 0..count {
+        let len = u64::from_le_bytes(take(8)?.try_into().ok()?);
+        binaries.push(take(usize::try_from(len).ok()?)?);
+    }
+
+    let mut program = Program::create_from_binary(ctx, ctx.devices(), &binaries).ok()?;
+    program.build(ctx.devices(), options).ok()?;
+    Some(program)
+}
+
+fn store_binaries(program: &Program, path: &Path) -> io::Result<()> {
+    let binaries = program.get_binaries().map_err(|e| io::Error::other(e.to_string()))?;
+    let mut data = BINARY_MAGIC.to_vec();
+    data.extend_from_slice(&(binaries.len() as u32).to_le_bytes());
+    for bin in &binaries {
+        data.extend_from_slice(&(bin.len() as u64).to_le_bytes());
+        data.extend_from_slice(bin);
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Erst temporär schreiben, dann umbenennen: parallele Prozesse sehen nie halbe Dateien
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, data)?;
+    fs::rename(tmp, path)
+}
+
+/// A built program with several entry points; kernels are created on first
+/// request and cached. Buffers bound to a cached kernel stay borrowed for
+/// `'a`, i.e. as long as the module is used.
+pub struct ProgramModule<'a> {
+    program: Program,
+    names: Vec<String>,
+    kernels: HashMap<String, SafeKernel<'a>>,
+}
+
+impl<'a> ProgramModule<'a> {
+    /// Build `source` once; a failed build returns the build log as `BuildFailed`
+    pub fn build(ctx: &Context, source: &str, options: &str) -> Result<Self> {
+        Self::from_program(build_from_source(ctx, source, options)?)
+    }
+
+    /// Wrap an already built program
+    pub fn from_program(program: Program) -> Result<Self> {
+        let names = program
+            .get_kernel_names()?
+            .split(';')
+            .filter(|n| !n.is_empty())
+            .map(str::to_owned)
+            .collect();
+        Ok(ProgramModule { program, names, kernels: HashMap::new() })
+    }
+
+    /// Kernel `name`, created on first use; `KernelNotFound` if the program
+    /// has no such entry point
+    pub fn kernel(&mut self, name: &str) -> Result<&mut SafeKernel<'a>> {
+        if !self.kernels.contains_key(name) {
+            if !self.names.iter().any(|n| n == name) {
+                return Err(ClError::KernelNotFound {
+                    name: name.to_owned(),
+                    available: self.names.join(", "),
+                });
+            }
+            let kernel = SafeKernel::new(&self.program, name)?;
+            self.kernels.insert(name.to_owned(), kernel);
+        }
+        Ok(self.kernels.get_mut(name).unwrap())
+    }
+
+    /// Entry points defined by the program
+    pub fn kernel_names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+}
@@ -0,0 +1,38 @@
+//! Command queue queries and sync points
+
+use opencl3::{
+    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
+    event::Event,
+    types::cl_event,
+};
+
+use crate::error::{counted, ClError, Result};
+
+/// Whether `queue` was created with `CL_QUEUE_PROFILING_ENABLE`
+/// (`CL_QUEUE_PROPERTIES`); `false` if the query itself fails
+pub fn queue_has_profiling(queue: &CommandQueue) -> bool {
+    queue.properties().is_ok_and(|p| p & CL_QUEUE_PROFILING_ENABLE != 0)
+}
+
+/// `ProfilingDisabled` unless `queue` records device timestamps
+pub(crate) fn require_profiling(queue: &CommandQueue) -> Result<()> {
+    if queue_has_profiling(queue) { Ok(()) } else { Err(ClError::ProfilingDisabled) }
+}
+
+/// Sync point (`clEnqueueMarkerWithWaitList`): the returned event completes
+/// once every event in `deps` has; with empty `deps`, once every command
+/// enqueued on `queue` before it has. Unlike `finish()` the host does not
+/// block, so the marker can be a dependency of later commands (e.g.
+/// `SafeKernel::launch_with_deps`) on out-of-order queues.
+pub fn enqueue_marker(queue: &CommandQueue, deps: &[&Event]) -> Result<Event> {
+    #[cfg(feature = "metrics")]
+    let t = std::time::Instant::now();
+
+    let wait: Vec<cl_event> = deps.iter().map(|e| e.get()).collect();
+    let evt = queue.enqueue_marker_with_wait_list(&wait).map_err(counted("enqueue_marker"))?;
+
+    #[cfg(feature = "metrics")]
+    crate::metrics::record("enqueue_marker", t);
+
+    Ok(evt)
+}
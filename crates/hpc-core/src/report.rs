@@ -0,0 +1,224 @@
+//! Combined report: metrics latency joined with memtrace bytes
+//!
+//! With both features enabled, `enqueue_write`/`enqueue_read` latencies are
+//! joined with the traced H2D/D2H byte volume. With only one feature the
+//! corresponding half of the table is printed on its own.
+//!
+//! `bandwidth_efficiency` relates the traced transfer bandwidth to the
+//! theoretical link peak.
+
+use std::io::{self, Write};
+
+#[cfg(feature = "memtrace")]
+use crate::memtracer::{Dir, LOG};
+
+/// Traced bytes and busy time per direction
+#[cfg(feature = "memtrace")]
+#[derive(Debug, Default, Clone, Copy)]
+struct Traffic {
+    count: usize,
+    bytes: u64,
+    busy_us: u64,
+}
+
+#[cfg(feature = "memtrace")]
+fn traffic_by_dir() -> [(Dir, Traffic); 3] {
+    let mut out = [
+        (Dir::H2D, Traffic::default()),
+        (Dir::D2H, Traffic::default()),
+        (Dir::Kernel, Traffic::default()),
+    ];
+    for r in LOG.lock().unwrap().iter() {
+        if matches!(r.phase, crate::memtracer::Phase::Abort) {
+            continue;
+        }
+        let slot = match r.dir {
+            Dir::H2D => &mut out[0].1,
+            Dir::D2H => &mut out[1].1,
+            Dir::Kernel => &mut out[2].1,
+        };
+        slot.count += 1;
+        slot.bytes += r.bytes as u64;
+        slot.busy_us += r.duration_us();
+    }
+    out
+}
+
+/// GB/s (dezimal) aus Bytes und Mikrosekunden
+fn gbps(bytes: u64, us: u128) -> Option<f64> {
+    if bytes == 0 || us == 0 {
+        None
+    } else {
+        Some(bytes as f64 / us as f64 / 1e3)
+    }
+}
+
+/// Dezimale GB/s in die Giga-Einheit von `current_unit()` umrechnen
+fn in_unit(gbps: f64) -> f64 {
+    gbps * 1e9 / crate::current_unit().giga().0
+}
+
+fn fmt_opt<T: std::fmt::Display>(v: Option<T>) -> String {
+    v.map(|v| v.to_string()).unwrap_or_else(|| "-".into())
+}
+
+/// Print a unified latency / bytes / bandwidth table to stdout
+pub fn combined() {
+    combined_to(&mut std::io::stdout().lock()).unwrap();
+}
+
+/// Write the table of `combined()` to any sink (log buffer, file, ...)
+pub fn combined_to<W: Write>(w: &mut W) -> io::Result<()> {
+    #[cfg(feature = "memtrace")]
+    let traffic = traffic_by_dir();
+
+    writeln!(w, "── combined report ──")?;
+    writeln!(
+        w,
+        "{:<18} {:>7} {:>9} {:>9} {:>14} {:>9}",
+        "op", "count", "mean µs", "p95 µs", "bytes", format!("{}/s", crate::current_unit().giga().1)
+    )?;
+
+    #[cfg(feature = "metrics")]
+    for op in crate::metrics::snapshot().ops {
+        #[cfg(feature = "memtrace")]
+        let bytes = match op.name {
+            "enqueue_write" => Some(traffic[0].1.bytes),
+            "enqueue_read" => Some(traffic[1].1.bytes),
+            _ => None,
+        };
+        #[cfg(not(feature = "memtrace"))]
+        let bytes: Option<u64> = None;
+
+        // eigene Byte-Samples (record_bytes) haben Vorrang vor dem Trace-Join
+        let (bytes, bw) = match op.throughput_gbps() {
+            Some(g) => (Some(op.bytes), Some(g)),
+            None => (bytes, bytes.and_then(|b| gbps(b, op.total_us))),
+        };
+        writeln!(
+            w,
+            "{:<18} {:>7} {:>9} {:>9} {:>14} {:>9}",
+            op.name,
+            op.count,
+            op.mean_us,
+            op.p95_us,
+            fmt_opt(bytes),
+            fmt_opt(bw.map(|g| format!("{:.2}", in_unit(g)))),
+        )?;
+    }
+
+    #[cfg(feature = "memtrace")]
+    for (dir, t) in traffic.iter().filter(|(_, t)| t.count > 0) {
+        let mean = t.busy_us / t.count as u64;
+        let bw = gbps(t.bytes, t.busy_us as u128);
+        writeln!(
+            w,
+            "{:<18} {:>7} {:>9} {:>9} {:>14} {:>9}",
+            format!("trace:{}", dir.as_str()),
+            t.count,
+            mean,
+            "-",
+            t.bytes,
+            fmt_opt(bw.map(|g| format!("{:.2}", in_unit(g)))),
+        )?;
+    }
+    Ok(())
+}
+
+/// Theoretical host↔device peak per direction in GB/s (decimal).
+///
+/// OpenCL does not report the link speed, so this comes from the caller
+/// (e.g. `PeakBandwidth::pcie(4, 16)`) or from `for_device`'s guess.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PeakBandwidth {
+    pub h2d_gbps: f64,
+    pub d2h_gbps: f64,
+}
+
+impl PeakBandwidth {
+    /// Symmetric PCIe link after line encoding; `generation` is clamped to 1..=6
+    pub fn pcie(generation: u32, lanes: u32) -> Self {
+        // GB/s pro Lane und Richtung
+        let per_lane = match generation {
+            0 | 1 => 0.25,
+            2 => 0.5,
+            3 => 0.985,
+            4 => 1.969,
+            5 => 3.938,
+            _ => 7.563,
+        };
+        let g = per_lane * lanes as f64;
+        PeakBandwidth { h2d_gbps: g, d2h_gbps: g }
+    }
+
+    /// PCIe 3.0 x16 for discrete GPUs; `None` for unified-memory devices,
+    /// whose transfers are bounded by host DRAM instead
+    pub fn for_device(dev: &crate::device::DeviceInfo) -> Option<Self> {
+        match dev.kind {
+            crate::device::DeviceKind::Gpu if !dev.unified_memory => Some(Self::pcie(3, 16)),
+            _ => None,
+        }
+    }
+}
+
+/// Measured vs. theoretical bandwidth of one transfer direction
+#[derive(Clone, Copy, Debug)]
+pub struct Efficiency {
+    pub dir: &'static str,
+    pub measured_gbps: f64,
+    pub peak_gbps: f64,
+    /// `fraction()` is below the threshold passed to `bandwidth_efficiency`
+    pub below_threshold: bool,
+}
+
+impl Efficiency {
+    /// Measured / peak (0.0 if the peak is unknown)
+    pub fn fraction(&self) -> f64 {
+        if self.peak_gbps > 0.0 { self.measured_gbps / self.peak_gbps } else { 0.0 }
+    }
+}
+
+/// Traced H2D/D2H bandwidth as a fraction of `peak`.
+///
+/// Bandwidth is bytes over busy time of the traced transfers, so idle gaps
+/// between them do not count. Directions with a fraction below `threshold`
+/// (e.g. 0.5) are flagged; far below peak usually means pageable host
+/// memory or transfers too small to amortise the launch latency. Directions
+/// without traffic are omitted. `print_efficiency` shows the result as a
+/// table.
+#[cfg(feature = "memtrace")]
+pub fn bandwidth_efficiency(peak: PeakBandwidth, threshold: f64) -> Vec<Efficiency> {
+    let traffic = traffic_by_dir();
+    let mut out = Vec::with_capacity(2);
+    for ((dir, t), peak_gbps) in traffic.iter().zip([peak.h2d_gbps, peak.d2h_gbps]) {
+        let Some(measured_gbps) = gbps(t.bytes, t.busy_us as u128) else { continue };
+        let mut e = Efficiency { dir: dir.as_str(), measured_gbps, peak_gbps, below_threshold: false };
+        e.below_threshold = e.fraction() < threshold;
+        out.push(e);
+    }
+    out
+}
+
+/// Print the result of `bandwidth_efficiency` as a table to stdout
+pub fn print_efficiency(effs: &[Efficiency]) {
+    print_efficiency_to(&mut std::io::stdout().lock(), effs).unwrap();
+}
+
+/// Write the table of `print_efficiency` to any sink
+pub fn print_efficiency_to<W: Write>(w: &mut W, effs: &[Efficiency]) -> io::Result<()> {
+    writeln!(w, "── bandwidth efficiency ──")?;
+    let rate = format!("{}/s", crate::current_unit().giga().1);
+    writeln!(w, "{:<6} {:>10} {:>10} {:>8}", "dir", rate, "peak", "eff")?;
+    for e in effs {
+        writeln!(
+            w,
+            "{:<6} {:>10.2} {:>10.2} {:>7.1}%{}",
+            e.dir,
+            in_unit(e.measured_gbps),
+            in_unit(e.peak_gbps),
+            e.fraction() * 100.0,
+            if e.below_threshold { "  ← below threshold (pageable memory?)" } else { "" },
+        )?;
+    }
+    Ok(())
+}
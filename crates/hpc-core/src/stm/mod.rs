@@ -0,0 +1,187 @@
+//! Commit/abort counting for STM workloads, and the synthetic workload
+//! of the `stm_abort` example (`run_stm`)
+
+mod workload;
+
+pub use workload::{run_stm, Conflict, Model, StmConfig, StmMode, StmResult};
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        OnceLock,
+    },
+};
+
+/// Metrics op fed by `AbortCounters::commit_after`: one sample per
+/// committed transaction, its value the retries before the commit. The
+/// samples live in `metrics::RETRIES`, not among the µs latencies, so only
+/// `summary()` reports them.
+pub const STM_RETRY: &str = "stm_retry";
+
+/// Counters of one shard; padded to a cache line so shards do not false-share
+#[repr(align(64))]
+struct Shard {
+    commits: AtomicU64,
+    aborts: AtomicU64,
+    gave_up: AtomicU64,
+    // je Ursachen-Slot, der letzte für OTHER_CAUSE
+    causes: [AtomicU64; AbortCounters::MAX_CAUSES + 1],
+}
+
+impl Shard {
+    fn new() -> Self {
+        Shard {
+            commits: AtomicU64::new(0),
+            aborts: AtomicU64::new(0),
+            gave_up: AtomicU64::new(0),
+            causes: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+/// Thread-safe commit/abort counters with per-cause totals.
+///
+/// Each thread counts into its own shard (threads are spread round-robin
+/// over the shards), so concurrent workers do not contend on one atomic.
+/// Causes get a slot on first use; counting never takes a lock.
+/// `snapshot()` sums all shards.
+pub struct AbortCounters {
+    shards: Box<[Shard]>,
+    // Ursache je Slot, in Reihenfolge der ersten Verwendung belegt
+    names: Box<[OnceLock<Box<str>>]>,
+}
+
+/// Totals at the time of `AbortCounters::snapshot`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CounterSnapshot {
+    pub commits: u64,
+    /// `abort()` calls; given-up transactions are counted in `gave_up` only
+    pub aborts: u64,
+    pub gave_up: u64,
+    /// Aborts per cause
+    pub by_cause: BTreeMap<String, u64>,
+}
+
+impl Default for AbortCounters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AbortCounters {
+    /// One shard per available CPU
+    pub fn new() -> Self {
+        let n = std::thread::available_parallelism().map_or(1, |n| n.get());
+        AbortCounters {
+            shards: (0..n).map(|_| Shard::new()).collect(),
+            names: (0..Self::MAX_CAUSES).map(|_| OnceLock::new()).collect(),
+        }
+    }
+
+    /// Distinct causes counted separately; aborts with further causes are
+    /// counted under `OTHER_CAUSE`
+    pub const MAX_CAUSES: usize = 32;
+
+    /// `by_cause` key of the causes beyond `MAX_CAUSES`
+    pub const OTHER_CAUSE: &'static str = "other";
+
+    pub fn commit(&self) {
+        self.shard().commits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `commit()` of a transaction that needed `retries` attempts before it
+    /// succeeded. With the `metrics` feature the count is also recorded as
+    /// a sample of op `STM_RETRY`, so `metrics::summary()` shows mean/p95
+    /// retries per commit.
+    pub fn commit_after(&self, retries: u32) {
+        self.commit();
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_retries(retries);
+        #[cfg(not(feature = "metrics"))]
+        let _ = retries;
+    }
+
+    pub fn abort(&self, cause: &str) {
+        let shard = self.shard();
+        shard.aborts.fetch_add(1, Ordering::Relaxed);
+        shard.causes[self.cause_slot(cause)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A transaction abandoned after its retry budget (see `trace_gave_up`)
+    pub fn gave_up(&self) {
+        self.shard().gave_up.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Sum over all shards. Not atomic across counters: increments racing
+    /// with the snapshot may be missing from some totals.
+    pub fn snapshot(&self) -> CounterSnapshot {
+        let mut snap = CounterSnapshot::default();
+        for shard in self.shards.iter() {
+            snap.commits += shard.commits.load(Ordering::Relaxed);
+            snap.aborts += shard.aborts.load(Ordering::Relaxed);
+            snap.gave_up += shard.gave_up.load(Ordering::Relaxed);
+        }
+        let total = |slot: usize| -> u64 { self.shards.iter().map(|s| s.causes[slot].load(Ordering::Relaxed)).sum() };
+        let named = self.names.iter().map_while(OnceLock::get).enumerate().map(|(slot, cause)| (&**cause, total(slot)));
+        for (cause, n) in named.chain([(Self::OTHER_CAUSE, total(Self::MAX_CAUSES))]) {
+            if n > 0 {
+                *snap.by_cause.entry(cause.to_string()).or_insert(0) += n;
+            }
+        }
+        snap
+    }
+
+    // Slots füllen sich der Reihe nach und werden nie geleert: wer einen
+    // freien Slot zuerst setzt, besitzt ihn, alle anderen finden ihn dort
+    fn cause_slot(&self, cause: &str) -> usize {
+        self.names
+            .iter()
+            .position(|name| &**name.get_or_init(|| cause.into()) == cause)
+            .unwrap_or(Self::MAX_CAUSES)
+    }
+
+    fn shard(&self) -> &Shard {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        thread_local! {
+            static SLOT: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+        }
+        &self.shards[SLOT.with(|s| *s) % self.shards.len()]
+    }
+}
+
+#[cfg(feature = "memtrace")]
+impl CounterSnapshot {
+    /// Compare against the abort records in the trace log: per-cause counts
+    /// must match `by_cause`, the `budget_exhausted` records `gave_up`, and
+    /// the `trace_commit` counts `commits`.
+    /// Only meaningful if every abort was also traced (auto-trace on).
+    /// Returns one line per mismatch.
+    pub fn cross_check_trace(&self) -> std::result::Result<(), Vec<String>> {
+        use crate::memtracer::{CAUSE_BUDGET_EXHAUSTED, LOG, Phase};
+
+        let mut traced: BTreeMap<String, u64> = BTreeMap::new();
+        for r in LOG.lock().unwrap().iter().filter(|r| matches!(r.phase, Phase::Abort)) {
+            *traced.entry(r.cause.clone().unwrap_or_default()).or_insert(0) += 1;
+        }
+        let traced_gave_up = traced.remove(CAUSE_BUDGET_EXHAUSTED).unwrap_or(0);
+
+        let mut diffs = Vec::new();
+        let traced_commits: u64 = crate::memtracer::commit_counts().values().sum();
+        if traced_commits != self.commits {
+            diffs.push(format!("commits: counted {}, traced {}", self.commits, traced_commits));
+        }
+        if traced_gave_up != self.gave_up {
+            diffs.push(format!("gave_up: counted {}, traced {}", self.gave_up, traced_gave_up));
+        }
+        let causes: std::collections::BTreeSet<&String> = self.by_cause.keys().chain(traced.keys()).collect();
+        for cause in causes {
+            let counted = self.by_cause.get(cause).copied().unwrap_or(0);
+            let logged = traced.get(cause).copied().unwrap_or(0);
+            if counted != logged {
+                diffs.push(format!("cause '{}': counted {}, traced {}", cause, counted, logged));
+            }
+        }
+        if diffs.is_empty() { Ok(()) } else { Err(diffs) }
+    }
+}
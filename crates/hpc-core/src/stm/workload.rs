@@ -0,0 +1,377 @@
+//! Synthetic STM workload behind the `stm_abort` example, callable from
+//! benchmarks and tests.
+//!
+//! Deterministic for `Model::Uniform`: barrier start plus one RNG seed per
+//! thread. `Model::Hotspot` depends on the thread interleaving.
+
+use std::str::FromStr;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Barrier,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{AbortCounters, CounterSnapshot};
+use crate::BackoffPolicy;
+
+/// Contention level: abort probability (uniform) or Zipf skew (hotspot)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Conflict {
+    #[default]
+    Low,
+    Med,
+    High,
+}
+
+impl FromStr for Conflict {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(Conflict::Low),
+            "med" | "medium" => Ok(Conflict::Med),
+            "high" => Ok(Conflict::High),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How conflicts are decided
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Model {
+    /// Fixed abort probability per transaction
+    #[default]
+    Uniform,
+    /// Zipf-distributed addresses; a transaction aborts if another thread
+    /// touched one of its addresses within a short window
+    Hotspot,
+}
+
+impl FromStr for Model {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "uniform" => Ok(Model::Uniform),
+            "hotspot" => Ok(Model::Hotspot),
+            _ => Err(()),
+        }
+    }
+}
+
+/// When the run ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StmMode {
+    /// Fixed number of transactions, split evenly over the threads
+    Ops(u64),
+    /// Every thread runs transactions until the time is up
+    Duration(Duration),
+}
+
+/// Parameters of `run_stm`
+#[derive(Clone, Debug)]
+pub struct StmConfig {
+    pub threads: usize,
+    pub conflict: Conflict,
+    pub model: Model,
+    pub mode: StmMode,
+    pub seed: u64,
+    /// Retry an aborted transaction up to N times, then give up (cause
+    /// `budget_exhausted`); `None` = one abort, no retry
+    pub retry_budget: Option<u32>,
+    /// Spin after each abort; every thread adds `tid` µs on top, and a
+    /// `Jittered` policy is re-seeded per thread
+    pub backoff: BackoffPolicy,
+    /// Print a combined progress line to stderr about once per second
+    pub progress: bool,
+}
+
+impl Default for StmConfig {
+    fn default() -> Self {
+        StmConfig {
+            threads: 4,
+            conflict: Conflict::Low,
+            model: Model::Uniform,
+            mode: StmMode::Ops(1_000_000),
+            seed: 1,
+            retry_budget: None,
+            backoff: BackoffPolicy::default(),
+            progress: false,
+        }
+    }
+}
+
+/// Outcome of `run_stm`
+#[derive(Clone, Debug)]
+pub struct StmResult {
+    /// Transactions run (committed, aborted without retry, or given up)
+    pub ops: u64,
+    pub counters: CounterSnapshot,
+    /// From the barrier release to the last worker finishing
+    pub wall: Duration,
+}
+
+/// Run the workload on `config.threads` threads.
+///
+/// With the `memtrace` feature every abort is traced (`trace_abort`, token
+/// "stm"), given-up transactions via `trace_gave_up` and commits via
+/// `trace_commit`; with `metrics`,
+/// retries per commit are recorded as `STM_RETRY`. The trace is not
+/// flushed here.
+pub fn run_stm(config: StmConfig) -> StmResult {
+    let threads = config.threads.max(1);
+
+    // Konfliktwahrscheinlichkeit grob (feintuning später)
+    let p_conflict = match config.conflict {
+        Conflict::Low => 0.02_f32,
+        Conflict::Med => 0.15_f32,
+        Conflict::High => 0.40_f32,
+    };
+
+    // Hotspot: Konfliktlevel steuert die Schiefe der Zipf-Verteilung
+    let hotspot = match config.model {
+        Model::Uniform => None,
+        Model::Hotspot => Some(Hotspot::new(match config.conflict {
+            Conflict::Low => 0.8,
+            Conflict::Med => 1.0,
+            Conflict::High => 1.2,
+        })),
+    };
+
+    let counters = AbortCounters::new();
+    // Fortschritt je Thread; ausgegeben wird zentral vom Monitor
+    let progress: Vec<AtomicU64> = (0..threads).map(|_| AtomicU64::new(0)).collect();
+    let workers_done = AtomicBool::new(false);
+    // Barrier für synchronen Start; +1 für den Aufrufer, der die Zeit misst
+    let barrier = Barrier::new(threads + 1);
+
+    // Für Ops-Modus: faire Verteilung
+    let (ops_base, ops_extra) = match config.mode {
+        StmMode::Ops(n) => (n / threads as u64, n % threads as u64),
+        StmMode::Duration(_) => (0, 0),
+    };
+
+    let wall = thread::scope(|s| {
+        // Monitor: eine kombinierte Fortschrittszeile ca. 1×/s statt eprintln je Thread
+        if config.progress {
+            s.spawn(|| {
+                let mut last = Instant::now();
+                while !workers_done.load(Ordering::Relaxed) {
+                    thread::sleep(Duration::from_millis(50));
+                    if last.elapsed().as_secs() >= 1 {
+                        print_progress(&progress);
+                        last = Instant::now();
+                    }
+                }
+            });
+        }
+
+        let mut handles = Vec::with_capacity(threads);
+        for tid in 0..threads {
+            let (barrier, counters, progress, hotspot) = (&barrier, &counters, &progress, hotspot.as_ref());
+            let retry_budget = config.retry_budget;
+            let mode = config.mode;
+            let per_thread = ops_base + if (tid as u64) < ops_extra { 1 } else { 0 };
+
+            // deterministischer Seed je Thread
+            let thread_seed = config.seed
+                ^ (((tid as u64) + 1) << 32)
+                ^ 0x9E37_79B9_7F4A_7C15u64;
+            let mut rng = XorShift64::new(thread_seed);
+            let backoff = config.backoff.with_seed(thread_seed);
+
+            handles.push(s.spawn(move || {
+                // synchroner Start
+                barrier.wait();
+                let mut tx = || {
+                    run_tx(hotspot, p_conflict, tid, &mut rng, retry_budget, &backoff, counters);
+                    // Fortschritt zählen (Ausgabe macht der Monitor-Thread)
+                    progress[tid].fetch_add(1, Ordering::Relaxed);
+                };
+                match mode {
+                    StmMode::Ops(_) => (0..per_thread).for_each(|_| tx()),
+                    StmMode::Duration(d) => {
+                        let deadline = Instant::now() + d;
+                        while Instant::now() < deadline {
+                            tx();
+                        }
+                    }
+                }
+            }));
+        }
+
+        barrier.wait();
+        let t0 = Instant::now();
+        for h in handles {
+            let _ = h.join();
+        }
+        let wall = t0.elapsed();
+        workers_done.store(true, Ordering::Relaxed);
+        wall
+    });
+
+    StmResult {
+        ops: progress.iter().map(|p| p.load(Ordering::Relaxed)).sum(),
+        counters: counters.snapshot(),
+        wall,
+    }
+}
+
+// ---- sehr einfacher, deterministischer PRNG ----
+#[derive(Clone)]
+struct XorShift64 {
+    state: u64,
+}
+impl XorShift64 {
+    fn new(seed: u64) -> Self { Self { state: seed.max(1) } }
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        (x >> 32) as u32
+    }
+    fn next_f32(&mut self) -> f32 {
+        let v = self.next_u32();
+        (v as f32) / (u32::MAX as f32)
+    }
+}
+
+// ---- Hotspot-Modell ----
+// Jede Transaktion berührt TOUCHES_PER_TX Adressen (Zipf-verteilt über HOT_ADDRS).
+// Konflikt: ein anderer Thread hat dieselbe Adresse vor < WINDOW_NS berührt.
+// conflict_sz = Anzahl konfliktbehafteter Adressen × LINE_BYTES.
+
+const HOT_ADDRS: usize = 1024;
+const LINE_BYTES: usize = 64;
+const TOUCHES_PER_TX: usize = 4;
+const WINDOW_NS: u64 = 3_000;
+
+struct Hotspot {
+    cdf: Vec<f32>,
+    // je Adresse: (ns seit t0 << 8) | tid
+    slots: Vec<AtomicU64>,
+    t0: Instant,
+}
+impl Hotspot {
+    fn new(skew: f64) -> Self {
+        let weights: Vec<f64> = (1..=HOT_ADDRS).map(|k| 1.0 / (k as f64).powf(skew)).collect();
+        let total: f64 = weights.iter().sum();
+        let mut acc = 0.0;
+        let cdf = weights
+            .iter()
+            .map(|w| {
+                acc += w / total;
+                acc as f32
+            })
+            .collect();
+        Self {
+            cdf,
+            slots: (0..HOT_ADDRS).map(|_| AtomicU64::new(0)).collect(),
+            t0: Instant::now(),
+        }
+    }
+
+    fn sample(&self, rng: &mut XorShift64) -> usize {
+        let u = rng.next_f32();
+        self.cdf.partition_point(|&c| c < u).min(HOT_ADDRS - 1)
+    }
+
+    // Konfliktgröße in Bytes (0 = kein Konflikt)
+    fn touch(&self, tid: usize, rng: &mut XorShift64) -> usize {
+        let me = (tid & 0xFF) as u64;
+        let mut conflicting = 0;
+        for _ in 0..TOUCHES_PER_TX {
+            let addr = self.sample(rng);
+            let now = self.t0.elapsed().as_nanos() as u64;
+            let prev = self.slots[addr].swap((now << 8) | me, Ordering::Relaxed);
+            let (prev_t, prev_tid) = (prev >> 8, prev & 0xFF);
+            if prev != 0 && prev_tid != me && now.saturating_sub(prev_t) < WINDOW_NS {
+                conflicting += 1;
+            }
+        }
+        conflicting * LINE_BYTES
+    }
+}
+
+// Eine Op: Arbeit simulieren, Konflikt samplen, bei Abort ggf. wiederholen.
+// Ohne Budget bleibt es bei einem Abort ohne Retry.
+fn run_tx(
+    hotspot: Option<&Hotspot>,
+    p_conflict: f32,
+    tid: usize,
+    rng: &mut XorShift64,
+    retry_budget: Option<u32>,
+    backoff: &BackoffPolicy,
+    counters: &AbortCounters,
+) {
+    let mut attempt = 0u32;
+    loop {
+        // Arbeit simulieren
+        spin_for_ns(1500 + (rng.next_u32() % 1500) as u64);
+
+        // Konfliktsampling
+        let conflict_sz = sample_conflict(hotspot, p_conflict, tid, rng);
+        if conflict_sz == 0 {
+            counters.commit_after(attempt);
+            #[cfg(feature = "memtrace")]
+            crate::memtracer::trace_commit("stm");
+            return;
+        }
+        counters.abort("conflict");
+        attempt += 1;
+        // Backoff laut Policy, je Thread um tid µs versetzt
+        spin_for_ns(backoff.next_delay(attempt).as_nanos() as u64 + (tid as u64) * 1_000);
+
+        #[cfg(feature = "memtrace")]
+        crate::memtracer::trace_abort(
+            /*tx_id*/ 0,
+            /*cause*/ "conflict",
+            /*retries*/ attempt,
+            /*conflict_sz*/ conflict_sz as u32,
+            /*abort_token*/ "stm",
+        );
+
+        match retry_budget {
+            None => return,
+            Some(budget) if attempt > budget => {
+                counters.gave_up();
+                #[cfg(feature = "memtrace")]
+                crate::memtracer::trace_gave_up(0, attempt, "stm");
+                return;
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+// Konfliktgröße der aktuellen Op (0 = Commit); uniform behält conflict_sz = 1
+fn sample_conflict(hotspot: Option<&Hotspot>, p_conflict: f32, tid: usize, rng: &mut XorShift64) -> usize {
+    match hotspot {
+        Some(h) => h.touch(tid, rng),
+        None => {
+            if rng.next_f32() < p_conflict { 1 } else { 0 }
+        }
+    }
+}
+
+// eine Zeile: Summe + Anteil je Thread
+fn print_progress(progress: &[AtomicU64]) {
+    let per_thread: Vec<u64> = progress.iter().map(|p| p.load(Ordering::Relaxed)).collect();
+    let total: u64 = per_thread.iter().sum();
+    let parts: Vec<String> = per_thread
+        .iter()
+        .enumerate()
+        .map(|(tid, n)| format!("t{}={}", tid, n))
+        .collect();
+    eprintln!("progress done={} [{}]", total, parts.join(" "));
+}
+
+// sehr kleiner, portabler Busy-Wait (für deterministische Mikro-Sleeps)
+#[inline(always)]
+fn spin_for_ns(nanos: u64) {
+    let start = Instant::now();
+    while start.elapsed().as_nanos() < nanos as u128 {
+        core::hint::spin_loop();
+    }
+}
@@ -0,0 +1,84 @@
+//! Byte and bandwidth formatting for the human-readable reports
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Size convention of all printed reports (`metrics::summary`,
+/// `report::combined`, memtrace_summary.txt, `summary_oneline`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Unit {
+    /// Powers of 1024: KiB, MiB, GiB
+    #[default]
+    Binary,
+    /// Powers of 1000: kB, MB, GB
+    Decimal,
+}
+
+// true = Decimal
+static DECIMAL: AtomicBool = AtomicBool::new(false);
+
+/// Choose the convention for every report printed afterwards (default `Binary`)
+pub fn set_unit(unit: Unit) {
+    DECIMAL.store(unit == Unit::Decimal, Ordering::Relaxed);
+}
+
+/// The convention chosen with `set_unit`
+pub fn current_unit() -> Unit {
+    if DECIMAL.load(Ordering::Relaxed) { Unit::Decimal } else { Unit::Binary }
+}
+
+/// `bytes` in the largest fitting unit of `current_unit()`, e.g. "1.50 MiB"
+pub fn format_bytes(bytes: u64) -> String {
+    current_unit().format_bytes(bytes)
+}
+
+/// `bytes` moved in `us` microseconds as "9.10 GiB/s" ("-" if `us` is 0)
+pub fn format_rate(bytes: u64, us: u128) -> String {
+    current_unit().format_rate(bytes, us)
+}
+
+impl Unit {
+    fn base(self) -> f64 {
+        match self { Unit::Binary => 1024.0, Unit::Decimal => 1000.0 }
+    }
+
+    fn suffixes(self) -> [&'static str; 5] {
+        match self {
+            Unit::Binary => ["B", "KiB", "MiB", "GiB", "TiB"],
+            Unit::Decimal => ["B", "kB", "MB", "GB", "TB"],
+        }
+    }
+
+    /// Giga unit (GiB or GB) as divisor and name; rates always use it, so
+    /// rows of one table stay comparable
+    pub(crate) fn giga(self) -> (f64, &'static str) {
+        (self.base().powi(3), self.suffixes()[3])
+    }
+
+    /// Mega unit (MiB or MB) as divisor and name
+    #[cfg(feature = "memtrace")]
+    pub(crate) fn mega(self) -> (f64, &'static str) {
+        (self.base().powi(2), self.suffixes()[2])
+    }
+
+    pub fn format_bytes(self, bytes: u64) -> String {
+        let mut v = bytes as f64;
+        let mut i = 0;
+        while v >= self.base() && i < 4 {
+            v /= self.base();
+            i += 1;
+        }
+        if i == 0 {
+            format!("{} B", bytes)
+        } else {
+            format!("{:.2} {}", v, self.suffixes()[i])
+        }
+    }
+
+    pub fn format_rate(self, bytes: u64, us: u128) -> String {
+        if us == 0 {
+            return "-".into();
+        }
+        let (div, name) = self.giga();
+        format!("{:.2} {}/s", bytes as f64 / div / (us as f64 / 1e6), name)
+    }
+}
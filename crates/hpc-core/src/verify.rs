@@ -0,0 +1,50 @@
+//! Host-side result checks with a tolerance matching the element precision
+
+/// Float element types comparable via `f64`
+pub trait Tolerance: Copy {
+    /// Default relative tolerance, a few ULP at 1.0
+    const DEFAULT_TOL: f64;
+
+    fn to_f64(self) -> f64;
+}
+
+impl Tolerance for f32 {
+    const DEFAULT_TOL: f64 = 1e-6;
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Tolerance for f64 {
+    const DEFAULT_TOL: f64 = 1e-12;
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+// f16: 10 Mantissenbits, EPSILON ~ 9.8e-4
+#[cfg(feature = "half")]
+impl Tolerance for half::f16 {
+    const DEFAULT_TOL: f64 = 2e-3;
+    fn to_f64(self) -> f64 {
+        half::f16::to_f64(self)
+    }
+}
+
+/// Index of the first element of `got` differing from `expected` by more
+/// than `tol * max(1, |expected|)`; a length mismatch counts from the
+/// shorter length. `None` if all match.
+pub fn first_mismatch<T: Tolerance>(got: &[T], expected: &[T], tol: f64) -> Option<usize> {
+    let bad = got.iter().zip(expected).position(|(&g, &e)| {
+        let (g, e) = (g.to_f64(), e.to_f64());
+        let diff = (g - e).abs();
+        // NaN gilt immer als Abweichung
+        diff.is_nan() || diff > tol * e.abs().max(1.0)
+    });
+    bad.or_else(|| (got.len() != expected.len()).then(|| got.len().min(expected.len())))
+}
+
+/// `got` matches `expected` within `T::DEFAULT_TOL`
+pub fn all_close<T: Tolerance>(got: &[T], expected: &[T]) -> bool {
+    first_mismatch(got, expected, T::DEFAULT_TOL).is_none()
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{reset, trace_abort, AbortAgg, Record, LOG};
+
+// Record wie ihn trace_abort ins Log schreibt
+fn abort(retries: u32, conflict_sz: u32) -> Record {
+    reset();
+    trace_abort(0, "conflict", retries, conflict_sz, "stm");
+    let rec = LOG.lock().unwrap().pop().unwrap();
+    reset();
+    rec
+}
+
+#[test]
+fn abort_sums_saturate_instead_of_wrapping() {
+    let mut agg = AbortAgg {
+        count: u64::MAX / 2,
+        retries_sum: u64::MAX / 2,
+        conflict_sum: u64::MAX - 1,
+        ..Default::default()
+    };
+
+    for _ in 0..4 {
+        agg.add(&abort(u32::MAX, u32::MAX));
+    }
+
+    assert_eq!(agg.count, u64::MAX / 2 + 4);
+    assert_eq!(agg.retries_sum, u64::MAX / 2 + 4 * u32::MAX as u64);
+    assert_eq!(agg.conflict_sum, u64::MAX);
+    assert!(agg.retries_avg() > 0.0);
+}
@@ -0,0 +1,38 @@
+use hpc_core::AbortCounters;
+
+#[test]
+fn causes_are_counted_across_threads() {
+    let counters = AbortCounters::new();
+    std::thread::scope(|s| {
+        for t in 0..4 {
+            let counters = &counters;
+            s.spawn(move || {
+                for i in 0..1000 {
+                    counters.abort(if (i + t) % 2 == 0 { "conflict" } else { "validation" });
+                }
+                counters.commit();
+            });
+        }
+    });
+
+    let snap = counters.snapshot();
+    assert_eq!((snap.commits, snap.aborts), (4, 4000));
+    assert_eq!(snap.by_cause.get("conflict"), Some(&2000));
+    assert_eq!(snap.by_cause.get("validation"), Some(&2000));
+    assert_eq!(snap.by_cause.len(), 2);
+}
+
+#[test]
+fn causes_beyond_the_table_count_as_other() {
+    let counters = AbortCounters::new();
+    for i in 0..AbortCounters::MAX_CAUSES + 3 {
+        counters.abort(&format!("cause{i}"));
+    }
+    counters.abort("cause0");
+
+    let snap = counters.snapshot();
+    assert_eq!(snap.aborts, AbortCounters::MAX_CAUSES as u64 + 4);
+    assert_eq!(snap.by_cause.get("cause0"), Some(&2));
+    assert_eq!(snap.by_cause.get(AbortCounters::OTHER_CAUSE), Some(&3));
+    assert_eq!(snap.by_cause.len(), AbortCounters::MAX_CAUSES + 1);
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "memtrace_full")]
+
+use hpc_core::memtracer::{flush_csv_to, reset, set_abort_full_limit, trace_abort, trace_gave_up, AbortFullLimit};
+
+mod common;
+
+fn seqs(dir: &std::path::Path) -> Vec<u64> {
+    let csv = std::fs::read_to_string(dir.join("memtrace_abort_full.csv")).unwrap();
+    csv.lines().skip(1).map(|l| l.split(',').next().unwrap().parse().unwrap()).collect()
+}
+
+fn summary_line(dir: &std::path::Path) -> Option<String> {
+    let txt = std::fs::read_to_string(dir.join("memtrace_summary.txt")).unwrap();
+    txt.lines().find(|l| l.starts_with("abort_full_omitted:")).map(str::to_string)
+}
+
+// ein Test pro Binary: set_abort_full_limit ist global
+#[test]
+fn full_csv_is_capped_aggregate_is_not() {
+    reset();
+    for i in 0..99 {
+        trace_abort(i, "conflict", 0, 8, "a");
+    }
+    // Aufgabe-Marker stehen auch in der Voll-CSV
+    trace_gave_up(99, 3, "a");
+    let dir = common::temp_dir("abort_full_limit");
+
+    flush_csv_to(&dir);
+    assert_eq!(seqs(&dir).len(), 100);
+    assert_eq!(summary_line(&dir), None);
+
+    set_abort_full_limit(AbortFullLimit::First(10));
+    flush_csv_to(&dir);
+    assert_eq!(seqs(&dir), (0..10).collect::<Vec<u64>>());
+    assert_eq!(summary_line(&dir).as_deref(), Some("abort_full_omitted: 90"));
+    let agg = std::fs::read_to_string(dir.join("memtrace_abort.csv")).unwrap();
+    assert!(agg.lines().any(|l| l.starts_with("a,conflict,99,")), "{agg}");
+
+    // Stichprobe: n Records, sortiert, deterministisch je Seed
+    set_abort_full_limit(AbortFullLimit::Sample { n: 10, seed: 42 });
+    flush_csv_to(&dir);
+    let first = seqs(&dir);
+    assert_eq!(first.len(), 10);
+    assert!(first.windows(2).all(|w| w[0] < w[1]));
+    assert!(*first.last().unwrap() >= 10, "{first:?}");
+    flush_csv_to(&dir);
+    assert_eq!(seqs(&dir), first);
+
+    // Limit über der Anzahl: nichts weggelassen
+    set_abort_full_limit(AbortFullLimit::First(500));
+    flush_csv_to(&dir);
+    assert_eq!(seqs(&dir).len(), 100);
+    assert_eq!(summary_line(&dir).as_deref(), Some("abort_full_omitted: 0"));
+
+    set_abort_full_limit(AbortFullLimit::All);
+}
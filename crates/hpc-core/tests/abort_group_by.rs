@@ -0,0 +1,44 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{flush_csv_to, reset, set_abort_group_by, trace_abort, trace_commit, trace_gave_up, GroupBy};
+
+mod common;
+
+// ein Test pro Binary: set_abort_group_by ist global
+#[test]
+fn abort_csv_rows_follow_group_by() {
+    reset();
+    trace_abort(0, "conflict", 1, 8, "a");
+    trace_abort(1, "validation", 3, 24, "a");
+    trace_gave_up(1, 3, "a");
+    trace_abort(2, "conflict", 2, 16, "b");
+    trace_commit("a");
+
+    let dir = common::temp_dir("group_by");
+    let flush = |g| {
+        set_abort_group_by(g);
+        flush_csv_to(&dir);
+        let csv = std::fs::read_to_string(dir.join("memtrace_abort.csv")).unwrap();
+        let mut lines: Vec<String> = csv.lines().map(|l| l.to_string()).collect();
+        lines[1..].sort();
+        lines
+    };
+
+    // a: 2 Aborts (ohne budget_exhausted), 1 aufgegeben, 1 Commit
+    let by_token = flush(GroupBy::Token);
+    assert_eq!(by_token[0], "abort_token,count,retries_avg,conflict_avg,conflict_min,conflict_max,first_us,last_us,gave_up_count,commits,abort_rate");
+    assert_eq!(by_token.len(), 3);
+    assert!(by_token[1].starts_with("a,2,2.000,16.000,8,24,"), "{}", by_token[1]);
+    assert!(by_token[1].ends_with(",1,1,0.667"), "{}", by_token[1]);
+
+    let by_cause = flush(GroupBy::Cause);
+    assert_eq!(by_cause[0], "cause,count,retries_avg,conflict_avg,conflict_min,conflict_max,first_us,last_us");
+    assert_eq!(by_cause.len(), 4);
+    assert!(by_cause[2].starts_with("conflict,2,1.500,12.000,8,16,"), "{}", by_cause[2]);
+
+    let both = flush(GroupBy::Both);
+    assert!(both[0].starts_with("abort_token,cause,count,"));
+    assert_eq!(both.len(), 5);
+
+    set_abort_group_by(GroupBy::Both);
+}
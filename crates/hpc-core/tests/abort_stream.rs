@@ -0,0 +1,40 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{clear_abort_sink, reset, set_abort_sink, trace_abort};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+// Write-Ziel, das der Test nach dem Lauf auslesen kann
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn every_nth_abort_becomes_a_json_line() {
+    reset();
+    let out = Shared::default();
+    set_abort_sink(out.clone(), 2);
+    trace_abort(1, "conflict", 0, 64, "stm");
+    // sofort sichtbar, ohne flush_csv
+    assert_eq!(String::from_utf8(out.0.lock().unwrap().clone()).unwrap().lines().count(), 1);
+    trace_abort(2, "conflict", 1, 64, "stm");
+    trace_abort(3, "say \"hi\"", 2, 8, "a\\b");
+    clear_abort_sink().unwrap();
+    trace_abort(4, "conflict", 0, 0, "stm");
+
+    let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2, "{text}");
+    assert!(lines[0].starts_with(r#"{"t_us":"#), "{}", lines[0]);
+    assert!(lines[0].ends_with(r#","token":"stm","cause":"conflict","retries":0,"conflict_sz":64}"#), "{}", lines[0]);
+    assert!(lines[1].ends_with(r#","token":"a\\b","cause":"say \"hi\"","retries":2,"conflict_sz":8}"#), "{}", lines[1]);
+    reset();
+}
@@ -0,0 +1,44 @@
+use hpc_core::{retry, BackoffPolicy};
+use std::time::Duration;
+
+#[test]
+fn delays_follow_policy() {
+    let us = Duration::from_micros;
+    assert_eq!(BackoffPolicy::Fixed(us(10)).next_delay(5), us(10));
+
+    let exp = BackoffPolicy::Exponential { base: us(10), cap: us(50) };
+    let got: Vec<Duration> = (0..=4).map(|a| exp.next_delay(a)).collect();
+    assert_eq!(got, vec![us(10), us(10), us(20), us(40), us(50)]);
+    assert_eq!(exp.next_delay(u32::MAX), us(50));
+
+    // Jitter: innerhalb [d/2, d), deterministisch je Seed
+    let jit = BackoffPolicy::Jittered { base: us(100), cap: us(1000), seed: 7 };
+    for a in 1..=5 {
+        let (d, full) = (jit.next_delay(a), exp_of(a));
+        assert!(d >= full / 2 && d < full, "{a}: {d:?}");
+        assert_eq!(d, jit.next_delay(a));
+    }
+    assert_ne!(jit.next_delay(1), jit.with_seed(8).next_delay(1));
+}
+
+fn exp_of(attempt: u32) -> Duration {
+    BackoffPolicy::Exponential { base: Duration::from_micros(100), cap: Duration::from_micros(1000) }.next_delay(attempt)
+}
+
+#[test]
+fn retry_stops_at_success_or_budget() {
+    let policy = BackoffPolicy::Fixed(Duration::ZERO);
+    let mut calls = Vec::new();
+    let ok: Result<u32, ()> = retry(&policy, 5, |a| {
+        calls.push(a);
+        if a == 2 { Ok(a) } else { Err(()) }
+    });
+    assert_eq!((ok, calls), (Ok(2), vec![0, 1, 2]));
+
+    let mut n = 0;
+    let err: Result<(), u32> = retry(&policy, 2, |a| {
+        n += 1;
+        Err(a)
+    });
+    assert_eq!((err, n), (Err(2), 3));
+}
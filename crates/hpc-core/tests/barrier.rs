@@ -0,0 +1,27 @@
+#![cfg(all(feature = "memtrace", feature = "test-util"))]
+
+use hpc_core::memtracer::{flush_csv_to, log_transfer, max_concurrent_ops, reset, reset_clock, set_clock, trace_barrier, Dir, LOG};
+
+mod common;
+
+#[test]
+fn barriers_stay_out_of_the_summary() {
+    reset();
+    set_clock(Box::new(|| 200));
+    log_transfer(100, 110, 64, Dir::H2D);
+    trace_barrier("finish"); // 200
+    log_transfer(300, 310, 64, Dir::D2H);
+    reset_clock();
+
+    assert_eq!(max_concurrent_ops(&LOG.lock().unwrap()), 1);
+
+    let dir = common::temp_dir("barrier");
+    flush_csv_to(&dir);
+    let summary = std::fs::read_to_string(dir.join("memtrace_summary.txt")).unwrap();
+    reset();
+
+    // Lücke 110..300 gehört ganz dem D2H, nicht zur Hälfte der Barriere
+    for line in ["events_total: 2", "idle_total_us: 290", "idle_before_d2h_us: 190", "idle_before_kernel_us: 0", "wall_us: 210"] {
+        assert!(summary.lines().any(|l| l == line), "{line} missing in\n{summary}");
+    }
+}
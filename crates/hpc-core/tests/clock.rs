@@ -0,0 +1,26 @@
+#![cfg(all(feature = "memtrace", feature = "test-util"))]
+
+use hpc_core::memtracer::{reset, reset_clock, set_clock, start, trace_barrier, Dir, LOG};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn injected_clock_gives_exact_timestamps() {
+    reset();
+    // jede Abfrage rückt die Uhr um 10 µs vor, beginnend bei 100
+    let ticks = Arc::new(AtomicU64::new(100));
+    let t = Arc::clone(&ticks);
+    set_clock(Box::new(move || t.fetch_add(10, Ordering::Relaxed)));
+
+    start(Dir::H2D, 64).finish(); // 100..110
+    ticks.store(150, Ordering::Relaxed);
+    trace_barrier("sync"); // 150
+    start(Dir::Kernel, 0).finish(); // 160..170
+    reset_clock();
+
+    let got: Vec<(u64, u64, u64)> =
+        LOG.lock().unwrap().iter().map(|r| (r.t_start_us, r.t_end_us, r.idle_us)).collect();
+    // die Barriere trägt kein Idle, die Lücke geht an den Kernel
+    assert_eq!(got, vec![(100, 110, 100), (150, 150, 0), (160, 170, 50)]);
+    reset();
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{
+    commit_counts, disable_auto_trace, enable_auto_trace, flush_csv_to, reset, trace_abort, trace_commit, trace_gave_up, LOG,
+};
+
+mod common;
+
+#[test]
+fn abort_csv_reports_commits_and_rate_per_token() {
+    reset();
+    for _ in 0..3 {
+        trace_commit("stm");
+    }
+    trace_abort(3, "conflict", 1, 8, "stm");
+    trace_gave_up(3, 2, "stm");
+    trace_commit("idle");
+
+    let dir = common::temp_dir("commits");
+    flush_csv_to(&dir);
+    let csv = std::fs::read_to_string(dir.join("memtrace_abort.csv")).unwrap();
+
+    assert!(csv.lines().next().unwrap().ends_with(",gave_up_count,commits,abort_rate"));
+    // 1 Abort (budget_exhausted zählt nicht) auf 3 Commits
+    let conflict = csv.lines().find(|l| l.starts_with("stm,conflict,")).unwrap();
+    assert!(conflict.ends_with(",1,3,0.250"), "{conflict}");
+    assert!(csv.lines().any(|l| l == "idle,,0,0.000,0.000,0,0,0,0,0,1,0.000"), "{csv}");
+
+    // Summary zählt wie die CSV: der Marker nur unter gave_up
+    let summary = std::fs::read_to_string(dir.join("memtrace_summary.txt")).unwrap();
+    assert!(summary.lines().any(|l| l == "aborts: 1"), "{summary}");
+    assert!(summary.lines().any(|l| l == "gave_up: 1"), "{summary}");
+
+    reset();
+    assert!(commit_counts().is_empty());
+
+    // Threads zählen in denselben Slot, auch nach reset; ohne Auto-Trace
+    // fallen Commits und Aborts gleichermaßen weg
+    let threads: Vec<_> = (0..4)
+        .map(|_| std::thread::spawn(|| (0..1000).for_each(|_| trace_commit("stm"))))
+        .collect();
+    threads.into_iter().for_each(|t| t.join().unwrap());
+    disable_auto_trace();
+    trace_commit("stm");
+    trace_abort(4, "conflict", 0, 8, "stm");
+    trace_gave_up(4, 2, "stm");
+    enable_auto_trace();
+    assert_eq!(commit_counts().get("stm"), Some(&4000));
+    assert!(LOG.lock().unwrap().is_empty());
+    reset();
+}
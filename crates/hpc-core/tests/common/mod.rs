@@ -0,0 +1,65 @@
+//! Shared helpers for the integration tests: an OpenCL device and
+//! temporary output directories
+
+// jede Testdatei nutzt nur einen Teil der Helfer
+#![allow(dead_code)]
+
+use opencl3::{
+    context::Context,
+    device::{Device, CL_DEVICE_TYPE_GPU},
+    platform::get_platforms,
+};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// First GPU of the first platform with a context on it; `None` (after a
+/// "skipped" note) without an OpenCL platform, e.g. in CI
+pub fn gpu_context() -> Option<(Device, Context)> {
+    let Some(device_id) = get_platforms()
+        .ok()
+        .and_then(|p| p.first()?.get_devices(CL_DEVICE_TYPE_GPU).ok()?.first().copied())
+    else {
+        eprintln!("no OpenCL GPU, skipped");
+        return None;
+    };
+    let device = Device::new(device_id);
+    let context = Context::from_device(&device).unwrap();
+    Some((device, context))
+}
+
+/// Directory under the system temp dir, removed with its content on drop
+pub struct TempDir(PathBuf);
+
+impl std::ops::Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+/// Fresh empty directory `hpc_core_<name>_<pid>_<n>`; the counter keeps
+/// parallel tests of one binary apart
+pub fn temp_dir(name: &str) -> TempDir {
+    static N: AtomicU64 = AtomicU64::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "hpc_core_{}_{}_{}",
+        name,
+        std::process::id(),
+        N.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    TempDir(dir)
+}
@@ -0,0 +1,32 @@
+use hpc_core::{BufferCompare, GpuBuffer, Ready};
+use opencl3::command_queue::CommandQueue;
+
+mod common;
+
+#[test]
+fn mismatches_count_equal_different_and_tolerated() {
+    let Some((device, context)) = common::gpu_context() else { return };
+    let queue = CommandQueue::create(&context, device.id(), 0).unwrap();
+    let mut cmp = BufferCompare::new(&context).unwrap();
+    let upload = |v: &[f32]| GpuBuffer::<Ready>::from_slice(&context, &queue, v).unwrap();
+
+    let reference: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+    let b = upload(&reference);
+
+    let same = upload(&reference);
+    assert_eq!(cmp.run(&context, &queue, &same, &b, 0.0).unwrap(), 0);
+
+    let shifted: Vec<f32> = reference.iter().map(|x| x + 10.0).collect();
+    let all_off = upload(&shifted);
+    assert_eq!(cmp.run(&context, &queue, &all_off, &b, 1e-3).unwrap(), 1000);
+
+    // relative Toleranz: 0.5 % Abweichung liegt unter tol = 1 %, ab |b| > 1
+    let near: Vec<f32> = reference.iter().map(|x| x * 1.005).collect();
+    let near = upload(&near);
+    assert_eq!(cmp.run(&context, &queue, &near, &b, 0.01).unwrap(), 0);
+    assert_eq!(cmp.run(&context, &queue, &near, &b, 0.001).unwrap(), 999);
+
+    let mut nan = reference.clone();
+    nan[7] = f32::NAN;
+    assert_eq!(cmp.run(&context, &queue, &upload(&nan), &b, 1.0).unwrap(), 1);
+}
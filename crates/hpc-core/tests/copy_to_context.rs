@@ -0,0 +1,54 @@
+use hpc_core::{enqueue_marker, GpuBuffer, GpuEventGuard, Queued};
+use opencl3::{
+    command_queue::CommandQueue,
+    context::Context,
+    memory::{CL_MEM_HOST_READ_ONLY, CL_MEM_READ_ONLY},
+};
+
+mod common;
+
+#[test]
+fn copy_keeps_data_and_flags() {
+    let Some((device, src_ctx)) = common::gpu_context() else { return };
+    let dst_ctx = Context::from_device(&device).unwrap();
+    let src_queue = CommandQueue::create(&src_ctx, device.id(), 0).unwrap();
+    let dst_queue = CommandQueue::create(&dst_ctx, device.id(), 0).unwrap();
+    let data: Vec<u8> = (0..=255).collect();
+
+    let (src, g) = GpuBuffer::<Queued>::with_flags(&src_ctx, data.len(), CL_MEM_READ_ONLY)
+        .unwrap()
+        .enqueue_write(&src_queue, &data)
+        .unwrap();
+    let src = src.into_ready(g);
+
+    #[cfg(feature = "metrics")]
+    let bytes = |op| hpc_core::metrics::BYTES.lock().unwrap().get(op).map_or(0, |e| e.0);
+    #[cfg(feature = "metrics")]
+    let before = (bytes("copy_to_context"), bytes("enqueue_read_into"), bytes("enqueue_write"));
+
+    let copy = src.copy_to_context(&src_queue, &dst_ctx, &dst_queue).unwrap();
+    assert_eq!(copy.flags(), CL_MEM_READ_ONLY);
+    assert_eq!(copy.read_to_vec::<u8>(&dst_queue).unwrap(), data);
+
+    // nur copy_to_context zählt die Bytes, die Teilschritte nicht noch einmal
+    #[cfg(feature = "metrics")]
+    assert_eq!(
+        (bytes("copy_to_context"), bytes("enqueue_read_into"), bytes("enqueue_write")),
+        (before.0 + 256, before.1, before.2)
+    );
+}
+
+#[test]
+fn host_read_only_copy_is_created_without_a_write() {
+    let Some((device, src_ctx)) = common::gpu_context() else { return };
+    let dst_ctx = Context::from_device(&device).unwrap();
+    let src_queue = CommandQueue::create(&src_ctx, device.id(), 0).unwrap();
+    let dst_queue = CommandQueue::create(&dst_ctx, device.id(), 0).unwrap();
+
+    // Inhalt egal, nur die Flags zählen: ohne Upload über einen Marker nach Ready
+    let marker = GpuEventGuard::new(enqueue_marker(&src_queue, &[]).unwrap());
+    let src = GpuBuffer::<Queued>::with_flags(&src_ctx, 64, CL_MEM_HOST_READ_ONLY).unwrap().launch().into_ready(marker);
+    // enqueue_write wäre auf dem Ziel verboten, die Kopie entsteht per COPY_HOST_PTR
+    let copy = src.copy_to_context(&src_queue, &dst_ctx, &dst_queue).unwrap();
+    assert_eq!(copy.flags(), CL_MEM_HOST_READ_ONLY);
+}
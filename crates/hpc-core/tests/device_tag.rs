@@ -0,0 +1,27 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{flush_csv_to, load_csv, log_transfer, reset, set_current_device, start, Dir};
+
+mod common;
+
+#[test]
+fn records_carry_the_device_of_their_thread() {
+    reset();
+    log_transfer(0, 10, 64, Dir::H2D);
+    std::thread::spawn(|| {
+        set_current_device(2);
+        let tok = start(Dir::Kernel, 0);
+        // Token nimmt das Gerät beim Start mit, nicht beim Abschluss
+        set_current_device(3);
+        tok.finish();
+    })
+    .join()
+    .unwrap();
+
+    let dir = common::temp_dir("device");
+    flush_csv_to(&dir);
+    let devices: Vec<u16> = load_csv(dir.join("memtrace.csv")).unwrap().iter().map(|r| r.device).collect();
+    assert_eq!(devices, [0, 2]);
+
+    reset();
+}
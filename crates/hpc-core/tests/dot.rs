@@ -0,0 +1,43 @@
+#![cfg(all(feature = "memtrace", feature = "test-util"))]
+
+use hpc_core::memtracer::{flush_dot, log_transfer, reset, reset_clock, set_clock, start, Dir};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+mod common;
+
+// Record über die normale Token-API, Zeiten aus der Test-Uhr
+fn traced(ticks: &AtomicU64, tx: u64, s: u64, e: u64, bytes: usize, dir: Dir) {
+    ticks.store(s, Ordering::Relaxed);
+    let tok = start(dir, bytes).tx(tx);
+    ticks.store(e, Ordering::Relaxed);
+    tok.finish();
+}
+
+#[test]
+fn transactions_become_chained_clusters() {
+    reset();
+    let ticks = Arc::new(AtomicU64::new(0));
+    let t = Arc::clone(&ticks);
+    set_clock(Box::new(move || t.load(Ordering::Relaxed)));
+    traced(&ticks, 7, 0, 10, 1024, Dir::H2D);
+    traced(&ticks, 9, 5, 8, 512, Dir::H2D);
+    traced(&ticks, 7, 10, 40, 0, Dir::Kernel);
+    log_transfer(12, 13, 64, Dir::H2D); // ohne tx_id: nicht im Graphen
+    traced(&ticks, 7, 40, 45, 1024, Dir::D2H);
+    reset_clock();
+
+    let dir = common::temp_dir("dot");
+    let path = dir.join("memtrace.dot");
+    flush_dot(&path).unwrap();
+    let dot = std::fs::read_to_string(&path).unwrap();
+
+    assert!(dot.starts_with("digraph memtrace {"));
+    assert!(dot.contains("label=\"tx 7 (45 µs)\";"));
+    assert!(dot.contains("tx7_1 [label=\"KRN\\n0 B\\n30 µs\"];"), "{dot}");
+    assert!(dot.contains("tx7_0 -> tx7_1;") && dot.contains("tx7_1 -> tx7_2;"));
+    assert!(dot.contains("subgraph cluster_tx9") && !dot.contains("tx9_0 ->"));
+    assert_eq!(dot.matches("[label=").count(), 4);
+
+    reset();
+}
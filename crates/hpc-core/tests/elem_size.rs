@@ -0,0 +1,61 @@
+use hpc_core::{elem_count, ClError, GpuBuffer, Queued};
+use opencl3::{
+    command_queue::CommandQueue,
+    memory::{CL_MEM_HOST_READ_ONLY, CL_MEM_HOST_WRITE_ONLY, CL_MEM_READ_ONLY},
+};
+
+mod common;
+use common::gpu_context;
+
+#[test]
+fn elem_count_checks_alignment_without_a_device() {
+    assert_eq!(elem_count::<f32>(16).unwrap(), 4);
+    assert_eq!(elem_count::<f32>(0).unwrap(), 0);
+    assert_eq!(elem_count::<u8>(7).unwrap(), 7);
+    assert_eq!(elem_count::<[f64; 3]>(48).unwrap(), 2);
+    assert!(matches!(elem_count::<f32>(7), Err(ClError::SizeNotAligned { bytes: 7, elem: 4 })));
+    assert!(matches!(elem_count::<u64>(12), Err(ClError::SizeNotAligned { bytes: 12, elem: 8 })));
+    // Nullgröße: nur 0 Bytes passen
+    assert_eq!(elem_count::<()>(0).unwrap(), 0);
+    assert!(matches!(elem_count::<()>(1), Err(ClError::SizeNotAligned { bytes: 1, elem: 0 })));
+}
+
+#[test]
+fn seven_byte_f32_buffer_is_rejected() {
+    let Some((_, context)) = gpu_context() else { return };
+
+    match GpuBuffer::<Queued>::new_typed::<f32>(&context, 7) {
+        Err(ClError::SizeNotAligned { bytes: 7, elem: 4 }) => {}
+        other => panic!("expected SizeNotAligned, got {:?}", other.map(|b| b.len())),
+    }
+}
+
+#[test]
+fn buffer_above_max_alloc_is_rejected() {
+    let Some((device, context)) = gpu_context() else { return };
+    let max = device.max_mem_alloc_size().unwrap();
+
+    match GpuBuffer::<Queued>::new(&context, max as usize + 1) {
+        Err(ClError::AllocTooLarge { requested, max: m }) => assert_eq!((requested as u64, m), (max + 1, max)),
+        other => panic!("expected AllocTooLarge, got {:?}", other.map(|b| b.len())),
+    }
+}
+
+#[test]
+fn host_access_flags_are_checked_before_enqueue() {
+    let Some((device, context)) = gpu_context() else { return };
+    let queue = CommandQueue::create(&context, device.id(), 0).unwrap();
+    let data = [1u8; 64];
+
+    let host_read_only = GpuBuffer::<Queued>::with_flags(&context, 64, CL_MEM_HOST_READ_ONLY).unwrap();
+    match host_read_only.enqueue_write(&queue, &data) {
+        Err(ClError::FlagViolation { op: "enqueue_write", flags }) => assert_eq!(flags, CL_MEM_HOST_READ_ONLY),
+        other => panic!("expected FlagViolation, got {:?}", other.map(|(b, _)| b.len())),
+    }
+
+    // Kernel-Eingabe: Host schreibt, liest aber nie zurück
+    let input = GpuBuffer::<Queued>::with_flags(&context, 64, CL_MEM_READ_ONLY | CL_MEM_HOST_WRITE_ONLY).unwrap();
+    let (input, g) = input.enqueue_write(&queue, &data).unwrap();
+    let input = input.into_ready(g);
+    assert!(matches!(input.read_to_vec::<u8>(&queue), Err(ClError::FlagViolation { op: "read_to_vec", .. })));
+}
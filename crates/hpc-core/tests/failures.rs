@@ -0,0 +1,20 @@
+#![cfg(feature = "metrics")]
+
+use hpc_core::metrics::{failures, record, record_failure, summary_to};
+use std::time::Instant;
+
+#[test]
+fn failures_are_counted_and_reported() {
+    record("enqueue_write", Instant::now());
+    record_failure("enqueue_write");
+    record_failure("enqueue_write");
+    record_failure("kernel_launch");
+    assert_eq!(failures(), vec![("enqueue_write", 2), ("kernel_launch", 1)]);
+
+    let mut out = Vec::new();
+    summary_to(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("failed enqueue_write") && out.contains("2   (1 ok)"), "{out}");
+    // summary leert die Zähler
+    assert!(failures().is_empty());
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{flush_all, flush_csv_to, log_transfer, reset, trace_abort, Dir, FlushConfig};
+
+mod common;
+
+#[test]
+fn one_pass_matches_single_flushes() {
+    reset();
+    log_transfer(10, 20, 4096, Dir::H2D);
+    log_transfer(15, 40, 0, Dir::Kernel);
+    trace_abort(1, "conflict", 2, 64, "stm");
+    log_transfer(45, 60, 4096, Dir::D2H);
+
+    let (single, all) = (common::temp_dir("single"), common::temp_dir("all"));
+    flush_csv_to(&single);
+    #[cfg(feature = "json")]
+    {
+        hpc_core::memtracer::flush_json(single.join("memtrace.json")).unwrap();
+        hpc_core::memtracer::flush_chrome_trace(single.join("memtrace_chrome.json")).unwrap();
+    }
+    flush_all(FlushConfig::everything(all.to_path_buf())).unwrap();
+
+    // memtrace_abort.csv fehlt: Zeilenreihenfolge folgt der HashMap
+    for name in ["memtrace.csv", "memtrace_summary.txt", "memtrace.json", "memtrace_chrome.json"] {
+        let a = std::fs::read(single.join(name)).ok();
+        assert_eq!(a, std::fs::read(all.join(name)).ok(), "{name}");
+    }
+    assert!(std::fs::read_to_string(all.join("memtrace_abort.csv")).unwrap().contains("stm,conflict,1,"));
+
+    reset();
+}
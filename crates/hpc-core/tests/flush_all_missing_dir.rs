@@ -0,0 +1,20 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{flush_all, flush_csv_to, log_transfer, reset, Dir, FlushConfig};
+
+mod common;
+
+// eigene Binary neben flush_all.rs: beide leeren und füllen das globale LOG
+#[test]
+fn missing_dir_is_an_error_not_a_panic() {
+    reset();
+    log_transfer(10, 20, 4096, Dir::H2D);
+    let parent = common::temp_dir("missing");
+    let missing = parent.join("does_not_exist");
+
+    // flush_csv_to meldet den Fehler nur, flush_all gibt ihn zurück
+    flush_csv_to(&missing);
+    assert!(flush_all(FlushConfig::new(&missing)).is_err());
+
+    reset();
+}
@@ -0,0 +1,25 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{flush_csv_append_to, load_csv, log_transfer, reset, Dir};
+
+mod common;
+
+#[test]
+fn append_flush_writes_each_record_once() {
+    let dir = common::temp_dir("append");
+    reset();
+
+    log_transfer(10, 20, 64, Dir::H2D);
+    log_transfer(20, 30, 64, Dir::D2H);
+    flush_csv_append_to(&dir);
+    // ohne reset(): nur der neue Record darf hinzukommen
+    log_transfer(30, 40, 64, Dir::H2D);
+    flush_csv_append_to(&dir);
+    flush_csv_append_to(&dir);
+
+    let log = load_csv(dir.join("memtrace.csv")).unwrap();
+    let starts: Vec<u64> = log.iter().map(|r| r.t_start_us).collect();
+    assert_eq!(starts, [10, 20, 30]);
+
+    reset();
+}
@@ -0,0 +1,14 @@
+#![cfg(feature = "gl-interop")]
+
+use hpc_core::{GlReleased, GpuBuffer};
+
+mod common;
+
+#[test]
+fn gl_buffer_needs_a_shared_context() {
+    let Some((_, context)) = common::gpu_context() else { return };
+
+    // gewöhnlicher Kontext ohne CL_GL_CONTEXT_KHR: OpenCL lehnt jedes GL-Objekt ab
+    let res: Result<GpuBuffer<GlReleased>, _> = GpuBuffer::from_gl_buffer(&context, 1);
+    assert!(res.is_err());
+}
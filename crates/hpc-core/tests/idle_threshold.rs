@@ -0,0 +1,30 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{
+    flush_csv_to, idle_exceeds_threshold, log_transfer, reset, set_idle_warn_threshold, Dir,
+};
+
+mod common;
+
+#[test]
+fn idle_above_threshold_warns_and_flags() {
+    reset();
+    // 100 µs Arbeit, 300 µs Lücke, 100 µs Arbeit: 60 % idle von 500 µs
+    log_transfer(1_000, 1_100, 4096, Dir::H2D);
+    log_transfer(1_400, 1_500, 0, Dir::Kernel);
+    assert!(!idle_exceeds_threshold(), "default off");
+
+    let dir = common::temp_dir("idle");
+
+    set_idle_warn_threshold(Some(0.5));
+    assert!(idle_exceeds_threshold());
+    flush_csv_to(&dir);
+    let summary = std::fs::read_to_string(dir.join("memtrace_summary.txt")).unwrap();
+    assert!(summary.contains("wall_us: 500\n"), "{summary}");
+    assert!(summary.contains("warning: GPU idle 60.0% of wall time (300 of 500 µs)"), "{summary}");
+
+    set_idle_warn_threshold(Some(0.75));
+    assert!(!idle_exceeds_threshold());
+    set_idle_warn_threshold(None);
+    reset();
+}
@@ -0,0 +1,24 @@
+use bytemuck::cast_slice;
+use hpc_core::{ClError, GpuBuffer, Queued, SafeKernel};
+use opencl3::{command_queue::CommandQueue, program::Program};
+
+mod common;
+
+const SRC: &str = "__kernel void twice(__global float* x) { x[get_global_id(0)] *= 2.0f; }";
+
+#[test]
+fn launch_after_binds_the_buffer_for_one_launch() {
+    let Some((device, context)) = common::gpu_context() else { return };
+    let queue = CommandQueue::create(&context, device.id(), 0).unwrap();
+    let program = Program::create_and_build_from_source(&context, SRC, "-cl-kernel-arg-info").unwrap();
+    let mut kernel = SafeKernel::new(&program, "twice").unwrap();
+    let data = [1.0f32, 2.0, 3.0, 4.0];
+
+    let (buf, g) = GpuBuffer::<Queued>::new(&context, 16).unwrap().enqueue_write(&queue, cast_slice(&data)).unwrap();
+    let (buf, g) = kernel.launch_after::<f32>(&queue, &[4], None, 0, buf, g).unwrap();
+    let buf = buf.into_ready(g);
+    assert_eq!(buf.read_to_vec::<f32>(&queue).unwrap(), [2.0, 4.0, 6.0, 8.0]);
+
+    // die Bindung endet mit dem Launch: der Kernel hält den Puffer nicht fest
+    assert!(matches!(kernel.launch(&queue, &[4], None), Err(ClError::ArgUnset(0))));
+}
@@ -0,0 +1,15 @@
+use hpc_core::{GpuBuffer, Queued};
+use opencl3::memory::ClMem;
+
+mod common;
+use common::gpu_context;
+
+#[test]
+fn raw_guard_derefs_to_the_buffer_handle() {
+    let Some((_, context)) = gpu_context() else { return };
+    let buf = GpuBuffer::<Queued>::new(&context, 64).unwrap();
+
+    let guard = buf.as_raw_guard();
+    assert_eq!(*guard, buf.raw().get());
+    assert!(!guard.is_null());
+}
@@ -0,0 +1,21 @@
+use hpc_core::{GpuBuffer, Queued, Ready};
+use opencl3::command_queue::CommandQueue;
+
+mod common;
+
+#[test]
+fn read_after_write_sees_the_written_data() {
+    let Some((device, context)) = common::gpu_context() else { return };
+    let (upload, download) = (
+        CommandQueue::create(&context, device.id(), 0).unwrap(),
+        CommandQueue::create(&context, device.id(), 0).unwrap(),
+    );
+    let data: Vec<u8> = (0..=255).collect();
+    let mut back = vec![0u8; data.len()];
+
+    // zwei Queues: nur die Abhängigkeit ordnet den Read hinter den Write
+    let (buf, g) = GpuBuffer::<Queued>::new(&context, data.len()).unwrap().enqueue_write(&upload, &data).unwrap();
+    let (buf, g) = buf.enqueue_read_after(&download, &mut back, g).unwrap();
+    let _: GpuBuffer<Ready> = buf.into_ready(g);
+    assert_eq!(back, data);
+}
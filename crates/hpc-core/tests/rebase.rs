@@ -0,0 +1,56 @@
+#![cfg(all(feature = "memtrace", feature = "test-util"))]
+
+use hpc_core::memtracer::{now_us, rebase_timebase, reset, reset_clock, set_clock, start, trace_abort, Dir, LOG};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+fn spans() -> Vec<(u64, u64)> {
+    LOG.lock().unwrap().iter().map(|r| (r.t_start_us, r.t_end_us)).collect()
+}
+
+#[test]
+fn rebase_restarts_at_zero_and_optionally_shifts() {
+    reset();
+    let ticks = Arc::new(AtomicU64::new(1_000));
+    let t = Arc::clone(&ticks);
+    set_clock(Box::new(move || t.load(Ordering::Relaxed)));
+
+    let tok = start(Dir::H2D, 64);
+    ticks.store(1_100, Ordering::Relaxed);
+    tok.finish(); // 1000..1100
+
+    // Phase 2 ab 2000: alte Records bleiben unverändert
+    ticks.store(2_000, Ordering::Relaxed);
+    rebase_timebase(false);
+    assert_eq!(now_us(), 0);
+    let tok = start(Dir::D2H, 64);
+    ticks.store(2_050, Ordering::Relaxed);
+    tok.finish(); // 0..50
+    assert_eq!(spans(), vec![(1_000, 1_100), (0, 50)]);
+
+    // Phase 3 ab 2030, mit Verschieben: alles um 30 µs zurück, 0..50 → 0..20
+    ticks.store(2_030, Ordering::Relaxed);
+    rebase_timebase(true);
+    assert_eq!(spans(), vec![(970, 1_070), (0, 20)]);
+    ticks.store(2_040, Ordering::Relaxed);
+    assert_eq!(now_us(), 10);
+
+    // offener Token über einen Rebase hinweg: Start klemmt auf 0, Ende 20
+    let tok = start(Dir::H2D, 64);
+    ticks.store(2_100, Ordering::Relaxed);
+    rebase_timebase(false);
+    ticks.store(2_120, Ordering::Relaxed);
+    tok.finish();
+    assert_eq!(spans().last(), Some(&(0, 20)));
+
+    // Aborts auf derselben Zeitbasis: t = now_us, beim Verschieben mitgezogen
+    ticks.store(2_130, Ordering::Relaxed);
+    trace_abort(1, "conflict", 0, 0, "stm");
+    assert_eq!(spans().last(), Some(&(30, 30)));
+    ticks.store(2_140, Ordering::Relaxed);
+    rebase_timebase(true);
+    assert_eq!(spans().last(), Some(&(0, 0)));
+
+    reset_clock();
+    reset();
+}
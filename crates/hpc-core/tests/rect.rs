@@ -0,0 +1,28 @@
+use hpc_core::{ClError, Rect};
+
+fn invalid(rect: &Rect, buffer_len: usize, host_len: usize) -> String {
+    match rect.validate(buffer_len, host_len) {
+        Err(ClError::InvalidRect(msg)) => msg,
+        other => panic!("expected InvalidRect, got {other:?}"),
+    }
+}
+
+#[test]
+fn rect_geometry_is_checked_against_both_sides() {
+    // 4 Zeilen à 16 Byte aus einem 32 Byte breiten Buffer
+    let rect = Rect { region: [16, 4, 1], buffer_row_pitch: 32, ..Rect::default() };
+    rect.validate(32 * 3 + 16, 64).unwrap();
+    assert!(invalid(&rect, 32 * 3 + 15, 64).starts_with("buffer region ends at byte 112"));
+    assert!(invalid(&rect, 128, 63).starts_with("host region ends at byte 64"));
+    assert!(invalid(&Rect { region: [16, 0, 1], ..rect }, 128, 64).starts_with("empty region"));
+    assert!(invalid(&Rect { buffer_row_pitch: 8, ..rect }, 128, 64).contains("smaller than region"));
+}
+
+#[test]
+fn overflowing_rect_is_an_error() {
+    let huge = Rect { region: [1, 1, 1], buffer_origin: [0, 0, usize::MAX], buffer_slice_pitch: 2, ..Rect::default() };
+    assert!(invalid(&huge, usize::MAX, 1).contains("overflows usize"));
+
+    let wide = Rect { region: [usize::MAX, 2, 1], ..Rect::default() };
+    assert!(invalid(&wide, usize::MAX, usize::MAX).contains("overflows usize"));
+}
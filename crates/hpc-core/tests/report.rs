@@ -0,0 +1,39 @@
+#![cfg(feature = "metrics")]
+
+use hpc_core::{
+    metrics::record_duration,
+    report::{combined_to, print_efficiency_to, Efficiency},
+};
+
+#[test]
+fn combined_table_goes_to_any_writer() {
+    record_duration("report_op", 40);
+    record_duration("report_op", 60);
+
+    let mut out = Vec::new();
+    combined_to(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.starts_with("── combined report ──\n"), "{out}");
+    let row = out.lines().find(|l| l.starts_with("report_op ")).expect("op row");
+    let cols: Vec<&str> = row.split_whitespace().collect();
+    // count, mean µs; ohne Bytes weder Volumen noch Bandbreite
+    assert_eq!(cols[1..3], ["2", "50"], "{row}");
+    assert_eq!(cols[4..], ["-", "-"], "{row}");
+}
+
+#[test]
+fn efficiency_table_flags_slow_directions() {
+    let effs = [
+        Efficiency { dir: "H2D", measured_gbps: 12.0, peak_gbps: 16.0, below_threshold: false },
+        Efficiency { dir: "D2H", measured_gbps: 4.0, peak_gbps: 16.0, below_threshold: true },
+    ];
+    let mut out = Vec::new();
+    print_efficiency_to(&mut out, &effs).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    let h2d = out.lines().find(|l| l.starts_with("H2D")).unwrap();
+    let d2h = out.lines().find(|l| l.starts_with("D2H")).unwrap();
+    assert!(h2d.ends_with("75.0%"), "{out}");
+    assert!(d2h.contains("25.0%") && d2h.contains("below threshold"), "{out}");
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{current_roi, flush_all, flush_csv_append_to, load_csv, log_transfer, reset, roi_begin, roi_end, Dir, FlushConfig, RoiGuard};
+
+mod common;
+
+#[test]
+fn roi_only_keeps_tagged_records() {
+    reset();
+    log_transfer(0, 10, 100, Dir::H2D);
+    roi_begin("solve");
+    log_transfer(10, 20, 200, Dir::H2D);
+    {
+        let _inner = RoiGuard::new("inner");
+        log_transfer(20, 30, 0, Dir::Kernel);
+    }
+    roi_end();
+    log_transfer(30, 40, 300, Dir::D2H);
+
+    let dir = common::temp_dir("roi");
+    flush_all(FlushConfig { roi_only: true, ..FlushConfig::new(dir.to_path_buf()) }).unwrap();
+
+    let csv = std::fs::read_to_string(dir.join("memtrace.csv")).unwrap();
+    let rows: Vec<&str> = csv.lines().skip(1).collect();
+    assert_eq!(rows.len(), 2, "{csv}");
+    assert!(rows[0].starts_with("1,10,20,200,") && rows[0].ends_with("roi=solve,0"));
+    assert!(rows[1].ends_with("roi=inner,0"));
+    let summary = std::fs::read_to_string(dir.join("memtrace_summary.txt")).unwrap();
+    assert!(summary.contains("bytes_h2d: 200\n"), "{summary}");
+
+    // die gefilterte CSV setzt die Marke nicht: append schreibt noch alle
+    let rest = common::temp_dir("roi_rest");
+    flush_csv_append_to(&rest);
+    assert_eq!(load_csv(rest.join("memtrace.csv")).unwrap().len(), 4);
+
+    reset();
+}
+
+#[test]
+fn regions_are_per_thread() {
+    let _outer = RoiGuard::new("main");
+    std::thread::spawn(|| {
+        assert_eq!(current_roi(), None);
+        let _inner = RoiGuard::new("worker");
+        assert_eq!(current_roi().as_deref(), Some("worker"));
+    })
+    .join()
+    .unwrap();
+    assert_eq!(current_roi().as_deref(), Some("main"));
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{log_transfer, reset, start_sampler, stop_sampler, Dir};
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn sampler_writes_rows_until_stopped() {
+    reset();
+    let dir = common::temp_dir("sampled");
+    let path = dir.join("memtrace_sampled.csv");
+    // Intervall länger als der Test: nur die Zeilen bei Start und Stop,
+    // die zusammenfallen, wenn der Thread erst nach dem Stop anläuft
+    start_sampler(Duration::from_secs(3600), &path).unwrap();
+    log_transfer(0, 10, 64, Dir::H2D);
+    stop_sampler().unwrap();
+
+    let csv = std::fs::read_to_string(&path).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows[0], "t_us,inflight_buffers,log_len,alloc_bytes");
+    assert!(rows.len() >= 2, "{csv}");
+    assert!(rows.last().unwrap().split(',').nth(2) == Some("1"), "{csv}");
+
+    // gestoppt: keine weiteren Zeilen
+    std::thread::sleep(Duration::from_millis(10));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), csv);
+    stop_sampler().unwrap();
+
+    reset();
+}
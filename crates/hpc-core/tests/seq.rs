@@ -0,0 +1,41 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{flush_csv_to, load_csv, log_transfer, reset, trace_abort, with_records, Dir};
+
+mod common;
+
+#[test]
+fn seq_is_gap_free_across_threads_and_kinds() {
+    reset();
+    let threads: Vec<_> = (0..4)
+        .map(|t| {
+            std::thread::spawn(move || {
+                for i in 0..50 {
+                    if i % 5 == 0 {
+                        trace_abort(i, "conflict", 0, 8, "stm");
+                    } else {
+                        log_transfer(i, i + 1, 64, Dir::H2D);
+                    }
+                }
+                t
+            })
+        })
+        .collect();
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    // seq entspricht der Position im Log, auch bei gleichen Zeitstempeln
+    with_records(|log| assert!(log.iter().enumerate().all(|(i, r)| r.seq == i as u64)));
+
+    let dir = common::temp_dir("seq");
+    flush_csv_to(&dir);
+    let loaded = load_csv(dir.join("memtrace.csv")).unwrap();
+    // Aborts fehlen in memtrace.csv: Lücken genau dort
+    assert_eq!(loaded.len(), 160);
+    assert!(loaded.windows(2).all(|w| w[0].seq < w[1].seq));
+
+    reset();
+    log_transfer(0, 1, 1, Dir::D2H);
+    with_records(|log| assert_eq!(log[0].seq, 0));
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::{flush_csv_to, log_transfer, reset, slowest_transfers, trace_abort, with_records, Dir};
+
+mod common;
+
+#[test]
+fn slowest_records_longest_first() {
+    reset();
+    log_transfer(0, 900, 4096, Dir::H2D); // erster Transfer hängt
+    log_transfer(900, 910, 4096, Dir::H2D);
+    log_transfer(910, 1010, 0, Dir::Kernel);
+    trace_abort(1, "conflict", 0, 1, "stm");
+    log_transfer(1010, 1110, 4096, Dir::D2H);
+
+    let top: Vec<(u64, u64)> = slowest_transfers(3).iter().map(|r| (r.t_start_us, r.duration_us())).collect();
+    assert_eq!(top, vec![(0, 900), (910, 100), (1010, 100)]);
+    assert_eq!(slowest_transfers(10).len(), 4);
+    assert_eq!(with_records(|r| r.iter().map(|r| r.bytes).sum::<usize>()), 3 * 4096);
+
+    let dir = common::temp_dir("slowest");
+    flush_csv_to(&dir);
+    let summary = std::fs::read_to_string(dir.join("memtrace_summary.txt")).unwrap();
+    assert!(summary.contains("slowest: H2D 4.00 KiB 900 µs @0, KRN 0 B 100 µs @910, D2H 4.00 KiB 100 µs @1010\n"), "{summary}");
+    reset();
+}
@@ -0,0 +1,21 @@
+#![cfg(feature = "metrics")]
+
+use hpc_core::{metrics, AbortCounters};
+
+#[test]
+fn retries_stay_out_of_the_latency_samples() {
+    let counters = AbortCounters::new();
+    for retries in [0, 0, 1, 3] {
+        counters.commit_after(retries);
+    }
+
+    // Snapshot, CSV-Dump und Baseline sehen nur µs-Latenzen
+    assert!(metrics::snapshot().ops.iter().all(|op| op.name != "stm_retry"));
+
+    let mut out = Vec::new();
+    metrics::summary_to(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let line = out.lines().find(|l| l.starts_with("stm_retry")).expect("retry line");
+    assert!(line.contains("mean= 1.00 retries/commit") && line.ends_with("p95=  1"), "{line}");
+    assert!(metrics::RETRIES.lock().unwrap().is_empty());
+}
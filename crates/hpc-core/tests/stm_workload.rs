@@ -0,0 +1,30 @@
+use hpc_core::stm::{run_stm, Conflict, StmConfig, StmMode};
+
+fn config(seed: u64) -> StmConfig {
+    StmConfig {
+        threads: 2,
+        conflict: Conflict::High,
+        mode: StmMode::Ops(2_000),
+        seed,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn uniform_run_is_deterministic_and_complete() {
+    let a = run_stm(config(7));
+    let b = run_stm(config(7));
+
+    // ohne Retry-Budget endet jede Op mit genau einem Commit oder Abort
+    assert_eq!(a.ops, 2_000);
+    assert_eq!(a.counters.commits + a.counters.aborts, a.ops);
+    assert_eq!(a.counters, b.counters);
+}
+
+#[test]
+fn retry_budget_bounds_aborts_per_op() {
+    let res = run_stm(StmConfig { retry_budget: Some(2), ..config(3) });
+    let c = &res.counters;
+    assert_eq!(c.commits + c.gave_up, res.ops);
+    assert!(c.aborts <= res.ops * 3);
+}
@@ -0,0 +1,44 @@
+use hpc_core::{overlap_bytes, ClError, GpuBuffer, Ready};
+use opencl3::command_queue::CommandQueue;
+
+mod common;
+
+#[test]
+fn views_overlap_by_their_byte_ranges() {
+    let Some((device, context)) = common::gpu_context() else { return };
+    let queue = CommandQueue::create(&context, device.id(), 0).unwrap();
+    let align = (device.mem_base_addr_align().unwrap() / 8) as usize;
+
+    let parent = GpuBuffer::<Ready>::from_slice(&context, &queue, &vec![0u8; 4 * align]).unwrap();
+    let a = parent.sub_buffer(0, 2 * align).unwrap();
+    let b = parent.sub_buffer(align, 2 * align).unwrap();
+    assert_eq!((a.sub_offset(), b.sub_offset()), (Some(0), Some(align)));
+    assert_eq!(overlap_bytes(&*a, &*b), align);
+    assert_eq!(overlap_bytes(&parent, &*b), 2 * align);
+
+    let other = GpuBuffer::<Ready>::from_slice(&context, &queue, &vec![0u8; align]).unwrap();
+    assert_eq!(overlap_bytes(&other, &*a), 0);
+}
+
+#[test]
+fn split_at_halves_and_rejects_empty_halves() {
+    let Some((device, context)) = common::gpu_context() else { return };
+    let queue = CommandQueue::create(&context, device.id(), 0).unwrap();
+    let align = (device.mem_base_addr_align().unwrap() / 8) as usize;
+    let len = 3 * align;
+
+    let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+    let parent = GpuBuffer::<Ready>::from_slice(&context, &queue, &data).unwrap();
+    let (head, tail) = parent.split_at(align).unwrap();
+    assert_eq!((head.len(), tail.len()), (align, 2 * align));
+    assert_eq!((head.sub_offset(), tail.sub_offset()), (Some(0), Some(align)));
+    assert_eq!(overlap_bytes(&*head, &*tail), 0);
+    assert_eq!(tail.read_to_vec::<u8>(&queue).unwrap(), &data[align..]);
+
+    for offset in [0, len, len + align] {
+        match parent.split_at(offset) {
+            Err(ClError::SplitOutOfRange { offset: o, len: l }) => assert_eq!((o, l), (offset, len)),
+            other => panic!("split_at({offset}): expected SplitOutOfRange, got {:?}", other.map(|_| ())),
+        }
+    }
+}
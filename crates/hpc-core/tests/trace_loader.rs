@@ -0,0 +1,116 @@
+#![cfg(feature = "memtrace")]
+
+use hpc_core::memtracer::load_csv;
+use proptest::prelude::*;
+
+mod common;
+
+const VALID: &str = "t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase\n\
+                     10,20,4096,H2D,10,,Transfer\n\
+                     25,40,0,Kernel,5,tok,Kernel\n";
+
+// Eigenes Verzeichnis pro Fall, Tests laufen parallel
+fn load(content: &[u8]) -> hpc_core::Result<Vec<hpc_core::memtracer::Record>> {
+    let dir = common::temp_dir("loader");
+    let path = dir.join("memtrace.csv");
+    std::fs::write(&path, content).unwrap();
+    load_csv(&path)
+}
+
+#[test]
+fn malformed_rows_are_errors() {
+    let rows = [
+        "10,20,4096,H2D,10,",                 // fehlende Spalten
+        "ten,20,4096,H2D,10,,Transfer",       // nicht-numerischer Zeitstempel
+        "10,20,-1,H2D,10,,Transfer",          // negative Bytes
+        "10,20,4096,UP,10,,Transfer",         // unbekannte Richtung
+        "10,20,4096,H2D,10,,",                // leere Phase
+        "10,20,4096,H2D,10,,Trnasfer",        // unbekannte Phase
+        "10,20,4096,H2D,10,a,b,Transfer",     // Komma im Token
+        "10,20,99999999999999999999,H2D,10,,Transfer",
+    ];
+    for row in rows {
+        assert!(load(row.as_bytes()).is_err(), "accepted: {row}");
+    }
+}
+
+#[test]
+fn valid_trace_loads() {
+    let log = load(VALID.as_bytes()).unwrap();
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[1].abort_token.as_deref(), Some("tok"));
+}
+
+#[test]
+fn only_known_phases_load() {
+    let csv = "t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase\n\
+               10,10,0,Kernel,0,,Barrier\n";
+    assert_eq!(load(csv.as_bytes()).unwrap()[0].phase.as_str(), "Barrier");
+    match load(b"10,20,4096,H2D,10,,map") {
+        Err(hpc_core::ClError::TraceParse { line: 1, reason }) => assert_eq!(reason, "unknown phase: 'map'"),
+        other => panic!("expected TraceParse, got {:?}", other.map(|l| l.len())),
+    }
+}
+
+#[test]
+fn tags_column_round_trips() {
+    let csv = "t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase,tags\n\
+               10,20,4096,H2D,10,,Transfer,tile=3;iter=17\n\
+               25,40,0,Kernel,5,,Kernel,\n";
+    let log = load(csv.as_bytes()).unwrap();
+    assert_eq!(log[0].tags, [("tile".into(), "3".into()), ("iter".into(), "17".into())]);
+    assert!(log[1].tags.is_empty());
+    assert!(load(b"10,20,4096,H2D,10,,Transfer,tile").is_err());
+}
+
+#[test]
+fn separators_in_tags_round_trip() {
+    use hpc_core::memtracer::{flush_csv_to, reset, start, Dir};
+
+    reset();
+    start(Dir::H2D, 64).tag("a,b", "x;y=z").tag("pct", "100%\n").finish();
+    let dir = common::temp_dir("loader_tags");
+    flush_csv_to(&dir);
+    let log = load_csv(dir.join("memtrace.csv")).unwrap();
+
+    assert_eq!(log[0].tags, [("a,b".into(), "x;y=z".into()), ("pct".into(), "100%\n".into())]);
+    assert!(load(b"10,20,4096,H2D,10,,Transfer,k=%zz").is_err());
+}
+
+#[test]
+fn device_column_round_trips() {
+    let csv = "t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase,tags,device\n\
+               10,20,4096,H2D,10,,Transfer,,1\n";
+    assert_eq!(load(csv.as_bytes()).unwrap()[0].device, 1);
+    assert!(load(b"10,20,4096,H2D,10,,Transfer,,gpu1").is_err());
+}
+
+#[test]
+fn seq_column_round_trips() {
+    let csv = "# run=1\n\
+               seq,t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase,tags,device\n\
+               4,10,20,4096,H2D,10,,Transfer,,0\n\
+               6,25,40,0,Kernel,5,,Kernel,,0\n";
+    let seqs: Vec<u64> = load(csv.as_bytes()).unwrap().iter().map(|r| r.seq).collect();
+    assert_eq!(seqs, [4, 6]);
+    // ohne seq-Spalte: Reihenfolge in der Datei
+    assert_eq!(load(VALID.as_bytes()).unwrap()[1].seq, 1);
+    assert!(load(b"seq,t_start_us,t_end_us,bytes,dir,idle_us,abort_token,phase,tags,device\nx,10,20,4096,H2D,10,,Transfer,,0").is_err());
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_bytes_never_panic(data in proptest::collection::vec(any::<u8>(), 0..512)) {
+        let _ = load(&data);
+    }
+
+    #[test]
+    fn csv_like_text_never_panics(text in "[0-9a-zA-Z,#\\-\r\n ]{0,256}") {
+        let _ = load(text.as_bytes());
+    }
+
+    #[test]
+    fn truncated_trace_never_panics(cut in 0..VALID.len()) {
+        let _ = load(&VALID.as_bytes()[..cut]);
+    }
+}
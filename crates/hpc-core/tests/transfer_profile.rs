@@ -0,0 +1,63 @@
+use hpc_core::{LinkFit, TransferProfile, TransferSample};
+
+mod common;
+
+#[test]
+fn fit_recovers_latency_and_bandwidth() {
+    // t = 10 µs + n / (1 GB/s), d.h. 1000 Bytes pro µs
+    let samples: Vec<(usize, f64)> = [4096, 65536, 1 << 20, 16 << 20]
+        .iter()
+        .map(|&n| (n, 10.0 + n as f64 / 1000.0))
+        .collect();
+    let fit = LinkFit::fit(&samples).unwrap();
+    assert!((fit.latency_us - 10.0).abs() < 1e-6, "{fit:?}");
+    assert!((fit.bandwidth_gbps - 1.0).abs() < 1e-9, "{fit:?}");
+    assert_eq!(fit.half_bandwidth_bytes(), 10_000);
+
+    // eine Größe oder fallende Zeiten: kein Fit
+    assert_eq!(LinkFit::fit(&[(4096, 5.0), (4096, 6.0)]), None);
+    assert_eq!(LinkFit::fit(&[(4096, 9.0), (8192, 5.0)]), None);
+    assert_eq!(LinkFit::fit(&[]), None);
+}
+
+#[test]
+fn csv_has_fit_comments_then_rows() {
+    let p = TransferProfile {
+        samples: vec![
+            TransferSample { bytes: 1000, h2d_us: 11.0, d2h_us: 12.0 },
+            TransferSample { bytes: 2000, h2d_us: 12.0, d2h_us: 14.0 },
+        ],
+        h2d: LinkFit::fit(&[(1000, 11.0), (2000, 12.0)]),
+        d2h: None,
+    };
+    let dir = common::temp_dir("profile");
+    let path = dir.join("profile.csv");
+    p.write_csv(&path).unwrap();
+    let csv = std::fs::read_to_string(&path).unwrap();
+
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines, [
+        "# h2d_latency_us=10.000",
+        "# h2d_bandwidth_gbps=1.000",
+        "bytes,h2d_us,d2h_us",
+        "1000,11.000,12.000",
+        "2000,12.000,14.000",
+    ]);
+}
+
+#[test]
+fn table_goes_to_any_writer() {
+    let p = TransferProfile {
+        samples: vec![TransferSample { bytes: 1000, h2d_us: 11.0, d2h_us: 0.0 }],
+        h2d: None,
+        d2h: None,
+    };
+    let mut out = Vec::new();
+    p.print_to(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.starts_with("── transfer profile ──\n"), "{out}");
+    // D2H ohne Zeit: Rate "-" statt Division durch 0
+    assert!(out.lines().nth(2).unwrap().trim_end().ends_with(" -"), "{out}");
+    assert!(out.contains("H2D: no fit") && out.contains("D2H: no fit"), "{out}");
+}
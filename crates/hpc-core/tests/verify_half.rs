@@ -0,0 +1,17 @@
+#![cfg(feature = "half")]
+
+use half::f16;
+use hpc_core::{verify, ClType};
+
+#[test]
+fn f16_elements_and_tolerance() {
+    assert_eq!(<f16 as ClType>::CL_NAME, "half");
+    assert_eq!(bytemuck::cast_slice::<f16, u8>(&[f16::ONE; 3]).len(), 6);
+
+    // 1.1 + 2.2 in f16 gerechnet weicht vom f32-Ergebnis um ~1 ULP ab
+    let got = [f16::from_f32(1.1) + f16::from_f32(2.2)];
+    let exp = [f16::from_f32(1.1f32 + 2.2)];
+    assert!(verify::all_close(&got, &exp));
+    assert_eq!(verify::first_mismatch(&[f16::from_f32(3.0)], &[f16::from_f32(3.1)], 2e-3), Some(0));
+    assert_eq!(verify::first_mismatch(&got, &[], 2e-3), Some(0));
+}
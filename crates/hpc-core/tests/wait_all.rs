@@ -0,0 +1,26 @@
+use hpc_core::{into_ready_all, GpuBuffer, Queued};
+use opencl3::command_queue::CommandQueue;
+
+mod common;
+
+#[test]
+fn empty_list_needs_no_device() {
+    assert!(into_ready_all(Vec::new()).unwrap().is_empty());
+}
+
+#[test]
+fn three_writes_become_ready_with_one_wait() {
+    let Some((device, context)) = common::gpu_context() else { return };
+    let queue = CommandQueue::create(&context, device.id(), 0).unwrap();
+
+    let data = [7u8; 64];
+    let bufs: Vec<_> = (1..=3usize)
+        .map(|k| {
+            let buf = GpuBuffer::<Queued>::new(&context, 16 * k).unwrap();
+            buf.enqueue_write(&queue, &data[..16 * k]).unwrap()
+        })
+        .collect();
+
+    let ready = into_ready_all(bufs).unwrap();
+    assert_eq!(ready.iter().map(|b| b.len()).collect::<Vec<_>>(), [16, 32, 48]);
+}